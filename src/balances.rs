@@ -0,0 +1,400 @@
+use crate::execution::{transaction_error_description, ErrorTables};
+use crate::rpc::{fetch_account_states, fetch_writable_addresses, simulate_transaction, RpcEndpoint, SimulateOptions};
+use crate::transaction::{Address, Transaction};
+use crate::{Error, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use serde_json::Value as json_Value;
+use std::str::FromStr;
+
+// Token accounts are: mint pubkey, owner pubkey, u64 amount, and then fields that do not concern balances.  Token-2022
+// accounts with extensions follow these with an account type, which is 2 for token accounts, and then the extensions.
+pub const TOKEN_ACCOUNT_SIZE : usize = 165;
+pub const TOKEN_ACCOUNT_MINT_OFFSET : usize = 0;
+pub const TOKEN_ACCOUNT_OWNER_OFFSET : usize = 32;
+pub const TOKEN_ACCOUNT_AMOUNT_OFFSET : usize = 64;
+pub const TOKEN_2022_ACCOUNT_TYPE_ACCOUNT : u8 = 2;
+
+// The state of an account, as far as balances are concerned
+#[derive(Clone)]
+pub struct AccountState
+{
+    pub lamports : u64,
+
+    // The program that owns the account
+    pub owner : Address,
+
+    pub data : Vec<u8>
+}
+
+// The balance of a token account
+#[derive(Clone)]
+pub struct TokenBalance
+{
+    pub mint : Address,
+
+    // The owner of the tokens, not the program that owns the account
+    pub owner : Address,
+
+    pub amount : u64
+}
+
+// The states of an account before and after a transaction executes; a state is None when the account does not exist
+pub struct BalanceChange
+{
+    pub address : Address,
+
+    pub before : Option<AccountState>,
+
+    pub after : Option<AccountState>
+}
+
+impl AccountState
+{
+    // Reads the state of an account as returned by an RPC node with base64 encoding, which is null if the account does
+    // not exist
+    pub fn from_json(v : &json_Value) -> Result<Option<Self>, Error>
+    {
+        if v.is_null() {
+            return Ok(None);
+        }
+
+        let invalid = || Error::rpc(&format!("Invalid account state: {}", v));
+
+        let lamports = v.get("lamports").and_then(|lamports| lamports.as_u64()).ok_or_else(invalid)?;
+
+        let owner = v
+            .get("owner")
+            .and_then(|owner| owner.as_str())
+            .and_then(|owner| Address::from_str(owner).ok())
+            .ok_or_else(invalid)?;
+
+        let data = match v.get("data").and_then(|data| data.as_array()).and_then(|data| data.first()) {
+            Some(json_Value::String(s)) => {
+                base64::decode(s).map_err(|e| Error::rpc(&format!("Invalid account data: {}", e)))?
+            },
+            _ => return Err(invalid())
+        };
+
+        Ok(Some(AccountState { lamports, owner, data }))
+    }
+
+    // Returns the balance of the account if it is a token account of the Token or Token-2022 program
+    pub fn token_balance(&self) -> Option<TokenBalance>
+    {
+        let owner = format!("{}", self.owner);
+
+        let is_token_account = if owner == TOKEN_PROGRAM_ID {
+            self.data.len() == TOKEN_ACCOUNT_SIZE
+        }
+        else if owner == TOKEN_2022_PROGRAM_ID {
+            // Token-2022 mints with extensions are padded to the size of a token account, and so are told apart by
+            // their account type
+            (self.data.len() == TOKEN_ACCOUNT_SIZE) ||
+                (self.data.get(TOKEN_ACCOUNT_SIZE) == Some(&TOKEN_2022_ACCOUNT_TYPE_ACCOUNT))
+        }
+        else {
+            false
+        };
+
+        if !is_token_account {
+            return None;
+        }
+
+        let address = |offset : usize| Address(self.data[offset..(offset + 32)].try_into().unwrap());
+
+        Some(TokenBalance {
+            mint : address(TOKEN_ACCOUNT_MINT_OFFSET),
+            owner : address(TOKEN_ACCOUNT_OWNER_OFFSET),
+            amount : u64::from_le_bytes(
+                self.data[TOKEN_ACCOUNT_AMOUNT_OFFSET..(TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)].try_into().unwrap()
+            )
+        })
+    }
+}
+
+impl BalanceChange
+{
+    pub fn lamports_before(&self) -> u64
+    {
+        self.before.as_ref().map(|state| state.lamports).unwrap_or(0)
+    }
+
+    pub fn lamports_after(&self) -> u64
+    {
+        self.after.as_ref().map(|state| state.lamports).unwrap_or(0)
+    }
+
+    pub fn lamport_delta(&self) -> i128
+    {
+        (self.lamports_after() as i128) - (self.lamports_before() as i128)
+    }
+
+    // Returns the token balances of the account before and after, if it was a token account before or after.  A
+    // token account that does not exist holds no tokens.
+    pub fn token_balances(&self) -> Option<(TokenBalance, TokenBalance)>
+    {
+        let before = self.before.as_ref().and_then(|state| state.token_balance());
+
+        let after = self.after.as_ref().and_then(|state| state.token_balance());
+
+        let empty = |balance : &TokenBalance| TokenBalance { amount : 0, ..balance.clone() };
+
+        match (before, after) {
+            (Some(before), Some(after)) => Some((before, after)),
+            (Some(before), None) => Some((before.clone(), empty(&before))),
+            (None, Some(after)) => Some((empty(&after), after)),
+            (None, None) => None
+        }
+    }
+
+    pub fn to_json(&self) -> json_Value
+    {
+        let token = match self.token_balances() {
+            Some((before, after)) => serde_json::json!({
+                "mint" : format!("{}", after.mint),
+                "owner" : format!("{}", after.owner),
+                "amount_before" : before.amount,
+                "amount_after" : after.amount,
+                "amount_delta" : delta_json((after.amount as i128) - (before.amount as i128))
+            }),
+            None => json_Value::Null
+        };
+
+        serde_json::json!({
+            "address" : format!("{}", self.address),
+            "exists_before" : self.before.is_some(),
+            "exists_after" : self.after.is_some(),
+            "lamports_before" : self.lamports_before(),
+            "lamports_after" : self.lamports_after(),
+            "lamport_delta" : delta_json(self.lamport_delta()),
+            "token" : token
+        })
+    }
+}
+
+// Previews how a transaction would change the balances of its writable accounts, including those that it loads from
+// address lookup tables, by simulating it with their states returned and comparing those with their current states.
+// Returns the report of the preview, giving the simulation's err and the error that describes it, the compute units
+// consumed, and the balance changes of each writable account.  A failed simulation returns no account states, so its
+// report gives no balance changes; the fee that the fee payer would still be charged for it is not reflected.
+pub fn preview_transaction(
+    endpoint : &RpcEndpoint,
+    transaction : &Transaction,
+    commitment : Option<String>,
+    error_tables : &ErrorTables
+) -> Result<json_Value, Error>
+{
+    let options = SimulateOptions {
+        replace_recent_blockhash : true,
+        commitment,
+        accounts : fetch_writable_addresses(endpoint, transaction)?,
+        ..SimulateOptions::default()
+    };
+
+    let before = fetch_account_states(endpoint, &options.accounts, options.commitment.as_deref())?;
+
+    let value = simulate_transaction(endpoint, transaction, &options)?;
+
+    let err = value.get("err").cloned().unwrap_or(json_Value::Null);
+
+    let after = match value.get("accounts") {
+        Some(json_Value::Array(after)) if after.len() == options.accounts.len() => after.clone(),
+        _ if err.is_null() => return Err(Error::rpc("Simulation result does not give the state of each account")),
+        _ => vec![json_Value::Null; options.accounts.len()]
+    };
+
+    let changes = options
+        .accounts
+        .iter()
+        .zip(before.iter().zip(after.iter()))
+        .map(|(address, (before, after))| {
+            let before = AccountState::from_json(before)?;
+            let after = if err.is_null() { AccountState::from_json(after)? } else { before.clone() };
+            Ok(BalanceChange { address : address.clone(), before, after }.to_json())
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(serde_json::json!({
+        "err" : err,
+        "error" : transaction_error_description(&err, value.get("logs"), transaction, error_tables),
+        "units_consumed" : value.get("unitsConsumed"),
+        "accounts" : changes
+    }))
+}
+
+// Deltas of u64 balances do not always fit in an i64, in which case they are given as strings
+fn delta_json(delta : i128) -> json_Value
+{
+    match i64::try_from(delta) {
+        Ok(delta) => json_Value::from(delta),
+        Err(_) => json_Value::String(delta.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::tests::{mock_rpc_node, system_transfer as transaction, DESTINATION, FEE_PAYER};
+    use crate::SYSTEM_PROGRAM_ID;
+
+    fn token_account_data(
+        len : usize,
+        amount : u64
+    ) -> Vec<u8>
+    {
+        let mut data = vec![0_u8; len];
+        data[TOKEN_ACCOUNT_MINT_OFFSET..(TOKEN_ACCOUNT_MINT_OFFSET + 32)].copy_from_slice(&[1_u8; 32]);
+        data[TOKEN_ACCOUNT_OWNER_OFFSET..(TOKEN_ACCOUNT_OWNER_OFFSET + 32)].copy_from_slice(&[2_u8; 32]);
+        data[TOKEN_ACCOUNT_AMOUNT_OFFSET..(TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    fn state(
+        lamports : u64,
+        owner : &str,
+        data : Vec<u8>
+    ) -> AccountState
+    {
+        AccountState { lamports, owner : Address::from_str(owner).unwrap(), data }
+    }
+
+    fn state_json(
+        lamports : u64,
+        owner : &str
+    ) -> json_Value
+    {
+        serde_json::json!({ "lamports" : lamports, "owner" : owner, "data" : ["", "base64"], "executable" : false })
+    }
+
+    #[test]
+    fn reads_token_balances_of_token_accounts_only()
+    {
+        let balance = state(1, TOKEN_PROGRAM_ID, token_account_data(TOKEN_ACCOUNT_SIZE, 42)).token_balance().unwrap();
+        assert!(balance.mint == Address([1_u8; 32]));
+        assert!(balance.owner == Address([2_u8; 32]));
+        assert_eq!(balance.amount, 42);
+
+        // Token-2022 accounts may have extensions, following their account type
+        let mut data = token_account_data(TOKEN_ACCOUNT_SIZE + 10, 7);
+        data[TOKEN_ACCOUNT_SIZE] = TOKEN_2022_ACCOUNT_TYPE_ACCOUNT;
+        assert_eq!(state(1, TOKEN_2022_PROGRAM_ID, data.clone()).token_balance().unwrap().amount, 7);
+
+        // Padded mints are not token accounts, nor are token program accounts of other sizes, nor accounts of other
+        // programs
+        data[TOKEN_ACCOUNT_SIZE] = 1;
+        assert!(state(1, TOKEN_2022_PROGRAM_ID, data.clone()).token_balance().is_none());
+        assert!(state(1, TOKEN_PROGRAM_ID, data).token_balance().is_none());
+        assert!(state(1, TOKEN_PROGRAM_ID, vec![0_u8; 82]).token_balance().is_none());
+        assert!(state(1, SYSTEM_PROGRAM_ID, token_account_data(TOKEN_ACCOUNT_SIZE, 42)).token_balance().is_none());
+    }
+
+    #[test]
+    fn balance_changes_cover_created_and_closed_accounts()
+    {
+        let created = BalanceChange {
+            address : Address([3_u8; 32]),
+            before : None,
+            after : Some(state(2039280, TOKEN_PROGRAM_ID, token_account_data(TOKEN_ACCOUNT_SIZE, 5)))
+        };
+
+        let json = created.to_json();
+        assert_eq!(json["exists_before"], false);
+        assert_eq!(json["exists_after"], true);
+        assert_eq!(json["lamport_delta"], 2039280);
+        assert_eq!(json["token"]["amount_before"], 0);
+        assert_eq!(json["token"]["amount_after"], 5);
+        assert_eq!(json["token"]["amount_delta"], 5);
+        assert_eq!(json["token"]["mint"], format!("{}", Address([1_u8; 32])));
+
+        let closed = BalanceChange {
+            address : Address([3_u8; 32]),
+            before : Some(state(u64::MAX, TOKEN_PROGRAM_ID, token_account_data(TOKEN_ACCOUNT_SIZE, u64::MAX))),
+            after : None
+        };
+
+        // Deltas that do not fit in an i64 are given as strings
+        let json = closed.to_json();
+        assert_eq!(json["lamports_after"], 0);
+        assert_eq!(json["lamport_delta"], "-18446744073709551615");
+        assert_eq!(json["token"]["amount_delta"], "-18446744073709551615");
+
+        assert!(AccountState::from_json(&json_Value::Null).unwrap().is_none());
+        assert!(AccountState::from_json(&serde_json::json!({ "lamports" : 1 })).is_err());
+    }
+
+    #[test]
+    fn preview_compares_current_and_simulated_account_states()
+    {
+        let endpoint = mock_rpc_node(|method, params, _| match method {
+            "getMultipleAccounts" => {
+                assert_eq!(params[0], serde_json::json!([FEE_PAYER, DESTINATION]));
+                assert_eq!(params[1]["commitment"], "confirmed");
+                serde_json::json!({ "result" : { "value" : [state_json(5000000, SYSTEM_PROGRAM_ID), null] } })
+            },
+            "simulateTransaction" => {
+                assert_eq!(params[1]["accounts"]["addresses"], serde_json::json!([FEE_PAYER, DESTINATION]));
+                serde_json::json!({
+                    "result" : {
+                        "value" : {
+                            "err" : null,
+                            "unitsConsumed" : 150,
+                            "accounts" : [
+                                state_json(3995000, SYSTEM_PROGRAM_ID),
+                                state_json(1000000, SYSTEM_PROGRAM_ID)
+                            ]
+                        }
+                    }
+                })
+            },
+            _ => panic!("Unexpected method {}", method)
+        });
+
+        let report =
+            preview_transaction(&endpoint, &transaction(), Some("confirmed".to_string()), &ErrorTables::default())
+                .unwrap();
+
+        assert!(report["error"].is_null());
+        assert_eq!(report["units_consumed"], 150);
+        assert_eq!(report["accounts"][0]["lamport_delta"], -1005000);
+        assert_eq!(report["accounts"][1]["exists_before"], false);
+        assert_eq!(report["accounts"][1]["lamport_delta"], 1000000);
+        assert!(report["accounts"][1]["token"].is_null());
+    }
+
+    #[test]
+    fn preview_of_a_failed_simulation_changes_no_balances()
+    {
+        let endpoint = mock_rpc_node(|method, _, _| match method {
+            "getMultipleAccounts" => {
+                serde_json::json!({ "result" : { "value" : [state_json(500, SYSTEM_PROGRAM_ID), null] } })
+            },
+            _ => serde_json::json!({
+                "result" : { "value" : { "err" : { "InstructionError" : [0, { "Custom" : 1 }] }, "accounts" : null } }
+            })
+        });
+
+        let report = preview_transaction(&endpoint, &transaction(), None, &ErrorTables::default()).unwrap();
+
+        assert_eq!(report["error"]["custom_name"], "ResultWithNegativeLamports");
+        assert_eq!(report["accounts"][0]["lamport_delta"], 0);
+        assert_eq!(report["accounts"][1]["exists_after"], false);
+    }
+
+    #[test]
+    fn preview_of_a_successful_simulation_requires_the_state_of_each_account()
+    {
+        for accounts in [serde_json::json!(null), serde_json::json!([state_json(500, SYSTEM_PROGRAM_ID)])] {
+            let endpoint = mock_rpc_node(move |method, _, _| match method {
+                "getMultipleAccounts" => {
+                    serde_json::json!({ "result" : { "value" : [state_json(500, SYSTEM_PROGRAM_ID), null] } })
+                },
+                _ => serde_json::json!({ "result" : { "value" : { "err" : null, "accounts" : accounts } } })
+            });
+
+            let error = preview_transaction(&endpoint, &transaction(), None, &ErrorTables::default()).err().unwrap();
+
+            assert_eq!(error.category(), "rpc");
+            assert_eq!(error.message(), "Simulation result does not give the state of each account");
+        }
+    }
+}
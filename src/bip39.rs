@@ -0,0 +1,185 @@
+use crate::Error;
+use sha2::{Digest, Sha256};
+
+// The BIP39 English word list, one word per line in index order
+const ENGLISH_WORDS : &str = include_str!("bip39_english.txt");
+
+// Each word of a mnemonic encodes 11 bits
+const BITS_PER_WORD : usize = 11;
+
+// Returns a new random BIP39 mnemonic of the given number of words, which must be one of 12, 15, 18, 21, or 24
+pub fn generate_mnemonic<R>(
+    word_count : usize,
+    rng : &mut R
+) -> Result<String, Error>
+where
+    R : rand::RngCore + rand::CryptoRng
+{
+    // Every 3 words encode 32 bits of entropy plus 1 bit of checksum
+    if ![12, 15, 18, 21, 24].contains(&word_count) {
+        return Err(Error::keypair(&format!(
+            "Invalid mnemonic word count {}: must be 12, 15, 18, 21, or 24",
+            word_count
+        )));
+    }
+
+    let mut entropy = vec![0_u8; (word_count / 3) * 4];
+
+    rng.fill_bytes(&mut entropy);
+
+    Ok(entropy_to_mnemonic(&entropy))
+}
+
+// Checks that a mnemonic has a valid number of words, that every word is in the word list, and that the checksum that
+// its final bits encode matches its entropy.  A mistyped word almost always fails one of these checks.
+pub fn validate_mnemonic(mnemonic : &str) -> Result<(), Error>
+{
+    let words : Vec<&str> = ENGLISH_WORDS.lines().collect();
+
+    let mnemonic_words : Vec<&str> = mnemonic.split_whitespace().collect();
+
+    if ![12, 15, 18, 21, 24].contains(&mnemonic_words.len()) {
+        return Err(Error::keypair(&format!(
+            "Invalid seed phrase: it has {} words, but must have 12, 15, 18, 21, or 24",
+            mnemonic_words.len()
+        )));
+    }
+
+    let indices = mnemonic_words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            words.binary_search(word).map_err(|_| {
+                Error::keypair(&format!("Invalid seed phrase: word {} is not in the BIP39 English word list", i + 1))
+            })
+        })
+        .collect::<Result<Vec<usize>, Error>>()?;
+
+    let bit = |i : usize| (indices[i / BITS_PER_WORD] >> (BITS_PER_WORD - 1 - (i % BITS_PER_WORD))) & 1;
+
+    let entropy_bits = (indices.len() * BITS_PER_WORD * 32) / 33;
+
+    let entropy : Vec<u8> =
+        (0..(entropy_bits / 8)).map(|byte| (0..8).fold(0_u8, |b, i| (b << 1) | (bit((byte * 8) + i) as u8))).collect();
+
+    if entropy_to_mnemonic(&entropy) != mnemonic_words.join(" ") {
+        return Err(Error::keypair("Invalid seed phrase: its checksum does not match; check for mistyped words"));
+    }
+
+    Ok(())
+}
+
+// The mnemonic is the entropy followed by the leading (entropy bits / 32) bits of the SHA-256 digest of the entropy,
+// split into 11 bit word indices
+fn entropy_to_mnemonic(entropy : &[u8]) -> String
+{
+    let words : Vec<&str> = ENGLISH_WORDS.lines().collect();
+
+    let checksum = Sha256::digest(entropy);
+
+    let entropy_bits = entropy.len() * 8;
+
+    let bit = |i : usize| -> usize {
+        let byte = if i < entropy_bits { entropy[i / 8] } else { checksum[(i - entropy_bits) / 8] };
+        ((byte >> (7 - (i % 8))) & 1) as usize
+    };
+
+    let word_count = (entropy_bits + (entropy_bits / 32)) / BITS_PER_WORD;
+
+    (0..word_count)
+        .map(|w| words[(0..BITS_PER_WORD).fold(0, |index, b| (index << 1) | bit((w * BITS_PER_WORD) + b))])
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use rand::SeedableRng;
+
+    // Entropy and mnemonic pairs from the Trezor BIP39 test vectors
+    const VECTORS : [(&str, &str); 7] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+        ),
+        (
+            "80808080808080808080808080808080",
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage above"
+        ),
+        ("ffffffffffffffffffffffffffffffff", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong"),
+        (
+            "9e885d952ad362caeb4efe34a8e91bd2",
+            "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic"
+        ),
+        (
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            concat!(
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon ",
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art"
+            )
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            concat!(
+                "legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful ",
+                "legal winner thank year wave sausage worth title"
+            )
+        )
+    ];
+
+    #[test]
+    fn entropy_to_mnemonic_matches_vectors()
+    {
+        for (entropy, mnemonic) in VECTORS {
+            assert_eq!(entropy_to_mnemonic(&hex::decode(entropy).unwrap()), mnemonic);
+        }
+    }
+
+    #[test]
+    fn validate_mnemonic_accepts_vectors()
+    {
+        for (_, mnemonic) in VECTORS {
+            assert!(validate_mnemonic(mnemonic).is_ok(), "{}", mnemonic);
+        }
+    }
+
+    #[test]
+    fn validate_mnemonic_rejects_bad_mnemonics()
+    {
+        // Too few words
+        assert!(validate_mnemonic("abandon abandon abandon").is_err());
+
+        // A word that is not in the word list is reported by position rather than named, since it may be a secret
+        let err = validate_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandn about"
+        )
+        .unwrap_err();
+        assert_eq!(err.message(), "Invalid seed phrase: word 11 is not in the BIP39 English word list");
+
+        // Valid words with the wrong checksum
+        assert!(validate_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn generate_mnemonic_gives_valid_mnemonics()
+    {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+        for word_count in [12, 15, 18, 21, 24] {
+            let mnemonic = generate_mnemonic(word_count, &mut rng).unwrap();
+            assert_eq!(mnemonic.split(' ').count(), word_count);
+            assert!(validate_mnemonic(&mnemonic).is_ok());
+        }
+
+        assert!(generate_mnemonic(13, &mut rng).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+use crate::compute_budget::{
+    set_compute_budget_instruction, set_compute_unit_limit_instruction, set_compute_unit_price_instruction
+};
+use crate::data_value::{write_data_value, DataValue, Encoding};
+use crate::transaction::{Address, Instruction, InstructionAddress, Pubkey, Sha256Digest, Transaction};
+use crate::{Error, SYSTEM_PROGRAM_ID};
+use std::str::FromStr;
+
+pub const RECENT_BLOCKHASHES_SYSVAR_ID : &str = "SysvarRecentB1ockHashes11111111111111111111";
+
+// System program instruction index of AdvanceNonceAccount, encoded as a bincode u32
+pub const ADVANCE_NONCE_ACCOUNT_INSTRUCTION_DATA : [u8; 4] = [4, 0, 0, 0];
+
+// Builds a transaction in the same order that the encode subcommand reads one: each program begins a new
+// instruction, and the accounts and data values that follow are added to that instruction.  Data values are encoded
+// when the transaction is built, using the builder's encoding.  Any error encountered along the way is returned by
+// build().
+pub struct TransactionBuilder
+{
+    fee_payer : Pubkey,
+
+    encoding : Encoding,
+
+    lookup_tables : Vec<(Address, Vec<Address>)>,
+
+    // (nonce account, nonce authority) of the AdvanceNonceAccount instruction to begin the transaction with
+    nonce : Option<(Pubkey, Pubkey)>,
+
+    instructions : Vec<(Instruction, Vec<DataValue>)>,
+
+    compute_unit_limit : Option<u32>,
+
+    // Priority fee, in micro-lamports per compute unit
+    compute_unit_price : Option<u64>,
+
+    recent_blockhash : Option<Sha256Digest>,
+
+    error : Option<Error>
+}
+
+impl TransactionBuilder
+{
+    pub fn new(fee_payer : Pubkey) -> Self
+    {
+        TransactionBuilder {
+            fee_payer,
+            encoding : Encoding::RustBincodeVarInt,
+            lookup_tables : vec![],
+            nonce : None,
+            instructions : vec![],
+            compute_unit_limit : None,
+            compute_unit_price : None,
+            recent_blockhash : None,
+            error : None
+        }
+    }
+
+    pub fn encoding(
+        mut self,
+        encoding : Encoding
+    ) -> Self
+    {
+        self.encoding = encoding;
+
+        self
+    }
+
+    // Addresses of the built transaction which are in the table are loaded from it, making the transaction a v0
+    // transaction
+    pub fn lookup_table(
+        mut self,
+        table : Address,
+        addresses : Vec<Address>
+    ) -> Self
+    {
+        self.lookup_tables.push((table, addresses));
+
+        self
+    }
+
+    // Begins a new instruction of the given program
+    pub fn program<T : Into<Address>>(
+        mut self,
+        program_id : T
+    ) -> Self
+    {
+        self.instructions
+            .push((Instruction { program_address : program_id.into(), addresses : vec![], data : vec![] }, vec![]));
+
+        self
+    }
+
+    // Adds an account to the most recently begun instruction
+    pub fn account<T : Into<InstructionAddress>>(
+        mut self,
+        address : T,
+        is_signed : bool,
+        is_read_write : bool
+    ) -> Self
+    {
+        match self.instructions.last_mut() {
+            Some((instruction, _)) => instruction.addresses.push((address.into(), is_signed, is_read_write)),
+            None => self.fail("Account added before any program")
+        }
+
+        self
+    }
+
+    // Adds a data value to the data of the most recently begun instruction
+    pub fn data(
+        mut self,
+        data_value : DataValue
+    ) -> Self
+    {
+        match self.instructions.last_mut() {
+            Some((_, data_values)) => data_values.push(data_value),
+            None => self.fail("Data added before any program")
+        }
+
+        self
+    }
+
+    // Adds an already complete instruction
+    pub fn instruction(
+        mut self,
+        instruction : Instruction
+    ) -> Self
+    {
+        self.instructions.push((instruction, vec![]));
+
+        self
+    }
+
+    // Begins the transaction with an AdvanceNonceAccount instruction and uses the nonce as the recent blockhash
+    pub fn advance_nonce(
+        mut self,
+        nonce_account : Pubkey,
+        nonce_authority : Pubkey,
+        nonce : Sha256Digest
+    ) -> Self
+    {
+        self.nonce = Some((nonce_account, nonce_authority));
+
+        self.recent_blockhash(nonce)
+    }
+
+    // Adds a ComputeBudget SetComputeUnitLimit instruction
+    pub fn compute_unit_limit(
+        mut self,
+        units : u32
+    ) -> Self
+    {
+        self.compute_unit_limit = Some(units);
+
+        self
+    }
+
+    // Adds a ComputeBudget SetComputeUnitPrice instruction, which sets the priority fee
+    pub fn compute_unit_price(
+        mut self,
+        micro_lamports : u64
+    ) -> Self
+    {
+        self.compute_unit_price = Some(micro_lamports);
+
+        self
+    }
+
+    pub fn recent_blockhash(
+        mut self,
+        recent_blockhash : Sha256Digest
+    ) -> Self
+    {
+        self.recent_blockhash = Some(recent_blockhash);
+
+        self
+    }
+
+    pub fn build(self) -> Result<Transaction, Error>
+    {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut transaction = Transaction::new(self.fee_payer);
+
+        if let Some((nonce_account, nonce_authority)) = &self.nonce {
+            transaction.add_instruction(advance_nonce_instruction(nonce_account, nonce_authority));
+        }
+
+        for (mut instruction, data_values) in self.instructions.into_iter() {
+            for dv in data_values.into_iter() {
+                write_data_value(dv, &self.encoding, &mut instruction.data)?;
+            }
+
+            transaction.add_instruction(instruction);
+        }
+
+        // Each is inserted at the beginning of the transaction, so the price is set first in order for the limit to
+        // come first
+        if let Some(micro_lamports) = self.compute_unit_price {
+            set_compute_budget_instruction(&mut transaction, set_compute_unit_price_instruction(micro_lamports));
+        }
+
+        if let Some(units) = self.compute_unit_limit {
+            set_compute_budget_instruction(&mut transaction, set_compute_unit_limit_instruction(units));
+        }
+
+        if !self.lookup_tables.is_empty() {
+            transaction.apply_address_lookup_tables(&self.lookup_tables);
+        }
+
+        if let Some(recent_blockhash) = self.recent_blockhash {
+            transaction.set_recent_blockhash(recent_blockhash);
+        }
+
+        Ok(transaction)
+    }
+
+    // Only the first error is kept, since later errors are often a consequence of it
+    fn fail(
+        &mut self,
+        msg : &str
+    )
+    {
+        if self.error.is_none() {
+            self.error = Some(Error::encode(msg));
+        }
+    }
+}
+
+// Makes the system program instruction which advances the nonce stored in a nonce account
+pub fn advance_nonce_instruction(
+    nonce_account : &Pubkey,
+    nonce_authority : &Pubkey
+) -> Instruction
+{
+    Instruction {
+        program_address : Address::from_str(SYSTEM_PROGRAM_ID).unwrap(),
+        addresses : vec![
+            (nonce_account.clone().into(), false, true),
+            (Address::from_str(RECENT_BLOCKHASHES_SYSVAR_ID).unwrap().into(), false, false),
+            (nonce_authority.clone().into(), true, false),
+        ],
+        data : ADVANCE_NONCE_ACCOUNT_INSTRUCTION_DATA.to_vec()
+    }
+}
+
+// Returns true if the instruction is an AdvanceNonceAccount instruction
+pub fn is_advance_nonce_instruction(instruction : &Instruction) -> bool
+{
+    (instruction.program_address == Address::from_str(SYSTEM_PROGRAM_ID).unwrap()) &&
+        (instruction.data == ADVANCE_NONCE_ACCOUNT_INSTRUCTION_DATA)
+}
+
+// Checks that an AdvanceNonceAccount instruction advances the given nonce account with the given nonce authority
+pub fn check_advance_nonce_instruction(
+    instruction : &Instruction,
+    nonce_account : &Pubkey,
+    nonce_authority : &Pubkey
+) -> Result<(), Error>
+{
+    let address = |index : usize| instruction.addresses.get(index).map(|(address, _, _)| address);
+
+    if address(0) != Some(&nonce_account.clone().into()) {
+        return Err(Error::encode("Transaction already advances a different nonce account"));
+    }
+
+    if address(2) != Some(&nonce_authority.clone().into()) {
+        return Err(Error::encode("Transaction already advances the nonce account with a different nonce authority"));
+    }
+
+    Ok(())
+}
+
+// Makes a transaction use a durable nonce in place of a recent blockhash, by beginning it with an AdvanceNonceAccount
+// instruction and setting its recent blockhash to the nonce.  If the transaction already begins with an
+// AdvanceNonceAccount instruction, it must be for the same nonce account and nonce authority, and is not added again.
+pub fn use_durable_nonce(
+    transaction : &mut Transaction,
+    nonce_account : &Pubkey,
+    nonce_authority : &Pubkey,
+    nonce : Sha256Digest
+) -> Result<(), Error>
+{
+    match transaction.instructions.first() {
+        Some(instruction) if is_advance_nonce_instruction(instruction) => {
+            check_advance_nonce_instruction(instruction, nonce_account, nonce_authority)?
+        },
+        _ => transaction.insert_instruction(0, advance_nonce_instruction(nonce_account, nonce_authority))
+    }
+
+    transaction.set_recent_blockhash(nonce);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const FEE_PAYER : &str = "EAoizDFAwK2o5jjAgtuwgKcPxnz8JmGyevUkEwi9gwmS";
+
+    const NONCE_ACCOUNT : &str = "AVheJF4ZzCZjfysZP2FHdFERY3r7dh9AdBRRcJRWKARc";
+
+    const RECENT_BLOCKHASH : &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+    fn pubkey(s : &str) -> Pubkey
+    {
+        Pubkey::from_str(s).unwrap()
+    }
+
+    fn address(s : &str) -> Address
+    {
+        Address::from_str(s).unwrap()
+    }
+
+    // A System program Transfer from the fee payer
+    fn builder() -> TransactionBuilder
+    {
+        TransactionBuilder::new(pubkey(FEE_PAYER))
+            .program(address(SYSTEM_PROGRAM_ID))
+            .account(pubkey(FEE_PAYER), true, true)
+            .account(pubkey(NONCE_ACCOUNT), false, true)
+            .data(DataValue::U32List(vec![2]))
+            .data(DataValue::U64List(vec![1000000]))
+            .recent_blockhash(Sha256Digest::from_str(RECENT_BLOCKHASH).unwrap())
+    }
+
+    #[test]
+    fn errors_are_returned_by_build()
+    {
+        assert_eq!(
+            TransactionBuilder::new(pubkey(FEE_PAYER))
+                .account(pubkey(FEE_PAYER), true, true)
+                .data(DataValue::U8List(vec![1]))
+                .build()
+                .err()
+                .unwrap()
+                .message(),
+            "Account added before any program"
+        );
+    }
+
+    #[test]
+    fn durable_nonce_begins_the_transaction_with_advance_nonce()
+    {
+        let nonce_account = pubkey(NONCE_ACCOUNT);
+        let nonce = Sha256Digest([7_u8; 32]);
+
+        let mut transaction = builder().build().unwrap();
+
+        use_durable_nonce(&mut transaction, &nonce_account, &pubkey(FEE_PAYER), nonce.clone()).unwrap();
+
+        assert_eq!(transaction.instructions.len(), 2);
+        assert!(is_advance_nonce_instruction(&transaction.instructions[0]));
+        assert!(transaction.recent_blockhash == Some(nonce.clone()));
+        assert!(
+            check_advance_nonce_instruction(&transaction.instructions[0], &nonce_account, &pubkey(FEE_PAYER)).is_ok()
+        );
+
+        // The builder makes the same instructions, though it may order the unsigned addresses differently
+        let built = builder().advance_nonce(nonce_account.clone(), pubkey(FEE_PAYER), nonce.clone()).build().unwrap();
+        assert_eq!(built.to_json()["instructions"], transaction.to_json()["instructions"]);
+        assert!(built.recent_blockhash == Some(nonce));
+
+        // Using the same nonce again only changes the nonce
+        let other_nonce = Sha256Digest([8_u8; 32]);
+        use_durable_nonce(&mut transaction, &nonce_account, &pubkey(FEE_PAYER), other_nonce.clone()).unwrap();
+        assert_eq!(transaction.instructions.len(), 2);
+        assert!(transaction.recent_blockhash == Some(other_nonce));
+    }
+
+    #[test]
+    fn durable_nonce_cannot_replace_a_different_nonce()
+    {
+        let nonce_account = pubkey(NONCE_ACCOUNT);
+        let nonce = Sha256Digest([7_u8; 32]);
+
+        let mut transaction =
+            builder().advance_nonce(nonce_account.clone(), pubkey(FEE_PAYER), nonce.clone()).build().unwrap();
+
+        assert_eq!(
+            use_durable_nonce(&mut transaction, &pubkey(FEE_PAYER), &pubkey(FEE_PAYER), nonce.clone())
+                .err()
+                .unwrap()
+                .message(),
+            "Transaction already advances a different nonce account"
+        );
+
+        assert_eq!(
+            use_durable_nonce(&mut transaction, &nonce_account, &nonce_account, nonce).err().unwrap().message(),
+            "Transaction already advances the nonce account with a different nonce authority"
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use crate::builder::is_advance_nonce_instruction;
+use crate::rpc::{
+    fetch_recent_prioritization_fees, fetch_writable_addresses, jv, simulate_transaction, RpcEndpoint, SimulateOptions
+};
+use crate::transaction::{Address, Instruction, Transaction};
+use crate::{Error, COMPUTE_BUDGET_PROGRAM_ID};
+use std::str::FromStr;
+
+// ComputeBudget program instruction indexes, encoded as a borsh u8
+const SET_COMPUTE_UNIT_LIMIT_INDEX : u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_INDEX : u8 = 3;
+
+// The most compute units that a transaction may request
+pub const MAXIMUM_COMPUTE_UNIT_LIMIT : u32 = 1_400_000;
+
+// Margin added to the compute units consumed in simulation when sizing the compute unit limit, as a percentage, by
+// default
+pub const DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT : u32 = 10;
+
+fn compute_budget_instruction(
+    index : u8,
+    arguments : &[u8]
+) -> Instruction
+{
+    let mut data = vec![index];
+
+    data.extend_from_slice(arguments);
+
+    Instruction { program_address : Address::from_str(COMPUTE_BUDGET_PROGRAM_ID).unwrap(), addresses : vec![], data }
+}
+
+pub fn set_compute_unit_limit_instruction(units : u32) -> Instruction
+{
+    compute_budget_instruction(SET_COMPUTE_UNIT_LIMIT_INDEX, &units.to_le_bytes())
+}
+
+pub fn set_compute_unit_price_instruction(micro_lamports : u64) -> Instruction
+{
+    compute_budget_instruction(SET_COMPUTE_UNIT_PRICE_INDEX, &micro_lamports.to_le_bytes())
+}
+
+fn is_compute_budget_instruction(
+    instruction : &Instruction,
+    index : u8
+) -> bool
+{
+    (instruction.program_address == Address::from_str(COMPUTE_BUDGET_PROGRAM_ID).unwrap()) &&
+        (instruction.data.first() == Some(&index))
+}
+
+// Sets a ComputeBudget instruction of a transaction.  An existing instruction of the same kind is replaced;
+// otherwise the instruction is inserted at the beginning of the transaction, after any AdvanceNonceAccount
+// instruction since that must remain first.  If the transaction changes, its signatures are cleared.
+pub fn set_compute_budget_instruction(
+    transaction : &mut Transaction,
+    instruction : Instruction
+)
+{
+    let index = instruction.data[0];
+
+    match transaction.instructions.iter().position(|i| is_compute_budget_instruction(i, index)) {
+        Some(position) => {
+            if transaction.instructions[position].data != instruction.data {
+                transaction.instructions[position].data = instruction.data;
+                transaction.clear_signatures();
+            }
+        },
+        None => {
+            let position = match transaction.instructions.first() {
+                Some(first) if is_advance_nonce_instruction(first) => 1,
+                _ => 0
+            };
+            transaction.insert_instruction(position, instruction);
+        }
+    }
+}
+
+// Sizes the compute unit limit of a transaction by simulating it.  The transaction is simulated with the maximum
+// compute unit limit so that the simulation cannot run out of compute units, and then its limit is set to the compute
+// units that the simulation consumed plus margin_percent percent.  The limit that was set is returned.
+pub fn set_simulated_compute_unit_limit(
+    endpoint : &RpcEndpoint,
+    transaction : &mut Transaction,
+    margin_percent : u32
+) -> Result<u32, Error>
+{
+    set_compute_budget_instruction(transaction, set_compute_unit_limit_instruction(MAXIMUM_COMPUTE_UNIT_LIMIT));
+
+    // The transaction's recent blockhash is replaced since it is usually applied only after the limit is set
+    let value = simulate_transaction(endpoint, transaction, &SimulateOptions {
+        replace_recent_blockhash : true,
+        ..SimulateOptions::default()
+    })?;
+
+    if let Some(err) = value.get("err").filter(|err| !err.is_null()) {
+        return Err(Error::rejected(&format!("Simulation failed: {}", err)));
+    }
+
+    let consumed =
+        jv(value, "unitsConsumed")?.as_u64().ok_or_else(|| Error::rpc("Invalid unitsConsumed in simulation result"))?;
+
+    let units =
+        (consumed * (100 + (margin_percent as u64))).div_ceil(100).min(MAXIMUM_COMPUTE_UNIT_LIMIT as u64) as u32;
+
+    set_compute_budget_instruction(transaction, set_compute_unit_limit_instruction(units));
+
+    Ok(units)
+}
+
+// Returns the fee at the given percentile (0 - 100) of recent prioritization fees, using the nearest rank method.
+// With no recent fees to go by, no priority fee is needed.
+pub fn percentile_fee(
+    mut fees : Vec<u64>,
+    percentile : u8
+) -> u64
+{
+    if fees.is_empty() {
+        return 0;
+    }
+
+    fees.sort_unstable();
+
+    let rank = ((percentile as usize) * fees.len()).div_ceil(100).max(1);
+
+    fees[rank - 1]
+}
+
+// Estimates the priority fee, in micro-lamports per compute unit, that a transaction needs as the given percentile of
+// the recent prioritization fees paid for writing to its writable accounts, including those it loads from address
+// lookup tables
+pub fn estimate_priority_fee(
+    endpoint : &RpcEndpoint,
+    transaction : &Transaction,
+    percentile : u8
+) -> Result<u64, Error>
+{
+    let fees = fetch_recent_prioritization_fees(endpoint, &fetch_writable_addresses(endpoint, transaction)?)?;
+
+    Ok(percentile_fee(fees, percentile))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::builder::use_durable_nonce;
+    use crate::data_value::{DataValue, Encoding};
+    use crate::rpc::tests::{mock_rpc_node, system_transfer as transaction, DESTINATION, FEE_PAYER};
+    use crate::transaction::{Pubkey, Sha256Digest};
+    use crate::{TransactionBuilder, SYSTEM_PROGRAM_ID};
+
+    fn program_ids(transaction : &Transaction) -> Vec<String>
+    {
+        transaction.instructions.iter().map(|instruction| format!("{}", instruction.program_address)).collect()
+    }
+
+    #[test]
+    fn percentile_fee_uses_the_nearest_rank()
+    {
+        let fees = vec![50, 10, 40, 20, 30];
+
+        assert_eq!(percentile_fee(fees.clone(), 0), 10);
+        assert_eq!(percentile_fee(fees.clone(), 20), 10);
+        assert_eq!(percentile_fee(fees.clone(), 21), 20);
+        assert_eq!(percentile_fee(fees.clone(), 50), 30);
+        assert_eq!(percentile_fee(fees.clone(), 75), 40);
+        assert_eq!(percentile_fee(fees, 100), 50);
+
+        assert_eq!(percentile_fee(vec![7], 50), 7);
+        assert_eq!(percentile_fee(vec![], 50), 0);
+    }
+
+    #[test]
+    fn compute_budget_instructions_are_set_once_at_the_beginning()
+    {
+        let mut transaction = transaction();
+
+        set_compute_budget_instruction(&mut transaction, set_compute_unit_price_instruction(1000));
+        set_compute_budget_instruction(&mut transaction, set_compute_unit_limit_instruction(200_000));
+
+        assert_eq!(program_ids(&transaction), vec![
+            COMPUTE_BUDGET_PROGRAM_ID,
+            COMPUTE_BUDGET_PROGRAM_ID,
+            SYSTEM_PROGRAM_ID
+        ]);
+        assert_eq!(transaction.instructions[0].data, vec![2, 0x40, 0x0d, 0x03, 0x00]);
+        assert_eq!(transaction.instructions[1].data, vec![3, 0xe8, 0x03, 0, 0, 0, 0, 0, 0]);
+
+        // Setting a price again replaces the existing one
+        set_compute_budget_instruction(&mut transaction, set_compute_unit_price_instruction(5));
+        assert_eq!(transaction.instructions.len(), 3);
+        assert_eq!(transaction.instructions[1].data, vec![3, 5, 0, 0, 0, 0, 0, 0, 0]);
+
+        // The builder makes the same instructions
+        let built = TransactionBuilder::new(Pubkey::from_str(FEE_PAYER).unwrap())
+            .encoding(Encoding::RustBincodeFixedInt)
+            .program(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap())
+            .account(Pubkey::from_str(FEE_PAYER).unwrap(), true, true)
+            .account(Pubkey::from_str(DESTINATION).unwrap(), false, true)
+            .data(DataValue::U32List(vec![2]))
+            .data(DataValue::U64List(vec![1000000]))
+            .compute_unit_limit(200_000)
+            .compute_unit_price(5)
+            .recent_blockhash(Sha256Digest([1_u8; 32]))
+            .build()
+            .unwrap();
+        assert_eq!(built.to_json(), transaction.to_json());
+    }
+
+    #[test]
+    fn compute_budget_instructions_follow_advance_nonce()
+    {
+        let mut transaction = transaction();
+
+        let fee_payer = Pubkey::from_str(FEE_PAYER).unwrap();
+
+        use_durable_nonce(&mut transaction, &Pubkey([9_u8; 32]), &fee_payer, Sha256Digest([2_u8; 32])).unwrap();
+
+        set_compute_budget_instruction(&mut transaction, set_compute_unit_limit_instruction(200_000));
+
+        assert!(is_advance_nonce_instruction(&transaction.instructions[0]));
+        assert_eq!(program_ids(&transaction), vec![SYSTEM_PROGRAM_ID, COMPUTE_BUDGET_PROGRAM_ID, SYSTEM_PROGRAM_ID]);
+    }
+
+    #[test]
+    fn simulated_compute_unit_limit_adds_the_margin_to_the_units_consumed()
+    {
+        let endpoint = mock_rpc_node(|method, params, _| {
+            assert_eq!(method, "simulateTransaction");
+            assert_eq!(params[1]["replaceRecentBlockhash"], true);
+            // The transaction is simulated with the maximum compute unit limit
+            let transaction =
+                Transaction::decode(&mut base64::decode(params[0].as_str().unwrap()).unwrap().as_slice()).unwrap();
+            assert_eq!(transaction.instructions[0].data, set_compute_unit_limit_instruction(1_400_000).data);
+            serde_json::json!({ "result" : { "value" : { "err" : null, "unitsConsumed" : 1001, "logs" : [] } } })
+        });
+
+        let mut transaction = transaction();
+
+        assert_eq!(set_simulated_compute_unit_limit(&endpoint, &mut transaction, 10).unwrap(), 1102);
+        assert_eq!(transaction.instructions.len(), 2);
+        assert_eq!(transaction.instructions[0].data, set_compute_unit_limit_instruction(1102).data);
+
+        // The limit never exceeds the maximum
+        assert_eq!(set_simulated_compute_unit_limit(&endpoint, &mut transaction, 200_000).unwrap(), 1_400_000);
+    }
+
+    #[test]
+    fn simulated_compute_unit_limit_fails_with_the_simulation()
+    {
+        let endpoint = mock_rpc_node(
+            |_, _, _| serde_json::json!({ "result" : { "value" : { "err" : "AccountNotFound", "unitsConsumed" : 0 } } })
+        );
+
+        let err = set_simulated_compute_unit_limit(&endpoint, &mut transaction(), 10).err().unwrap();
+
+        assert_eq!(err.category(), "rejected");
+        assert_eq!(err.message(), "Simulation failed: \"AccountNotFound\"");
+    }
+}
@@ -0,0 +1,715 @@
+use crate::rpc::{genesis_hash_from_arg, rpc_url_from_arg, RpcEndpoint, DEFAULT_MAINNET_RPC_URL};
+use crate::Error;
+use serde_json::Value as json_Value;
+
+// Where the config file is read from when SOLXACT_CONFIG does not give its path, relative to the home directory
+pub const DEFAULT_CONFIG_PATH : &str = "~/.config/solxact/config.json";
+
+// Where the Solana CLI keeps its config file, relative to the home directory
+pub const DEFAULT_SOLANA_CLI_CONFIG_PATH : &str = "~/.config/solana/cli/config.yml";
+
+// The profile that reads the Solana CLI's config file, unless the config file defines a profile of that name
+pub const SOLANA_CLI_PROFILE_NAME : &str = "solana-cli";
+
+// Settings that commands use in place of their defaults.  Values given on the command line take precedence over
+// these.
+#[derive(Clone, Default)]
+pub struct Profile
+{
+    pub rpc_url : Option<String>,
+
+    // HTTP headers sent with each request to the RPC URL, such as those that carry API keys
+    pub headers : Vec<(String, String)>,
+
+    pub commitment : Option<String>,
+
+    // The keypair that sign uses when it is given none
+    pub keypair : Option<String>,
+
+    // The genesis hash of the cluster that the RPC URL is expected to belong to
+    pub genesis_hash : Option<String>
+}
+
+// The profiles of a config file, along with the profile that is in use
+#[derive(Default)]
+pub struct Config
+{
+    default_profile : Option<String>,
+
+    profiles : Vec<(String, Profile)>,
+
+    // The profile in use, with any overrides from environment variables applied
+    pub profile : Profile,
+
+    // HTTP headers given on the command line, which are sent with each request to the RPC URL that the command uses,
+    // in place of any of a profile's headers of the same name
+    pub headers : Vec<(String, String)>,
+
+    // The RPC URL that the headers given on the command line are sent to.  These headers usually carry credentials
+    // for a single RPC provider, and so are never sent to a second RPC URL.
+    headers_url : std::cell::RefCell<Option<String>>
+}
+
+impl Config
+{
+    // Loads the config file, from the path given by SOLXACT_CONFIG or else the default path, and selects the profile
+    // to use: the named profile, or else the one named by SOLXACT_PROFILE, or else the config file's default profile.
+    // A config file that does not exist at the default path has no profiles.  Finally, any of SOLXACT_RPC_URL,
+    // SOLXACT_COMMITMENT, SOLXACT_KEYPAIR, and SOLXACT_CLUSTER that are set override the profile's settings.  The
+    // profile's headers and cluster belong to its own RPC URL, and so are not used with SOLXACT_RPC_URL's.
+    pub fn load(
+        profile_name : Option<&str>,
+        headers : &[(String, String)]
+    ) -> Result<Self, Error>
+    {
+        let mut config = match std::env::var("SOLXACT_CONFIG") {
+            Ok(path) => read_config_file(&expand_home(&path))?,
+            Err(_) => {
+                let path = expand_home(DEFAULT_CONFIG_PATH);
+                if std::path::Path::new(&path).exists() {
+                    read_config_file(&path)?
+                }
+                else {
+                    Config::default()
+                }
+            }
+        };
+
+        let profile_name = profile_name
+            .map(|name| name.to_string())
+            .or_else(|| std::env::var("SOLXACT_PROFILE").ok())
+            .or_else(|| config.default_profile.clone());
+
+        let mut profile = match profile_name {
+            Some(name) => {
+                config.find_profile(&name)?.ok_or_else(|| Error::usage(&format!("No such profile: {}", name)))?
+            },
+            None => Profile::default()
+        };
+
+        if let Ok(rpc_url) = std::env::var("SOLXACT_RPC_URL") {
+            profile.rpc_url = Some(rpc_url_from_arg(&rpc_url));
+            profile.headers = vec![];
+            profile.genesis_hash = None;
+        }
+
+        if let Ok(commitment) = std::env::var("SOLXACT_COMMITMENT") {
+            profile.commitment = Some(make_commitment(&commitment)?);
+        }
+
+        if let Ok(keypair) = std::env::var("SOLXACT_KEYPAIR") {
+            profile.keypair = Some(expand_home(&keypair));
+        }
+
+        if let Ok(cluster) = std::env::var("SOLXACT_CLUSTER") {
+            profile.genesis_hash = Some(genesis_hash_from_arg(&cluster)?);
+        }
+
+        config.profile = profile;
+
+        config.headers = headers.to_vec();
+
+        Ok(config)
+    }
+
+    // Returns the profile of the given name.  The Solana CLI profile is always available, unless the config file
+    // defines a profile of the same name.
+    fn find_profile(
+        &self,
+        name : &str
+    ) -> Result<Option<Profile>, Error>
+    {
+        match self.profiles.iter().find(|(profile_name, _)| profile_name == name) {
+            Some((_, profile)) => Ok(Some(profile.clone())),
+            None if name == SOLANA_CLI_PROFILE_NAME => {
+                read_solana_cli_config(&expand_home(DEFAULT_SOLANA_CLI_CONFIG_PATH)).map(Some)
+            },
+            None => Ok(None)
+        }
+    }
+
+    // Returns the RPC node that a command line argument refers to, along with the HTTP headers to send with each
+    // request to it and the genesis hash of the cluster that it is expected to belong to, if that is known.  An
+    // argument is the name of a profile that has an RPC URL, or a cluster identifier, or an RPC URL.  If there is no
+    // argument, the RPC URL of the profile in use is returned, or failing that the mainnet cluster's, with the
+    // profile's expected genesis hash.  The headers are those of the profile that the RPC URL comes from, merged with
+    // those given on the command line, which replace any of the profile's headers of the same name (ignoring case).
+    // It is an error for a command that was given headers on the command line to use more than one RPC URL, since
+    // those headers would then be sent to every one of them.
+    pub fn rpc_endpoint(
+        &self,
+        arg : Option<&str>
+    ) -> Result<RpcEndpoint, Error>
+    {
+        let profile = match arg {
+            Some(arg) => match self.find_profile(arg)? {
+                Some(profile) if profile.rpc_url.is_some() => profile,
+                _ => {
+                    let url = rpc_url_from_arg(arg);
+                    return Ok(RpcEndpoint { headers : self.command_line_headers(&url)?, url, genesis_hash : None });
+                }
+            },
+            None => self.profile.clone()
+        };
+
+        let (url, mut headers) = match profile.rpc_url {
+            Some(rpc_url) => (rpc_url, profile.headers),
+            None => (DEFAULT_MAINNET_RPC_URL.to_string(), vec![])
+        };
+
+        for (name, value) in self.command_line_headers(&url)? {
+            headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+            headers.push((name, value));
+        }
+
+        Ok(RpcEndpoint { url, headers, genesis_hash : profile.genesis_hash })
+    }
+
+    // Returns the headers given on the command line, to send to an RPC URL, after checking that they have not already
+    // been sent to a different RPC URL
+    fn command_line_headers(
+        &self,
+        url : &str
+    ) -> Result<Vec<(String, String)>, Error>
+    {
+        if self.headers.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut headers_url = self.headers_url.borrow_mut();
+
+        match headers_url.as_deref() {
+            Some(headers_url) if headers_url != url => Err(Error::usage(
+                "Headers given on the command line can only be sent to a single RPC node; give the headers of each \
+                 RPC node in its own profile instead"
+            )),
+            _ => {
+                *headers_url = Some(url.to_string());
+                Ok(self.headers.clone())
+            }
+        }
+    }
+}
+
+// Reads a config file, which holds a JSON object giving the default_profile, which is optional, and the profiles, an
+// object mapping the name of each profile to its settings
+pub fn read_config_file(path : &str) -> Result<Config, Error>
+{
+    let json : json_Value = serde_json::from_str(
+        &std::fs::read_to_string(path).map_err(|e| Error::io(&format!("Failed to read {}: {}", path, e)))?
+    )
+    .map_err(|e| Error::parse(&format!("Invalid config file {}: {}", path, e)))?;
+
+    let invalid = |what : &str| Error::parse(&format!("Invalid {} in config file {}", what, path));
+
+    let default_profile = match json.get("default_profile") {
+        None | Some(json_Value::Null) => None,
+        Some(json_Value::String(name)) => Some(name.clone()),
+        Some(_) => return Err(invalid("default_profile"))
+    };
+
+    let profiles = match json.get("profiles") {
+        None => vec![],
+        Some(json_Value::Object(profiles)) => profiles
+            .iter()
+            .map(|(name, profile)| Ok((name.clone(), read_profile(profile, path)?)))
+            .collect::<Result<Vec<_>, Error>>()?,
+        Some(_) => return Err(invalid("profiles"))
+    };
+
+    Ok(Config { default_profile, profiles, profile : Profile::default(), ..Config::default() })
+}
+
+// Reads the settings of a profile: rpc_url, headers, bearer_token, basic_auth, commitment, keypair, and cluster, each
+// optional.  A profile that gives solana_cli_config, either true or the path of a Solana CLI config file, starts from
+// the settings of that file.
+fn read_profile(
+    json : &json_Value,
+    path : &str
+) -> Result<Profile, Error>
+{
+    let invalid = |field : &str| Error::parse(&format!("Invalid {} in config file {}", field, path));
+
+    let string = |field : &str| match json.get(field) {
+        None | Some(json_Value::Null) => Ok(None),
+        Some(json_Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(invalid(field))
+    };
+
+    let mut profile = match json.get("solana_cli_config") {
+        None | Some(json_Value::Null) | Some(json_Value::Bool(false)) => Profile::default(),
+        Some(json_Value::Bool(true)) => read_solana_cli_config(&expand_home(DEFAULT_SOLANA_CLI_CONFIG_PATH))?,
+        Some(json_Value::String(cli_config_path)) => read_solana_cli_config(&expand_home(cli_config_path))?,
+        Some(_) => return Err(invalid("solana_cli_config"))
+    };
+
+    if let Some(rpc_url) = string("rpc_url")? {
+        profile.rpc_url = Some(rpc_url_from_arg(&rpc_url));
+    }
+
+    let mut headers = vec![];
+
+    match json.get("headers") {
+        None | Some(json_Value::Null) => (),
+        Some(json_Value::Object(json_headers)) => {
+            for (name, value) in json_headers {
+                match value {
+                    json_Value::String(value) => headers.push(make_header(name, &read_secret(value)?)?),
+                    _ => return Err(invalid("headers"))
+                }
+            }
+        },
+        Some(_) => return Err(invalid("headers"))
+    }
+
+    if let Some(token) = string("bearer_token")? {
+        headers.push(bearer_token_header(&read_secret(&token)?)?);
+    }
+
+    if let Some(credentials) = string("basic_auth")? {
+        headers.push(basic_auth_header(&read_secret(&credentials)?)?);
+    }
+
+    // Which of several Authorization headers an RPC node would use is undefined
+    if headers.iter().filter(|(name, _)| name.eq_ignore_ascii_case("Authorization")).count() > 1 {
+        return Err(Error::usage(&format!(
+            "Only one of an Authorization header, bearer_token, and basic_auth may be given by a profile in config \
+             file {}",
+            path
+        )));
+    }
+
+    profile.headers.extend(headers);
+
+    if let Some(commitment) = string("commitment")? {
+        profile.commitment = Some(make_commitment(&commitment).map_err(|_| invalid("commitment"))?);
+    }
+
+    if let Some(keypair) = string("keypair")? {
+        profile.keypair = Some(expand_home(&keypair));
+    }
+
+    if let Some(cluster) = string("cluster")? {
+        profile.genesis_hash = Some(genesis_hash_from_arg(&cluster).map_err(|_| invalid("cluster"))?);
+    }
+
+    Ok(profile)
+}
+
+// Reads the RPC URL, keypair path, and commitment from a Solana CLI config file.  That file is YAML, but its settings
+// are all top level "key: value" lines, which is all that is read.
+pub fn read_solana_cli_config(path : &str) -> Result<Profile, Error>
+{
+    let contents = std::fs::read_to_string(path).map_err(|e| Error::io(&format!("Failed to read {}: {}", path, e)))?;
+
+    let mut profile = Profile::default();
+
+    for line in contents.lines().filter(|line| !line.starts_with([' ', '\t', '#', '-'])) {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue
+        };
+
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+
+        if value.is_empty() {
+            continue;
+        }
+
+        match key {
+            "json_rpc_url" => profile.rpc_url = Some(value.to_string()),
+            "keypair_path" => profile.keypair = Some(expand_home(value)),
+            // The Solana CLI also accepts commitment levels that are no longer used, which are ignored
+            "commitment" => profile.commitment = make_commitment(value).ok(),
+            _ => ()
+        }
+    }
+
+    Ok(profile)
+}
+
+// Secrets such as API keys and credentials are best kept out of config files, and so a value that is "env:<NAME>" is
+// read from the environment variable of that name, and one that is "file:<PATH>" is read from that file, without any
+// trailing newline.  Any other value is used as is.
+pub fn read_secret(value : &str) -> Result<String, Error>
+{
+    if value.starts_with("env:") || value.starts_with("file:") {
+        read_secret_arg(value)
+    }
+    else {
+        Ok(value.to_string())
+    }
+}
+
+// Reads a secret given on the command line, which must be read from an environment variable or a file, since the
+// command line is visible to other users of the system and is kept in shell history
+pub fn read_secret_arg(value : &str) -> Result<String, Error>
+{
+    if let Some(name) = value.strip_prefix("env:") {
+        std::env::var(name).map_err(|_| Error::usage(&format!("Environment variable {} is not set", name)))
+    }
+    else if let Some(path) = value.strip_prefix("file:") {
+        let path = expand_home(path);
+        std::fs::read_to_string(&path)
+            .map(|contents| contents.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| Error::io(&format!("Failed to read {}: {}", path, e)))
+    }
+    else {
+        Err(Error::usage("Secrets given on the command line must be env:<NAME> or file:<PATH>"))
+    }
+}
+
+// Checks that a header name is an HTTP token and that its value is a single line
+pub fn make_header(
+    name : &str,
+    value : &str
+) -> Result<(String, String), Error>
+{
+    if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)) {
+        return Err(Error::usage(&format!("Invalid header name: {}", name)));
+    }
+
+    // The value is not given in the error, as it is likely to be a secret
+    if value.contains(['\r', '\n', '\0']) {
+        return Err(Error::usage(&format!("Invalid value for header {}", name)));
+    }
+
+    Ok((name.to_string(), value.to_string()))
+}
+
+// Returns the Authorization header that carries a bearer token
+pub fn bearer_token_header(token : &str) -> Result<(String, String), Error>
+{
+    make_header("Authorization", &format!("Bearer {}", token))
+}
+
+// Returns the Authorization header that carries basic auth credentials, which are given as <USER>:<PASSWORD>
+pub fn basic_auth_header(credentials : &str) -> Result<(String, String), Error>
+{
+    if !credentials.contains(':') {
+        return Err(Error::usage("Basic auth credentials must be given as <USER>:<PASSWORD>"));
+    }
+
+    make_header("Authorization", &format!("Basic {}", base64::encode(credentials)))
+}
+
+// Checks that a commitment level is one of processed, confirmed, or finalized
+pub fn make_commitment(commitment : &str) -> Result<String, Error>
+{
+    match commitment {
+        "processed" | "confirmed" | "finalized" => Ok(commitment.to_string()),
+        _ => Err(Error::usage(&format!("Invalid commitment: {}", commitment)))
+    }
+}
+
+// Paths that begin with ~/ are relative to the home directory
+pub(crate) fn expand_home(path : &str) -> String
+{
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home.trim_end_matches('/'), rest),
+        _ => path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::tests::mock_rpc_node;
+    use crate::rpc::{fetch_genesis_hash, DEFAULT_DEVNET_RPC_URL, DEVNET_GENESIS_HASH, MAINNET_GENESIS_HASH};
+
+    // Writes contents to a file in the temporary directory that is unique to this process and name
+    fn temp_file(
+        name : &str,
+        contents : &str
+    ) -> String
+    {
+        let path = std::env::temp_dir().join(format!("solxact-config-test-{}-{}", std::process::id(), name));
+
+        std::fs::write(&path, contents).unwrap();
+
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn reads_solana_cli_config_yaml()
+    {
+        let path = temp_file(
+            "cli.yml",
+            concat!(
+                "---\n",
+                "# Solana CLI config\n",
+                "json_rpc_url: \"https://api.devnet.solana.com\"\n",
+                "websocket_url: ''\n",
+                "keypair_path: '/keys/id.json'\n",
+                "address_labels:\n",
+                "  \"11111111111111111111111111111111\": System Program\n",
+                "commitment: confirmed\n"
+            )
+        );
+
+        let profile = read_solana_cli_config(&path).unwrap();
+
+        assert_eq!(profile.rpc_url.as_deref(), Some("https://api.devnet.solana.com"));
+        assert_eq!(profile.keypair.as_deref(), Some("/keys/id.json"));
+        assert_eq!(profile.commitment.as_deref(), Some("confirmed"));
+        assert!(profile.headers.is_empty());
+
+        // Commitment levels that are no longer used are ignored
+        std::fs::write(&path, "commitment: recent\n").unwrap();
+        assert_eq!(read_solana_cli_config(&path).unwrap().commitment, None);
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(read_solana_cli_config(&path).is_err());
+    }
+
+    #[test]
+    fn reads_profiles_from_config_files()
+    {
+        let cli_config = temp_file("profiles.yml", "json_rpc_url: http://cli.example\ncommitment: processed\n");
+
+        let token = temp_file("profiles.token", "secret\n");
+
+        let path = temp_file(
+            "profiles.json",
+            &serde_json::json!({
+                "default_profile" : "dev",
+                "profiles" : {
+                    "dev" : {
+                        "rpc_url" : "devnet",
+                        "headers" : { "x-api-key" : "key" },
+                        "bearer_token" : format!("file:{}", token),
+                        "commitment" : "confirmed",
+                        "keypair" : "/keys/dev.json",
+                        "cluster" : "devnet"
+                    },
+                    "cli" : { "solana_cli_config" : cli_config, "commitment" : "finalized" },
+                    "empty" : {}
+                }
+            })
+            .to_string()
+        );
+
+        let config = read_config_file(&path).unwrap();
+
+        assert_eq!(config.default_profile.as_deref(), Some("dev"));
+
+        let dev = config.find_profile("dev").unwrap().unwrap();
+        assert_eq!(dev.rpc_url.as_deref(), Some(DEFAULT_DEVNET_RPC_URL));
+        assert_eq!(dev.headers, vec![
+            ("x-api-key".to_string(), "key".to_string()),
+            ("Authorization".to_string(), "Bearer secret".to_string())
+        ]);
+        assert_eq!(dev.commitment.as_deref(), Some("confirmed"));
+        assert_eq!(dev.keypair.as_deref(), Some("/keys/dev.json"));
+        assert_eq!(dev.genesis_hash.as_deref(), Some(DEVNET_GENESIS_HASH));
+
+        // A profile's own settings take precedence over those of the Solana CLI config file that it starts from
+        let cli = config.find_profile("cli").unwrap().unwrap();
+        assert_eq!(cli.rpc_url.as_deref(), Some("http://cli.example"));
+        assert_eq!(cli.commitment.as_deref(), Some("finalized"));
+
+        assert!(config.find_profile("empty").unwrap().unwrap().rpc_url.is_none());
+        assert!(config.find_profile("missing").unwrap().is_none());
+
+        for path in [cli_config, token, path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_config_files()
+    {
+        for (name, contents, message) in [
+            ("json", "{ \"profiles\" : ", None),
+            ("default", "{ \"default_profile\" : 1 }", Some("default_profile")),
+            ("profiles", "{ \"profiles\" : [] }", Some("profiles")),
+            ("commitment", "{ \"profiles\" : { \"p\" : { \"commitment\" : \"recent\" } } }", Some("commitment")),
+            ("cluster", "{ \"profiles\" : { \"p\" : { \"cluster\" : \"nowhere\" } } }", Some("cluster")),
+            ("headers", "{ \"profiles\" : { \"p\" : { \"headers\" : { \"x\" : 1 } } } }", Some("headers")),
+            ("rpc_url", "{ \"profiles\" : { \"p\" : { \"rpc_url\" : true } } }", Some("rpc_url"))
+        ] {
+            let path = temp_file(&format!("invalid-{}.json", name), contents);
+            let err = read_config_file(&path).err().unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(err.category(), "parse");
+            if let Some(message) = message {
+                assert_eq!(err.message(), format!("Invalid {} in config file {}", message, path));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_profiles_with_more_than_one_authorization_header()
+    {
+        for (name, profile) in [
+            ("bearer-basic", serde_json::json!({ "bearer_token" : "token", "basic_auth" : "user:pw" })),
+            ("header-bearer", serde_json::json!({ "headers" : { "authorization" : "key" }, "bearer_token" : "token" })),
+            ("header-basic", serde_json::json!({ "headers" : { "Authorization" : "key" }, "basic_auth" : "user:pw" }))
+        ] {
+            let path = temp_file(
+                &format!("authorization-{}.json", name),
+                &serde_json::json!({ "profiles" : { "p" : profile } }).to_string()
+            );
+            let err = read_config_file(&path).err().unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(err.category(), "usage");
+        }
+    }
+
+    #[test]
+    fn checks_headers_and_secrets()
+    {
+        assert!(make_header("x-api-key", "key").is_ok());
+        assert!(make_header("", "key").is_err());
+        assert!(make_header("x api key", "key").is_err());
+        assert!(make_header("x-api-key", "key\r\nHost: elsewhere").is_err());
+
+        assert!(basic_auth_header("user").is_err());
+
+        assert_eq!(read_secret("plain").unwrap(), "plain");
+        assert!(read_secret("env:SOLXACT_CONFIG_TEST_UNSET_VARIABLE").is_err());
+        assert!(read_secret_arg("plain").is_err());
+
+        assert!(make_commitment("confirmed").is_ok());
+        assert!(make_commitment("max").is_err());
+    }
+
+    // The only test that sets environment variables, since tests run in parallel
+    #[test]
+    fn loads_the_profile_selected_by_name_or_environment()
+    {
+        let path = temp_file(
+            "load.json",
+            &serde_json::json!({
+                "default_profile" : "a",
+                "profiles" : {
+                    "a" : {
+                        "rpc_url" : "http://a.example",
+                        "headers" : { "x-key" : "env:SOLXACT_CONFIG_TEST_KEY" },
+                        "cluster" : "mainnet"
+                    },
+                    "b" : { "rpc_url" : "http://b.example", "commitment" : "processed" }
+                }
+            })
+            .to_string()
+        );
+
+        std::env::set_var("SOLXACT_CONFIG", &path);
+        std::env::set_var("SOLXACT_CONFIG_TEST_KEY", "a-key");
+
+        let headers = vec![("x-extra".to_string(), "1".to_string())];
+
+        let config = Config::load(None, &headers).unwrap();
+        assert_eq!(config.profile.headers, vec![("x-key".to_string(), "a-key".to_string())]);
+        let endpoint = config.rpc_endpoint(None).unwrap();
+        assert_eq!(endpoint.url, "http://a.example");
+        assert_eq!(endpoint.headers, vec![("x-key".to_string(), "a-key".to_string()), headers[0].clone()]);
+        assert_eq!(endpoint.genesis_hash.as_deref(), Some(MAINNET_GENESIS_HASH));
+
+        // Headers given on the command line are only ever sent to one RPC URL
+        assert!(config.rpc_endpoint(Some("http://elsewhere.example")).is_err());
+
+        std::env::set_var("SOLXACT_PROFILE", "b");
+        assert_eq!(Config::load(None, &[]).unwrap().profile.rpc_url.as_deref(), Some("http://b.example"));
+        assert_eq!(Config::load(Some("a"), &[]).unwrap().profile.rpc_url.as_deref(), Some("http://a.example"));
+
+        std::env::set_var("SOLXACT_RPC_URL", "devnet");
+
+        // A profile's headers and cluster are not used with an RPC URL that overrides the profile's
+        let overridden = Config::load(Some("a"), &[]).unwrap().rpc_endpoint(None).unwrap();
+        assert_eq!(overridden.url, DEFAULT_DEVNET_RPC_URL);
+        assert!(overridden.headers.is_empty());
+        assert!(overridden.genesis_hash.is_none());
+
+        std::env::set_var("SOLXACT_COMMITMENT", "finalized");
+        std::env::set_var("SOLXACT_CLUSTER", "devnet");
+        let config = Config::load(None, &[]).unwrap();
+        assert_eq!(config.profile.rpc_url.as_deref(), Some(DEFAULT_DEVNET_RPC_URL));
+        assert_eq!(config.profile.commitment.as_deref(), Some("finalized"));
+        assert_eq!(config.rpc_endpoint(None).unwrap().genesis_hash.as_deref(), Some(DEVNET_GENESIS_HASH));
+
+        // An RPC node named on the command line by profile uses that profile's RPC URL; otherwise the argument is a
+        // cluster or URL
+        let a = config.rpc_endpoint(Some("a")).unwrap();
+        assert_eq!(a.url, "http://a.example");
+        assert_eq!(a.headers, vec![("x-key".to_string(), "a-key".to_string())]);
+        assert_eq!(config.rpc_endpoint(Some("testnet")).unwrap().url, rpc_url_from_arg("testnet"));
+
+        std::env::set_var("SOLXACT_COMMITMENT", "max");
+        assert!(Config::load(None, &[]).is_err());
+
+        std::env::set_var("SOLXACT_PROFILE", "c");
+        assert_eq!(Config::load(None, &[]).err().unwrap().message(), "No such profile: c");
+
+        for name in [
+            "SOLXACT_CONFIG",
+            "SOLXACT_CONFIG_TEST_KEY",
+            "SOLXACT_PROFILE",
+            "SOLXACT_RPC_URL",
+            "SOLXACT_COMMITMENT",
+            "SOLXACT_CLUSTER"
+        ] {
+            std::env::remove_var(name);
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rpc_nodes_are_sent_the_headers_of_their_profile_and_the_command_line()
+    {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let sender = std::sync::Mutex::new(sender);
+
+        let node = mock_rpc_node(move |_, _, headers| {
+            sender.lock().unwrap().send(headers.to_vec()).unwrap();
+            serde_json::json!({ "result" : DEVNET_GENESIS_HASH })
+        });
+
+        let path = temp_file(
+            "headers.json",
+            &serde_json::json!({
+                "profiles" : {
+                    "provider" : { "rpc_url" : node.url, "headers" : { "x-api-key" : "key" }, "basic_auth" : "user:pw" }
+                }
+            })
+            .to_string()
+        );
+
+        let mut config = read_config_file(&path).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        config.headers = vec![
+            make_header("X-Api-Key", "cli-key").unwrap(),
+            make_header("x-request-source", "cli").unwrap()
+        ];
+
+        let endpoint = config.rpc_endpoint(Some("provider")).unwrap();
+
+        fetch_genesis_hash(&endpoint).unwrap();
+
+        let headers = receiver.recv().unwrap();
+
+        for (name, value) in [
+            ("x-api-key", "cli-key".to_string()),
+            ("authorization", format!("Basic {}", base64::encode("user:pw"))),
+            ("x-request-source", "cli".to_string())
+        ] {
+            assert!(headers.contains(&(name.to_string(), value)), "{} was not sent", name);
+        }
+
+        // A header given on the command line replaces the profile's header of the same name, whatever its case
+        assert_eq!(headers.iter().filter(|(name, _)| name == "x-api-key").count(), 1);
+
+        // Headers given on the command line are never sent to a second RPC node
+        assert_eq!(config.rpc_endpoint(Some("http://elsewhere.example")).err().unwrap().category(), "usage");
+    }
+}
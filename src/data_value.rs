@@ -0,0 +1,1187 @@
+use crate::keys::make_pubkey;
+use crate::pda::{find_pda, try_find_pda};
+use crate::transaction::{InstructionAddress, Pubkey};
+use crate::Error;
+use bincode::Options;
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+pub enum Encoding
+{
+    RustBincodeVarInt,
+
+    RustBincodeFixedInt,
+
+    RustBorsh,
+
+    C
+}
+
+#[derive(Clone)]
+pub enum DataValue
+{
+    BoolList(Vec<bool>),
+
+    U8List(Vec<u8>),
+
+    U16List(Vec<u16>),
+
+    U32List(Vec<u32>),
+
+    U64List(Vec<u64>),
+
+    I8List(Vec<i8>),
+
+    I16List(Vec<i16>),
+
+    I32List(Vec<i32>),
+
+    I64List(Vec<i64>),
+
+    F32List(Vec<f32>),
+
+    F64List(Vec<f64>),
+
+    String(String),
+
+    CString
+    {
+        max_length : u16,
+        string : String
+    },
+
+    Pubkey(Pubkey),
+
+    Sha256([u8; 32]),
+
+    Pda(Pubkey, Vec<Box<DataValue>>),
+
+    Bump(Pubkey, Vec<Box<DataValue>>),
+
+    PdaNoBump(Pubkey, Vec<Box<DataValue>>),
+
+    Vector(Vec<Box<DataValue>>),
+
+    Struct(Vec<Box<DataValue>>),
+
+    Enum
+    {
+        index : usize,
+        params : Option<Vec<Box<DataValue>>>
+    },
+
+    Some(Box<DataValue>),
+
+    None
+}
+
+pub fn make_sha256(s : &str) -> Result<[u8; 32], Error>
+{
+    let v = hex::decode(s).map_err(|e| Error::parse(&format!("Invalid sha256 {}: {}", s, e)))?;
+
+    if v.len() == 32 {
+        Ok(v.try_into().unwrap())
+    }
+    else {
+        Err(Error::parse(&format!("Invalid sha256: {}", s)))
+    }
+}
+
+pub fn skip_comments(words : &mut Vec<String>) -> Result<(), Error>
+{
+    while (words.len() > 0) && (words[0] == "//") {
+        // This is a comment, ignore it
+        words.remove(0);
+        loop {
+            if words.len() == 0 {
+                return Err(Error::parse("The final comment is incomplete"));
+            }
+            else {
+                let word = words.remove(0);
+                if word == "//" {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Turn words into a pubkey identifier, which is either a single String (being a path, or a Base58-encoded pubkey),
+// or is an JSON array (which is turned back into a string)
+pub fn pubkey_from_words(words : &mut Vec<String>) -> Result<String, Error>
+{
+    if words.len() == 0 {
+        Err(Error::parse("Missing pubkey value"))
+    }
+    else {
+        if words[0] == "[" {
+            let mut array = "[".to_string();
+            words.remove(0);
+            loop {
+                if words.len() == 0 {
+                    return Err(Error::parse("The final pubkey value array is incomplete"));
+                }
+                let word = words.remove(0);
+                array = format!("{}{}", array, word);
+                if word == "]" {
+                    return Ok(array);
+                }
+            }
+        }
+        else {
+            Ok(words.remove(0))
+        }
+    }
+}
+
+// encoding is used for pda and pda_nobump accounts
+pub fn read_accounts(
+    words : &mut Vec<String>,
+    into : &mut Vec<(InstructionAddress, bool, bool)>
+) -> Result<(), Error>
+{
+    loop {
+        skip_comments(words)?;
+
+        if (words.len() == 0) || (words[0] != "account") {
+            break;
+        }
+
+        words.remove(0);
+
+        if words.len() == 0 {
+            return Err(Error::parse("Missing account pubkey"));
+        }
+
+        // Account may come from a pda or pda_nobump value
+        let pubkey = match words[0].as_str() {
+            "pda" | "pda_nobump" => {
+                let dv = read_data_value(words)?.unwrap();
+                let mut bytes = vec![];
+                write_data_value(dv, &Encoding::C, &mut bytes)?;
+                Pubkey(bytes.as_slice().try_into().map_err(|_| Error::encode("Invalid PDA"))?)
+            },
+            _ => make_pubkey(&pubkey_from_words(words)?)?
+        };
+
+        let mut is_signed = false;
+
+        let mut is_write = false;
+
+        if words.len() > 0 {
+            match words[0].as_str() {
+                "s" => {
+                    words.remove(0);
+                    is_signed = true;
+                },
+                "w" => {
+                    words.remove(0);
+                    is_write = true;
+                },
+                "sw" | "ws" => {
+                    words.remove(0);
+                    is_signed = true;
+                    is_write = true;
+                },
+                _ => ()
+            }
+        }
+
+        into.push((pubkey.into(), is_signed, is_write));
+    }
+
+    Ok(())
+}
+
+fn is_data_value_terminator(s : &str) -> bool
+{
+    match s {
+        "program" | "bool" | "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "f32" | "f64" |
+        "string" | "c_string" | "pubkey" | "sha256" | "pda" | "bump" | "pda_nobump" | "vector" | "struct" |
+        "enum" | "some" | "none" | "]" | "//" => true,
+        _ => false
+    }
+}
+
+fn read_list<T, F>(
+    words : &mut Vec<String>,
+    mut f : F
+) -> Result<Vec<T>, Error>
+where
+    F : FnMut(&mut Vec<String>) -> Result<T, Error>
+{
+    let prefix = words.remove(0);
+
+    let mut ret = vec![];
+
+    loop {
+        // Stop at the end or when the next word is one of the value prefixes
+        if words.len() == 0 || is_data_value_terminator(&words[0]) {
+            break;
+        }
+        ret.push(f(words)?);
+    }
+
+    if ret.len() == 0 {
+        Err(Error::parse(&format!("Empty list of values after {}", prefix)))
+    }
+    else {
+        Ok(ret)
+    }
+}
+
+pub fn read_single_value(words : &mut Vec<String>) -> Result<String, Error>
+{
+    // Assume prefix is at first element of vector
+    let prefix = words.remove(0);
+
+    if words.len() == 0 {
+        return Err(Error::parse(&format!("The final {} parameter is incomplete", prefix)));
+    }
+
+    Ok(words.remove(0))
+}
+
+fn unescape_string(s : &str) -> String
+{
+    s.replace("\\\"", "\"").replace("\\\\", "\\")
+}
+
+fn read_string_value(words : &mut Vec<String>) -> Result<String, Error>
+{
+    if words.len() == 0 {
+        return Err(Error::parse("The final string parameter is incomplete"));
+    }
+
+    let word = words.remove(0);
+
+    if word.starts_with("\"") {
+        if word.ends_with("\"") {
+            Ok(unescape_string(&word))
+        }
+        else {
+            let mut ret = String::new();
+
+            write!(ret, "{}", unescape_string(&word[1..]))?;
+
+            loop {
+                if words.len() == 0 {
+                    return Err(Error::parse("The final string parameter is incomplete"));
+                }
+
+                let word = words.remove(0);
+
+                if word.ends_with("\"") {
+                    write!(ret, "{}", unescape_string(&word[0..(word.len() - 1)]))?;
+                    return Ok(ret);
+                }
+                else {
+                    write!(ret, "{}", unescape_string(&word))?;
+                }
+            }
+        }
+    }
+    else {
+        Ok(word)
+    }
+}
+
+fn read_vector(
+    prefix : &str,
+    words : &mut Vec<String>
+) -> Result<Vec<Box<DataValue>>, Error>
+{
+    if words.len() == 0 {
+        return Err(Error::parse(&format!("The final {} parameter is incomplete", prefix)));
+    }
+
+    let word = words.remove(0);
+
+    if word != "[" {
+        return Err(Error::parse(&format!("Expected [ after {}", prefix)));
+    }
+
+    let mut v = vec![];
+
+    loop {
+        skip_comments(words)?;
+
+        if words.len() == 0 {
+            return Err(Error::parse(&format!("The final {} parameter is incomplete", prefix)));
+        }
+
+        if words[0] == "]" {
+            words.remove(0);
+            break;
+        }
+
+        if let Some(dv) = read_data_value(words)? {
+            v.push(Box::new(dv));
+        }
+        else {
+            break;
+        }
+    }
+
+    if v.len() == 0 {
+        Err(Error::parse(&format!("Empty {}", prefix)))
+    }
+    else {
+        Ok(v)
+    }
+}
+
+pub fn read_data_value(words : &mut Vec<String>) -> Result<Option<DataValue>, Error>
+{
+    match words[0].as_str() {
+        "bool" => Ok(Some(DataValue::BoolList(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(bool::from_str(&word).map_err(|_| Error::parse(&format!("Invalid bool value: {}", word)))?)
+        })?))),
+        "u8" => Ok(Some(DataValue::U8List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(u8::from_str(&word).map_err(|_| Error::parse(&format!("Invalid u8 value: {}", word)))?)
+        })?))),
+        "u16" => Ok(Some(DataValue::U16List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(u16::from_str(&word).map_err(|_| Error::parse(&format!("Invalid u16 value: {}", word)))?)
+        })?))),
+        "u32" => Ok(Some(DataValue::U32List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(u32::from_str(&word).map_err(|_| Error::parse(&format!("Invalid u32 value: {}", word)))?)
+        })?))),
+        "u64" => Ok(Some(DataValue::U64List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(u64::from_str(&word).map_err(|_| Error::parse(&format!("Invalid u64 value: {}", word)))?)
+        })?))),
+        "i8" => Ok(Some(DataValue::I8List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(i8::from_str(&word).map_err(|_| Error::parse(&format!("Invalid i8 value: {}", word)))?)
+        })?))),
+        "i16" => Ok(Some(DataValue::I16List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(i16::from_str(&word).map_err(|_| Error::parse(&format!("Invalid i16 value: {}", word)))?)
+        })?))),
+        "i32" => Ok(Some(DataValue::I32List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(i32::from_str(&word).map_err(|_| Error::parse(&format!("Invalid i32 value: {}", word)))?)
+        })?))),
+        "i64" => Ok(Some(DataValue::I64List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(i64::from_str(&word).map_err(|_| Error::parse(&format!("Invalid i64 value: {}", word)))?)
+        })?))),
+        "f32" => Ok(Some(DataValue::F32List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(f32::from_str(&word).map_err(|_| Error::parse(&format!("Invalid f32 value: {}", word)))?)
+        })?))),
+        "f64" => Ok(Some(DataValue::F64List(read_list(words, |ws| {
+            let word = ws.remove(0);
+            Ok(f64::from_str(&word).map_err(|_| Error::parse(&format!("Invalid f64 value: {}", word)))?)
+        })?))),
+        "string" => {
+            words.remove(0); // string
+            Ok(Some(DataValue::String(read_string_value(words)?)))
+        },
+        "c_string" => {
+            words.remove(0); // c_string
+            if words.len() == 0 {
+                return Err(Error::parse(&format!("The final c_string parameter is incomplete")));
+            }
+            let word = words.remove(0);
+            let max_length = u16::from_str(&word)
+                .map_err(|_| Error::parse(&format!("Invalid max_length in c_string value: {}", word)))?;
+            Ok(Some(DataValue::CString { max_length, string : read_string_value(words)? }))
+        },
+        "pubkey" => {
+            words.remove(0);
+            Ok(Some(DataValue::Pubkey(make_pubkey(&pubkey_from_words(words)?)?)))
+        },
+        "sha256" => Ok(Some(DataValue::Sha256(make_sha256(&read_single_value(words)?)?))),
+        "pda" => {
+            words.remove(0);
+            Ok(Some(DataValue::Pda(make_pubkey(&pubkey_from_words(words)?)?, read_vector("pda", words)?)))
+        },
+        "bump" => {
+            words.remove(0);
+            Ok(Some(DataValue::Bump(make_pubkey(&pubkey_from_words(words)?)?, read_vector("bump", words)?)))
+        },
+        "pda_nobump" => {
+            words.remove(0);
+            Ok(Some(DataValue::PdaNoBump(make_pubkey(&pubkey_from_words(words)?)?, read_vector("pda_nobump", words)?)))
+        },
+        "vector" => {
+            words.remove(0); // vector
+            if words.len() == 0 {
+                return Err(Error::parse("The final vector parameter is incomplete"));
+            }
+            Ok(Some(DataValue::Vector(read_vector("vector", words)?)))
+        },
+        "struct" => {
+            words.remove(0); // struct
+            if words.len() == 0 {
+                return Err(Error::parse("The final struct parameter is incomplete"));
+            }
+            Ok(Some(DataValue::Struct(read_vector("struct", words)?)))
+        },
+        "enum" => {
+            words.remove(0); // enum
+            if words.len() == 0 {
+                return Err(Error::parse("The final enum parameter is incomplete"));
+            }
+            let index = words.remove(0);
+            let index = usize::from_str(&index)
+                .map_err(|err| Error::parse(&format!("Invalid enum index {}: {}", index, err)))?;
+            Ok(Some(DataValue::Enum {
+                index,
+                params : {
+                    if (words.len() == 0) || (words[0] != "[") {
+                        None
+                    }
+                    else {
+                        Some(read_vector("enum", words)?)
+                    }
+                }
+            }))
+        },
+        "some" => {
+            words.remove(0); // some
+            Ok(Some(DataValue::Some(Box::new(
+                read_data_value(words)?.ok_or_else(|| Error::parse("The final some parameter is incomplete"))?
+            ))))
+        },
+        "none" => Ok(Some(DataValue::None)),
+        "program" => Ok(None),
+        _ => Err(Error::parse(&format!("Invalid data: {}", words[0])))
+    }
+}
+
+pub fn read_data_values(
+    words : &mut Vec<String>,
+    into : &mut Vec<DataValue>
+) -> Result<(), Error>
+{
+    loop {
+        skip_comments(words)?;
+
+        if words.len() == 0 {
+            break;
+        }
+
+        let data_value = read_data_value(words)?;
+
+        if let Some(data_value) = data_value {
+            into.push(data_value);
+        }
+        else {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn vector_normalize(v : &Vec<Box<DataValue>>) -> Vec<Box<DataValue>>
+{
+    if v.len() == 1 {
+        match &*v[0] {
+            DataValue::U8List(v) => v.iter().map(|e| Box::new(DataValue::U8List(vec![*e]))).collect(),
+            DataValue::U16List(v) => v.iter().map(|e| Box::new(DataValue::U16List(vec![*e]))).collect(),
+            DataValue::U32List(v) => v.iter().map(|e| Box::new(DataValue::U32List(vec![*e]))).collect(),
+            DataValue::U64List(v) => v.iter().map(|e| Box::new(DataValue::U64List(vec![*e]))).collect(),
+            DataValue::I8List(v) => v.iter().map(|e| Box::new(DataValue::I8List(vec![*e]))).collect(),
+            DataValue::I16List(v) => v.iter().map(|e| Box::new(DataValue::I16List(vec![*e]))).collect(),
+            DataValue::I32List(v) => v.iter().map(|e| Box::new(DataValue::I32List(vec![*e]))).collect(),
+            DataValue::I64List(v) => v.iter().map(|e| Box::new(DataValue::I64List(vec![*e]))).collect(),
+            DataValue::F32List(v) => v.iter().map(|e| Box::new(DataValue::F32List(vec![*e]))).collect(),
+            DataValue::F64List(v) => v.iter().map(|e| Box::new(DataValue::F64List(vec![*e]))).collect(),
+            _ => v.clone()
+        }
+    }
+    else {
+        v.clone()
+    }
+}
+
+fn bincode_encode<T : serde::ser::Serialize>(
+    v : T,
+    varint : bool,
+    w : &mut dyn std::io::Write
+) -> Result<(), Error>
+where
+{
+    if varint {
+        bincode::DefaultOptions::new()
+            .with_varint_encoding()
+            .serialize_into(w, &v)
+            .map_err(|e| Error::encode(&e.to_string()))
+    }
+    else {
+        bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .serialize_into(w, &v)
+            .map_err(|e| Error::encode(&e.to_string()))
+    }
+}
+
+pub fn write_rust_bincode_value(
+    data_value : DataValue,
+    varint : bool,
+    into : &mut Vec<u8>
+) -> Result<(), Error>
+{
+    match data_value {
+        DataValue::BoolList(v) => {
+            for u in v.into_iter() {
+                bincode_encode(u, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U8List(v) => {
+            for u in v.into_iter() {
+                bincode_encode(u, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U16List(v) => {
+            for u in v.into_iter() {
+                bincode_encode(u, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U32List(v) => {
+            for u in v.into_iter() {
+                bincode_encode(u, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U64List(v) => {
+            for u in v.into_iter() {
+                bincode_encode(u, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I8List(v) => {
+            for i in v.into_iter() {
+                bincode_encode(i, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I16List(v) => {
+            for i in v.into_iter() {
+                bincode_encode(i, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I32List(v) => {
+            for i in v.into_iter() {
+                bincode_encode(i, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I64List(v) => {
+            for i in v.into_iter() {
+                bincode_encode(i, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::F32List(v) => {
+            for f in v.into_iter() {
+                bincode_encode(f, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::F64List(v) => {
+            for f in v.into_iter() {
+                bincode_encode(f, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::String(s) => bincode_encode(s, varint, into),
+
+        DataValue::CString { max_length, string } => {
+            let zeroes = (max_length as usize).checked_sub(string.len()).ok_or_else(|| {
+                Error::encode(&format!("c_string {} has length greater than max_length {}", string.len(), max_length))
+            })?;
+            into.extend(string.as_bytes());
+            for _ in 0..zeroes {
+                into.push(0);
+            }
+            Ok(())
+        },
+
+        DataValue::Pubkey(p) => bincode_encode(p.0, varint, into),
+
+        DataValue::Sha256(a) => {
+            for u in a {
+                bincode_encode(u, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::Pda(program_id, v) => {
+            // Encode v into a vector of bytes, which is the base seed.  Fixed int is used as varint doesn't
+            // make sense for seed values.
+            let mut seed = vec![];
+            write_rust_bincode_value(DataValue::Vector(v), false, &mut seed)?;
+            // Compute the address and bump seed, which must succeed since a
+            // bump seed is being used
+            let (pubkey, _) = find_pda(&program_id, &seed).unwrap();
+            // Encode the pubkey
+            bincode_encode(pubkey.0, false, into)
+        },
+
+        DataValue::Bump(program_id, v) => {
+            // Encode v into a vector of bytes, which is the base seed.  Fixed int is used as varint doesn't
+            // make sense for seed values.
+            let mut seed = vec![];
+            write_rust_bincode_value(DataValue::Vector(v), false, &mut seed)?;
+            // Compute the address and bump seed, which must succeed since a
+            // bump seed is being used
+            let (_, bump_seed) = find_pda(&program_id, &seed).unwrap();
+            // Encode the pubkey.
+            bincode_encode(bump_seed, varint, into)
+        },
+
+        DataValue::PdaNoBump(program_id, v) => {
+            // Encode v into a vector of bytes, which is the base seed.  Fixed int is used as varint doesn't
+            // make sense for seed values.
+            let mut seed = vec![];
+            write_rust_bincode_value(DataValue::Vector(v), false, &mut seed)?;
+            // Compute the address and bump seed, which may fail since a bump seed is not being used
+            match try_find_pda(&program_id, &seed, None) {
+                Some(pubkey) => {
+                    // Encode the pubkey
+                    bincode_encode(pubkey.0, false, into)
+                },
+                None => Err(Error::encode("PDA could not be derived"))
+            }
+        },
+
+        DataValue::Vector(v) => {
+            let v = vector_normalize(&v);
+            bincode_encode(v.len(), varint, into)?;
+            for v in v.into_iter() {
+                write_rust_bincode_value(*v, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::Struct(v) => {
+            for v in v.into_iter() {
+                write_rust_bincode_value(*v, varint, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::Enum { index, params } => {
+            if index > (u32::MAX as usize) {
+                return Err(Error::encode(&format!(
+                    "enum index {} is greater than max of {} supported by borsh encoding",
+                    index,
+                    u32::MAX
+                )));
+            }
+            bincode_encode(index as u32, varint, into)?;
+            if let Some(params) = params {
+                write_rust_bincode_value(DataValue::Struct(params), varint, into)
+            }
+            else {
+                Ok(())
+            }
+        },
+
+        DataValue::Some(v) => {
+            write_rust_bincode_value(DataValue::Enum { index : 1, params : Some(vec![v]) }, varint, into)
+        },
+
+        DataValue::None => write_rust_bincode_value(DataValue::Enum { index : 0, params : None }, varint, into)
+    }
+}
+
+fn borsh_encode<T : borsh::BorshSerialize>(
+    v : T,
+    w : &mut dyn std::io::Write
+) -> Result<(), Error>
+{
+    borsh::to_writer(w, &v).map_err(|e| e.into())
+}
+
+pub fn write_rust_borsh_value(
+    data_value : DataValue,
+    into : &mut Vec<u8>
+) -> Result<(), Error>
+{
+    match data_value {
+        DataValue::BoolList(v) => {
+            for u in v.into_iter() {
+                borsh_encode(u, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U8List(v) => {
+            for u in v.into_iter() {
+                borsh_encode(u, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U16List(v) => {
+            for u in v.into_iter() {
+                borsh_encode(u, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U32List(v) => {
+            for u in v.into_iter() {
+                borsh_encode(u, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::U64List(v) => {
+            for u in v.into_iter() {
+                borsh_encode(u, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I8List(v) => {
+            for i in v.into_iter() {
+                borsh_encode(i, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I16List(v) => {
+            for i in v.into_iter() {
+                borsh_encode(i, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I32List(v) => {
+            for i in v.into_iter() {
+                borsh_encode(i, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::I64List(v) => {
+            for i in v.into_iter() {
+                borsh_encode(i, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::F32List(v) => {
+            for f in v.into_iter() {
+                borsh_encode(f, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::F64List(v) => {
+            for f in v.into_iter() {
+                borsh_encode(f, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::String(s) => borsh_encode(s, into),
+
+        DataValue::CString { max_length, string } => {
+            let zeroes = (max_length as usize).checked_sub(string.len()).ok_or_else(|| {
+                Error::encode(&format!("c_string {} has length greater than max_length {}", string.len(), max_length))
+            })?;
+            into.extend(string.as_bytes());
+            for _ in 0..zeroes {
+                into.push(0);
+            }
+            Ok(())
+        },
+
+        DataValue::Pubkey(p) => borsh_encode(p.0, into),
+
+        DataValue::Sha256(a) => {
+            for u in a {
+                borsh_encode(u, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::Pda(program_id, v) => {
+            // Encode v into a vector of bytes, which is the base seed
+            let mut seed = vec![];
+            write_rust_borsh_value(DataValue::Vector(v), &mut seed)?;
+            // Compute the address and bump seed, which must succeed since a
+            // bump seed is being used
+            let (pubkey, _) = find_pda(&program_id, &seed).unwrap();
+            // Encode the pubkey
+            borsh_encode(pubkey.0, into)
+        },
+
+        DataValue::Bump(program_id, v) => {
+            // Encode v into a vector of bytes, which is the base seed
+            let mut seed = vec![];
+            write_rust_borsh_value(DataValue::Vector(v), &mut seed)?;
+            // Compute the address and bump seed, which must succeed since a
+            // bump seed is being used
+            let (_, bump_seed) = find_pda(&program_id, &seed).unwrap();
+            // Encode the pubkey
+            borsh_encode(bump_seed, into)
+        },
+
+        DataValue::PdaNoBump(program_id, v) => {
+            // Encode v into a vector of bytes, which is the base seed
+            let mut seed = vec![];
+            write_rust_borsh_value(DataValue::Vector(v), &mut seed)?;
+            // Compute the address and bump seed, which may fail since a bump seed is not being used
+            match try_find_pda(&program_id, &seed, None) {
+                Some(pubkey) => {
+                    // Encode the pubkey
+                    borsh_encode(pubkey.0, into)
+                },
+                None => Err(Error::encode("PDA could not be derived"))
+            }
+        },
+
+        DataValue::Vector(v) => {
+            let v = vector_normalize(&v);
+            if v.len() > (u32::MAX as usize) {
+                return Err(Error::encode(&format!(
+                    "vector length {} is greater than max of {} supported by borsh encoding",
+                    v.len(),
+                    u32::MAX
+                )));
+            }
+            borsh_encode(v.len() as u32, into)?;
+            for v in v.into_iter() {
+                write_rust_borsh_value(*v, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::Struct(v) => {
+            for v in v.into_iter() {
+                write_rust_borsh_value(*v, into)?;
+            }
+            Ok(())
+        },
+
+        DataValue::Enum { index, params } => {
+            if index > (u8::MAX as usize) {
+                return Err(Error::encode(&format!(
+                    "enum index {} is greater than max of {} supported by borsh encoding",
+                    index,
+                    u8::MAX
+                )));
+            }
+            borsh_encode(index as u8, into)?;
+            if let Some(params) = params {
+                write_rust_borsh_value(DataValue::Struct(params), into)
+            }
+            else {
+                Ok(())
+            }
+        },
+
+        DataValue::Some(v) => write_rust_borsh_value(DataValue::Enum { index : 1, params : Some(vec![v]) }, into),
+
+        DataValue::None => write_rust_borsh_value(DataValue::Enum { index : 0, params : None }, into)
+    }
+}
+
+fn c_align(
+    alignment : usize,
+    should_align : bool,
+    into : &mut Vec<u8>
+)
+{
+    if should_align {
+        while (into.len() % alignment) != 0 {
+            into.push(0);
+        }
+    }
+}
+
+fn c_alignment(dv : &DataValue) -> usize
+{
+    match dv {
+        DataValue::BoolList(_) => 1,
+        DataValue::U8List(_) => 1,
+        DataValue::U16List(_) => 2,
+        DataValue::U32List(_) => 4,
+        DataValue::U64List(_) => 8,
+        DataValue::I8List(_) => 1,
+        DataValue::I16List(_) => 2,
+        DataValue::I32List(_) => 4,
+        DataValue::I64List(_) => 8,
+        DataValue::F32List(_) => 4,
+        DataValue::F64List(_) => 8,
+        DataValue::String(_) => 1,
+        DataValue::CString { max_length: _, string: _ } => 1,
+        DataValue::Pubkey(_) => 1,
+        DataValue::Sha256(_) => 1,
+        DataValue::Pda(_, _) => 1,
+        DataValue::Bump(_, _) => 1,
+        DataValue::PdaNoBump(_, _) => 1,
+        DataValue::Vector(_) => 1,
+        DataValue::Struct(v) => c_max_alignment(v),
+        DataValue::Enum { index: _, params } => {
+            if let Some(p) = params {
+                c_max_alignment(&p)
+            }
+            else {
+                0
+            }
+        },
+        DataValue::Some(v) => c_alignment(v),
+        DataValue::None => 1
+    }
+}
+
+fn c_max_alignment(v : &Vec<Box<DataValue>>) -> usize
+{
+    let mut max = 1;
+
+    for dv in v {
+        let alignment = c_alignment(dv);
+        if alignment > max {
+            max = alignment;
+        }
+    }
+
+    max
+}
+
+pub fn write_c_value(
+    data_value : DataValue,
+    align : bool,
+    into : &mut Vec<u8>
+) -> Result<(), Error>
+{
+    match data_value {
+        DataValue::BoolList(v) => v.into_iter().for_each(|b| {
+            into.push(if b { 1 } else { 0 });
+        }),
+
+        DataValue::U8List(mut v) => {
+            into.append(&mut v);
+        },
+
+        DataValue::U16List(v) => v.into_iter().for_each(|u| {
+            c_align(2, align, into);
+            into.extend(u.to_le_bytes());
+        }),
+
+        DataValue::U32List(v) => v.into_iter().for_each(|u| {
+            c_align(4, align, into);
+            into.extend(u.to_le_bytes());
+        }),
+
+        DataValue::U64List(v) => v.into_iter().for_each(|u| {
+            c_align(8, align, into);
+            into.extend(u.to_le_bytes());
+        }),
+
+        DataValue::I8List(v) => v.into_iter().for_each(|i| {
+            into.extend(i.to_le_bytes());
+        }),
+
+        DataValue::I16List(v) => v.into_iter().for_each(|i| {
+            c_align(2, align, into);
+            into.extend(i.to_le_bytes());
+        }),
+
+        DataValue::I32List(v) => v.into_iter().for_each(|i| {
+            c_align(4, align, into);
+            into.extend(i.to_le_bytes());
+        }),
+
+        DataValue::I64List(v) => v.into_iter().for_each(|i| {
+            c_align(8, align, into);
+            into.extend(i.to_le_bytes());
+        }),
+
+        DataValue::F32List(v) => v.into_iter().for_each(|f| {
+            c_align(4, align, into);
+            into.extend(f.to_le_bytes());
+        }),
+
+        DataValue::F64List(v) => v.into_iter().for_each(|f| {
+            c_align(8, align, into);
+            into.extend(f.to_le_bytes());
+        }),
+
+        DataValue::String(s) => into.extend(s.as_bytes()),
+
+        DataValue::CString { max_length, string } => {
+            let zeroes = (max_length as usize).checked_sub(string.len()).ok_or_else(|| {
+                Error::encode(&format!("c_string {} has length greater than max_length {}", string.len(), max_length))
+            })?;
+            into.extend(string.as_bytes());
+            for _ in 0..zeroes {
+                into.push(0);
+            }
+        },
+
+        DataValue::Pubkey(p) => write_c_value(DataValue::U8List(p.0.into()), align, into)?,
+
+        DataValue::Sha256(p) => write_c_value(DataValue::U8List(p.into()), align, into)?,
+
+        DataValue::Pda(program_id, v) => {
+            // Encode v into a vector of data values.  No alignment is used since seeds should be directly
+            // concatenated.
+            let mut seed = vec![];
+            for dv in v.into_iter() {
+                write_c_value(*dv, false, &mut seed)?;
+            }
+            // Compute the address and bump seed, which must succeed since a
+            // bump seed is being used
+            let (pubkey, _) = find_pda(&program_id, &seed).unwrap();
+            // Encode the pubkey
+            write_c_value(DataValue::Pubkey(pubkey), false, into)?
+        },
+
+        DataValue::Bump(program_id, v) => {
+            // Encode v into a vector of data values.  No alignment is used since seeds should be directly
+            // concatenated.
+            let mut seed = vec![];
+            for dv in v.into_iter() {
+                write_c_value(*dv, false, &mut seed)?;
+            }
+            // Compute the address and bump seed, which must succeed since a
+            // bump seed is being used
+            let (_, bump_seed) = find_pda(&program_id, &seed).unwrap();
+            // Encode the pubkey
+            into.extend(bump_seed.to_le_bytes())
+        },
+
+        DataValue::PdaNoBump(program_id, v) => {
+            // Encode v into a vector of data values.  No alignment is used since seeds should be directly
+            // concatenated.
+            let mut seed = vec![];
+            for dv in v.into_iter() {
+                write_c_value(*dv, false, &mut seed)?;
+            }
+            // Compute the address and bump seed, which may fail since a bump seed is not being used
+            match try_find_pda(&program_id, &seed, None) {
+                Some(pubkey) => {
+                    // Encode the pubkey
+                    write_c_value(DataValue::Pubkey(pubkey), false, into)?
+                },
+                None => return Err(Error::encode("PDA could not be derived"))
+            }
+        },
+
+        DataValue::Vector(_) => return Err(Error::encode("vector value cannot be used with c encoding")),
+
+        DataValue::Struct(v) => {
+            let alignment = c_max_alignment(&v);
+            c_align(alignment, align, into);
+            for v in v.into_iter() {
+                write_c_value(*v, align, into)?;
+            }
+            c_align(alignment, align, into);
+        },
+
+        DataValue::Enum { index, params } => {
+            if index > (u8::MAX as usize) {
+                return Err(Error::encode(&format!(
+                    "enum index {} is greater than max of {} supported by c encoding",
+                    index,
+                    u8::MAX
+                )));
+            }
+            into.push(index as u8);
+            if let Some(params) = params {
+                write_c_value(DataValue::Struct(params), align, into)?
+            }
+        },
+
+        DataValue::Some(v) => write_c_value(DataValue::Enum { index : 1, params : Some(vec![v]) }, align, into)?,
+
+        DataValue::None => write_c_value(DataValue::Enum { index : 0, params : None }, align, into)?
+    }
+
+    Ok(())
+}
+
+pub fn write_data_value(
+    data_value : DataValue,
+    encoding : &Encoding,
+    into : &mut Vec<u8>
+) -> Result<(), Error>
+{
+    match encoding {
+        Encoding::RustBincodeVarInt => write_rust_bincode_value(data_value, true, into),
+        Encoding::RustBincodeFixedInt => write_rust_bincode_value(data_value, false, into),
+        Encoding::RustBorsh => write_rust_borsh_value(data_value, into),
+        Encoding::C => write_c_value(data_value, true, into)
+    }
+}
+
+fn split_proper(
+    mut w : &str,
+    delim : char
+) -> Vec<&str>
+{
+    let mut ret = vec![];
+
+    loop {
+        if let Some(index) = w.find(delim) {
+            if index > 0 {
+                ret.push(&w[0..index]);
+            }
+            ret.push(&w[index..(index + 1)]);
+            w = &w[(index + 1)..];
+        }
+        else {
+            if w.len() > 0 {
+                ret.push(&w);
+            }
+            break;
+        }
+    }
+
+    ret
+}
+
+pub fn make_words(w : &str) -> Vec<String>
+{
+    split_proper(w, '[')
+        .iter()
+        .map(|s| split_proper(s, ']'))
+        .flatten()
+        .collect::<Vec<&str>>()
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>()
+}
+
+pub fn make_encoding(s : &str) -> Result<Encoding, Error>
+{
+    match s {
+        "rust_bincode_varint" => Ok(Encoding::RustBincodeVarInt),
+        "rust_bincode_fixedint" => Ok(Encoding::RustBincodeFixedInt),
+        "rust_borsh" => Ok(Encoding::RustBorsh),
+        "c" => Ok(Encoding::C),
+        _ => Err(Error::parse(&format!("Invalid encoding: {}", s)))
+    }
+}
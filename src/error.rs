@@ -0,0 +1,256 @@
+use serde_json::{Number as json_Number, Value as json_Value};
+
+// The errors of solxact, one variant per category of failure.  Each category has its own exit code so that scripts
+// running the solxact program can tell why it failed.
+#[derive(Debug)]
+pub enum Error
+{
+    // Invalid command line arguments
+    Usage(String),
+
+    // Text that could not be parsed: encode parameters, data values, schemas, pubkeys, JSON transactions, and so on
+    Parse(String),
+
+    // Bytes that could not be decoded: transactions, and instruction data being decoded against a schema
+    Decode(String),
+
+    // Values that could not be encoded, or transactions that exceed the limits of the transaction format
+    Encode(String),
+
+    // Keypairs and seed phrases that could not be read, derived, or generated
+    Keypair(String),
+
+    // Signatures that are missing, invalid, or do not match their transaction
+    Signature(String),
+
+    // Failure to communicate with an RPC node, or an unexpected response from one
+    Rpc(String),
+
+    // A transaction that was rejected by simulation, preflight, or the cluster
+    Rejected(String),
+
+    // Failure to read or write files or standard streams
+    Io(String)
+}
+
+impl Error
+{
+    pub fn usage(msg : &str) -> Self
+    {
+        Error::Usage(msg.to_string())
+    }
+
+    pub fn parse(msg : &str) -> Self
+    {
+        Error::Parse(msg.to_string())
+    }
+
+    pub fn decode(msg : &str) -> Self
+    {
+        Error::Decode(msg.to_string())
+    }
+
+    pub fn encode(msg : &str) -> Self
+    {
+        Error::Encode(msg.to_string())
+    }
+
+    pub fn keypair(msg : &str) -> Self
+    {
+        Error::Keypair(msg.to_string())
+    }
+
+    pub fn signature(msg : &str) -> Self
+    {
+        Error::Signature(msg.to_string())
+    }
+
+    pub fn rpc(msg : &str) -> Self
+    {
+        Error::Rpc(msg.to_string())
+    }
+
+    pub fn rejected(msg : &str) -> Self
+    {
+        Error::Rejected(msg.to_string())
+    }
+
+    pub fn io(msg : &str) -> Self
+    {
+        Error::Io(msg.to_string())
+    }
+
+    pub fn category(&self) -> &'static str
+    {
+        match self {
+            Error::Usage(_) => "usage",
+            Error::Parse(_) => "parse",
+            Error::Decode(_) => "decode",
+            Error::Encode(_) => "encode",
+            Error::Keypair(_) => "keypair",
+            Error::Signature(_) => "signature",
+            Error::Rpc(_) => "rpc",
+            Error::Rejected(_) => "rejected",
+            Error::Io(_) => "io"
+        }
+    }
+
+    // The exit code of the solxact program when it fails with this error.  1 is left for failures that are not
+    // solxact errors, such as panics.
+    pub fn exit_code(&self) -> i32
+    {
+        match self {
+            Error::Usage(_) => 2,
+            Error::Parse(_) => 3,
+            Error::Decode(_) => 4,
+            Error::Encode(_) => 5,
+            Error::Keypair(_) => 6,
+            Error::Signature(_) => 7,
+            Error::Rpc(_) => 8,
+            Error::Rejected(_) => 9,
+            Error::Io(_) => 10
+        }
+    }
+
+    pub fn message(&self) -> &str
+    {
+        match self {
+            Error::Usage(msg) |
+            Error::Parse(msg) |
+            Error::Decode(msg) |
+            Error::Encode(msg) |
+            Error::Keypair(msg) |
+            Error::Signature(msg) |
+            Error::Rpc(msg) |
+            Error::Rejected(msg) |
+            Error::Io(msg) => msg
+        }
+    }
+
+    // Returns the same error, with its message preceded by what was being done when it occurred
+    pub fn with_context(
+        self,
+        context : &str
+    ) -> Self
+    {
+        let msg = format!("{}: {}", context, self.message());
+
+        match self {
+            Error::Usage(_) => Error::Usage(msg),
+            Error::Parse(_) => Error::Parse(msg),
+            Error::Decode(_) => Error::Decode(msg),
+            Error::Encode(_) => Error::Encode(msg),
+            Error::Keypair(_) => Error::Keypair(msg),
+            Error::Signature(_) => Error::Signature(msg),
+            Error::Rpc(_) => Error::Rpc(msg),
+            Error::Rejected(_) => Error::Rejected(msg),
+            Error::Io(_) => Error::Io(msg)
+        }
+    }
+
+    pub fn to_json(&self) -> json_Value
+    {
+        serde_json::json!({
+            "category" : self.category(),
+            "exit_code" : json_Number::from(self.exit_code()),
+            "message" : self.message()
+        })
+    }
+}
+
+impl std::error::Error for Error
+{
+}
+
+impl std::fmt::Display for Error
+{
+    fn fmt(
+        &self,
+        f : &mut std::fmt::Formatter
+    ) -> std::fmt::Result
+    {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::convert::From<std::io::Error> for Error
+{
+    fn from(e : std::io::Error) -> Self
+    {
+        Error::Io(e.to_string())
+    }
+}
+
+impl std::convert::From<std::fmt::Error> for Error
+{
+    fn from(e : std::fmt::Error) -> Self
+    {
+        Error::Io(e.to_string())
+    }
+}
+
+impl std::convert::From<ureq::Error> for Error
+{
+    fn from(e : ureq::Error) -> Self
+    {
+        Error::Rpc(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn each_category_has_its_own_exit_code()
+    {
+        let errors = [
+            Error::usage("m"),
+            Error::parse("m"),
+            Error::decode("m"),
+            Error::encode("m"),
+            Error::keypair("m"),
+            Error::signature("m"),
+            Error::rpc("m"),
+            Error::rejected("m"),
+            Error::io("m")
+        ];
+
+        assert_eq!(errors.iter().map(|e| e.exit_code()).collect::<Vec<_>>(), (2..=10).collect::<Vec<_>>());
+        assert_eq!(errors.iter().map(|e| e.category()).collect::<Vec<_>>(), vec![
+            "usage",
+            "parse",
+            "decode",
+            "encode",
+            "keypair",
+            "signature",
+            "rpc",
+            "rejected",
+            "io"
+        ]);
+        assert!(errors.iter().all(|e| e.message() == "m"));
+    }
+
+    #[test]
+    fn context_precedes_the_message_and_keeps_the_category()
+    {
+        let e = Error::signature("The transactions differ").with_context("Cannot merge b into a");
+
+        assert_eq!(e.category(), "signature");
+        assert_eq!(format!("{}", e), "Cannot merge b into a: The transactions differ");
+    }
+
+    #[test]
+    fn errors_convert_to_json()
+    {
+        assert_eq!(
+            Error::rejected("Transaction failed").to_json(),
+            serde_json::json!({ "category" : "rejected", "exit_code" : 9, "message" : "Transaction failed" })
+        );
+
+        let e : Error = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file").into();
+        assert_eq!(e.category(), "io");
+        assert_eq!(e.message(), "no such file");
+    }
+}
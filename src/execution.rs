@@ -0,0 +1,919 @@
+use crate::programs::{builtin_error_name, builtin_instruction_name, builtin_program_name};
+use crate::transaction::{Address, Transaction};
+use crate::Error;
+use serde_json::{Map as json_Map, Value as json_Value};
+use std::str::FromStr;
+
+// How an invocation of a program ended.  An invocation is incomplete if the logs end before it does, which happens
+// when the logs are truncated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InvocationResult
+{
+    Success,
+
+    Failed(String),
+
+    Incomplete
+}
+
+// A single invocation of a program, as recorded in a transaction's log messages, along with the invocations that it
+// made via cross-program invocation
+#[derive(Clone)]
+pub struct Invocation
+{
+    pub program_id : Address,
+
+    pub compute_units_consumed : Option<u64>,
+
+    pub result : InvocationResult,
+
+    // Messages logged by the program, and log lines not otherwise recognized
+    pub logs : Vec<String>,
+
+    // Data logged by the program; each entry holds the fields of a single Program data line
+    pub data : Vec<Vec<Vec<u8>>>,
+
+    pub return_data : Option<Vec<u8>>,
+
+    pub invocations : Vec<Invocation>
+}
+
+// Names of the custom errors of programs, as supplied by the user, keyed by program id
+#[derive(Clone, Default)]
+pub struct ErrorTables
+{
+    tables : Vec<(Address, Vec<(u32, String)>)>
+}
+
+impl Invocation
+{
+    fn new(program_id : Address) -> Self
+    {
+        Invocation {
+            program_id,
+            compute_units_consumed : None,
+            result : InvocationResult::Incomplete,
+            logs : vec![],
+            data : vec![],
+            return_data : None,
+            invocations : vec![]
+        }
+    }
+
+    pub fn to_json(
+        &self,
+        error_tables : &ErrorTables
+    ) -> json_Value
+    {
+        let mut map = json_Map::new();
+
+        map.insert("program_id".to_string(), json_Value::String(format!("{}", self.program_id)));
+
+        if let Some(name) = builtin_program_name(&self.program_id) {
+            map.insert("program_name".to_string(), json_Value::String(name.to_string()));
+        }
+
+        map.insert("compute_units_consumed".to_string(), self.compute_units_consumed.into());
+
+        match &self.result {
+            InvocationResult::Success => {
+                map.insert("result".to_string(), json_Value::String("success".to_string()));
+            },
+            InvocationResult::Failed(error) => {
+                map.insert("result".to_string(), json_Value::String("failed".to_string()));
+                map.insert("error".to_string(), json_Value::String(error.clone()));
+                if let Some(name) = custom_error_code(error).and_then(|code| error_tables.name(&self.program_id, code))
+                {
+                    map.insert("custom_name".to_string(), json_Value::String(name));
+                }
+            },
+            InvocationResult::Incomplete => {
+                map.insert("result".to_string(), json_Value::String("incomplete".to_string()));
+            }
+        }
+
+        map.insert("logs".to_string(), self.logs.clone().into());
+
+        map.insert(
+            "data".to_string(),
+            json_Value::Array(
+                self.data.iter().map(|fields| fields.iter().map(hex::encode).collect::<Vec<String>>().into()).collect()
+            )
+        );
+
+        map.insert("return_data".to_string(), self.return_data.as_ref().map(hex::encode).into());
+
+        map.insert(
+            "invocations".to_string(),
+            json_Value::Array(self.invocations.iter().map(|invocation| invocation.to_json(error_tables)).collect())
+        );
+
+        json_Value::Object(map)
+    }
+}
+
+impl ErrorTables
+{
+    pub fn add(
+        &mut self,
+        program_id : Address,
+        table : Vec<(u32, String)>
+    )
+    {
+        self.tables.push((program_id, table));
+    }
+
+    // Returns the name of a custom error of a program, from the tables supplied by the user if they name it, and
+    // otherwise from those of well-known programs
+    pub fn name(
+        &self,
+        program_id : &Address,
+        code : u32
+    ) -> Option<String>
+    {
+        self.tables
+            .iter()
+            .rev()
+            .filter(|(table_program_id, _)| table_program_id == program_id)
+            .flat_map(|(_, table)| table.iter())
+            .find(|(table_code, _)| *table_code == code)
+            .map(|(_, name)| name.clone())
+            .or_else(|| builtin_error_name(program_id, code).map(|name| name.to_string()))
+    }
+}
+
+// Reads the error table of a program from a JSON file, which is either an object mapping error codes to names, or an
+// Anchor IDL, whose errors array gives the code and name of each error
+pub fn read_error_table_file(path : &str) -> Result<Vec<(u32, String)>, Error>
+{
+    let file = std::fs::File::open(path).map_err(|e| Error::io(&format!("Failed to open {}: {}", path, e)))?;
+
+    let json : json_Value = serde_json::from_reader(file)
+        .map_err(|e| Error::parse(&format!("Failed to read error table {}: {}", path, e)))?;
+
+    let invalid = || Error::parse(&format!("Invalid error table in {}", path));
+
+    match json.get("errors") {
+        Some(json_Value::Array(errors)) => errors
+            .iter()
+            .map(|error| {
+                let code = error.get("code").and_then(|code| code.as_u64()).and_then(|code| u32::try_from(code).ok());
+                let name = error.get("name").and_then(|name| name.as_str());
+                match (code, name) {
+                    (Some(code), Some(name)) => Ok((code, name.to_string())),
+                    _ => Err(invalid())
+                }
+            })
+            .collect(),
+        Some(_) => Err(invalid()),
+        None => json
+            .as_object()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|(code, name)| match (parse_error_code(code), name.as_str()) {
+                (Some(code), Some(name)) => Ok((code, name.to_string())),
+                _ => Err(invalid())
+            })
+            .collect()
+    }
+}
+
+// Error codes may be given in decimal, or in hex with a 0x prefix
+fn parse_error_code(s : &str) -> Option<u32>
+{
+    match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => u32::from_str(s).ok()
+    }
+}
+
+// The runtime logs custom errors as "custom program error: 0x<CODE>"
+fn custom_error_code(error : &str) -> Option<u32>
+{
+    error.strip_prefix("custom program error: ").and_then(parse_error_code)
+}
+
+// Parses the log messages of a transaction into the tree of program invocations that they record.  Lines are
+// attributed to the invocation that was executing when they were logged.
+pub fn parse_invocations(logs : &[String]) -> Vec<Invocation>
+{
+    let mut top = vec![];
+
+    let mut stack : Vec<Invocation> = vec![];
+
+    let finish =
+        |invocation : Invocation, stack : &mut Vec<Invocation>, top : &mut Vec<Invocation>| match stack.last_mut() {
+            Some(parent) => parent.invocations.push(invocation),
+            None => top.push(invocation)
+        };
+
+    for line in logs {
+        if let Some(message) = line.strip_prefix("Program log: ") {
+            if let Some(current) = stack.last_mut() {
+                current.logs.push(message.to_string());
+                continue;
+            }
+        }
+        else if let Some(data) = line.strip_prefix("Program data: ") {
+            if let Some(current) = stack.last_mut() {
+                current.data.push(data.split_whitespace().map(|s| base64::decode(s).unwrap_or_default()).collect());
+                continue;
+            }
+        }
+        else if let Some(rest) = line.strip_prefix("Program return: ") {
+            if let (Some(current), Some((_, data))) = (stack.last_mut(), rest.split_once(' ')) {
+                current.return_data = base64::decode(data.trim()).ok();
+                continue;
+            }
+        }
+        else if let Some(rest) = line.strip_prefix("Program ") {
+            if let Some((program_id, event)) = rest.split_once(' ') {
+                if let Ok(program_id) = Address::from_str(program_id) {
+                    if event.starts_with("invoke [") {
+                        stack.push(Invocation::new(program_id));
+                        continue;
+                    }
+                    let is_current = stack.last().map(|current| current.program_id == program_id).unwrap_or(false);
+                    if is_current {
+                        if let Some(consumed) = event
+                            .strip_prefix("consumed ")
+                            .and_then(|s| s.split_whitespace().next())
+                            .and_then(|n| u64::from_str(n).ok())
+                        {
+                            if let Some(current) = stack.last_mut() {
+                                current.compute_units_consumed = Some(consumed);
+                            }
+                            continue;
+                        }
+                        let result = if event == "success" {
+                            Some(InvocationResult::Success)
+                        }
+                        else {
+                            event.strip_prefix("failed: ").map(|error| InvocationResult::Failed(error.to_string()))
+                        };
+                        if let Some(result) = result {
+                            if let Some(mut current) = stack.pop() {
+                                current.result = result;
+                                finish(current, &mut stack, &mut top);
+                            }
+                            continue;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(current) = stack.last_mut() {
+            current.logs.push(line.clone());
+        }
+    }
+
+    // Invocations still executing when the logs ended are incomplete
+    while let Some(current) = stack.pop() {
+        finish(current, &mut stack, &mut top);
+    }
+
+    top
+}
+
+// Returns the invocation that an invocation's failure came from: the deepest invocation that failed beneath it, or
+// else the invocation itself
+fn deepest_failed_invocation(invocation : &Invocation) -> &Invocation
+{
+    match invocation.invocations.iter().rev().find(|inner| matches!(inner.result, InvocationResult::Failed(_))) {
+        Some(inner) => deepest_failed_invocation(inner),
+        None => invocation
+    }
+}
+
+// Returns the lines of log messages as returned by an RPC node
+fn log_lines(logs : Option<&json_Value>) -> Vec<String>
+{
+    logs.and_then(|logs| logs.as_array())
+        .map(|logs| logs.iter().filter_map(|line| line.as_str().map(|line| line.to_string())).collect())
+        .unwrap_or_default()
+}
+
+// Describes the error that a transaction failed with, as returned by an RPC node along with the transaction's log
+// messages, if any.  An InstructionError is mapped back to the instruction of the transaction that failed.  The
+// program that the error came from is that of the deepest invocation that failed within the instruction, as recorded
+// in the logs, or the instruction's own program if there are no logs, and a custom error code is mapped to its name
+// in that program where it is known.
+pub fn describe_transaction_error(
+    err : &json_Value,
+    logs : Option<&json_Value>,
+    transaction : &Transaction,
+    error_tables : &ErrorTables
+) -> json_Value
+{
+    let mut map = json_Map::new();
+
+    let (index, instruction_error) = match err.get("InstructionError").and_then(|e| e.as_array()) {
+        Some(v) if v.len() == 2 && v[0].is_u64() => (v[0].as_u64().unwrap_or(0), &v[1]),
+        _ => {
+            let error = error_text(err);
+            map.insert("error".to_string(), json_Value::String(error.clone()));
+            map.insert("message".to_string(), json_Value::String(format!("Transaction failed: {}", error)));
+            return json_Value::Object(map);
+        }
+    };
+
+    map.insert("instruction_index".to_string(), index.into());
+
+    let mut message = format!("Instruction {}", index);
+
+    let instruction = usize::try_from(index).ok().and_then(|index| transaction.instructions.get(index));
+
+    let program_id = instruction.map(|instruction| instruction.program_address.clone());
+
+    // The top-level invocations are of the instructions in order, up to the one that failed
+    let failed_program_id = usize::try_from(index)
+        .ok()
+        .and_then(|index| parse_invocations(&log_lines(logs)).get(index).cloned())
+        .filter(|invocation| Some(&invocation.program_id) == program_id.as_ref())
+        .map(|invocation| deepest_failed_invocation(&invocation).program_id.clone())
+        .or_else(|| program_id.clone());
+
+    if let Some(instruction) = instruction {
+        map.insert("program_id".to_string(), json_Value::String(format!("{}", instruction.program_address)));
+        let program_name = builtin_program_name(&instruction.program_address);
+        if let Some(program_name) = program_name {
+            map.insert("program_name".to_string(), json_Value::String(program_name.to_string()));
+        }
+        let instruction_name = builtin_instruction_name(instruction);
+        if let Some(instruction_name) = instruction_name {
+            map.insert("instruction_name".to_string(), json_Value::String(instruction_name.to_string()));
+        }
+        match (program_name, instruction_name) {
+            (Some(program_name), Some(instruction_name)) => {
+                message.push_str(&format!(" ({} {})", program_name, instruction_name))
+            },
+            (Some(program_name), None) => message.push_str(&format!(" ({})", program_name)),
+            _ => message.push_str(&format!(" ({})", instruction.program_address))
+        }
+    }
+
+    if let Some(failed_program_id) = &failed_program_id {
+        map.insert("error_program_id".to_string(), json_Value::String(format!("{}", failed_program_id)));
+    }
+
+    // An error that came from a program invoked by the instruction's program names that program
+    let failed_in = match &failed_program_id {
+        Some(failed_program_id) if Some(failed_program_id) != program_id.as_ref() => {
+            match builtin_program_name(failed_program_id) {
+                Some(program_name) => format!(" in {}", program_name),
+                None => format!(" in {}", failed_program_id)
+            }
+        },
+        _ => "".to_string()
+    };
+
+    match instruction_error.get("Custom").and_then(|code| code.as_u64()).and_then(|code| u32::try_from(code).ok()) {
+        Some(code) => {
+            map.insert("error".to_string(), json_Value::String("Custom".to_string()));
+            map.insert("custom_code".to_string(), code.into());
+            message.push_str(&format!(" failed{}: custom program error {}", failed_in, code));
+            if let Some(name) = failed_program_id.and_then(|program_id| error_tables.name(&program_id, code)) {
+                message.push_str(&format!(" ({})", name));
+                map.insert("custom_name".to_string(), json_Value::String(name));
+            }
+        },
+        None => {
+            let error = error_text(instruction_error);
+            message.push_str(&format!(" failed{}: {}", failed_in, error));
+            map.insert("error".to_string(), json_Value::String(error));
+        }
+    }
+
+    map.insert("message".to_string(), json_Value::String(message));
+
+    json_Value::Object(map)
+}
+
+// Returns the description of the error that a transaction failed with, or null if it did not fail
+pub fn transaction_error_description(
+    err : &json_Value,
+    logs : Option<&json_Value>,
+    transaction : &Transaction,
+    error_tables : &ErrorTables
+) -> json_Value
+{
+    match err {
+        json_Value::Null => json_Value::Null,
+        err => describe_transaction_error(err, logs, transaction, error_tables)
+    }
+}
+
+// Makes the tree of program invocations recorded in log messages as returned by an RPC node
+pub fn invocations_json(
+    logs : Option<&json_Value>,
+    error_tables : &ErrorTables
+) -> json_Value
+{
+    json_Value::Array(
+        parse_invocations(&log_lines(logs)).iter().map(|invocation| invocation.to_json(error_tables)).collect()
+    )
+}
+
+// Returns the error that a failed transaction is rejected with, giving the description of its failure
+pub fn transaction_rejected(description : &json_Value) -> Error
+{
+    match description.get("message").and_then(|message| message.as_str()) {
+        Some(message) => Error::rejected(message),
+        None => Error::rejected(&format!("{}", description))
+    }
+}
+
+// Fails with the transaction's rejection if the report of its execution, which gives its err and the error that
+// describes it, shows that it failed
+pub fn check_execution_report(report : &json_Value) -> Result<(), Error>
+{
+    match report.get("err") {
+        None | Some(json_Value::Null) => Ok(()),
+        Some(_) => Err(transaction_rejected(report.get("error").unwrap_or(&json_Value::Null)))
+    }
+}
+
+// Makes the report of a simulation from the value of a simulation result, decoding the return data and account data
+// that the RPC node returns base64-encoded.  accounts are the addresses whose states the simulation returned.
+pub fn simulation_report(
+    value : json_Value,
+    accounts : &[Address],
+    transaction : &Transaction,
+    error_tables : &ErrorTables
+) -> Result<json_Value, Error>
+{
+    let base64_to_hex = |v : &json_Value| -> Result<String, Error> {
+        match v.as_array().and_then(|a| a.first()).and_then(|s| s.as_str()) {
+            Some(s) => Ok(hex::encode(base64::decode(s).map_err(|e| Error::rpc(&format!("Invalid base64: {}", e)))?)),
+            None => Err(Error::rpc("Invalid data in simulation result"))
+        }
+    };
+
+    let return_data = match value.get("returnData") {
+        Some(json_Value::Object(return_data)) => serde_json::json!({
+            "program_id" : return_data.get("programId"),
+            "data" : base64_to_hex(return_data.get("data").unwrap_or(&json_Value::Null))?
+        }),
+        _ => json_Value::Null
+    };
+
+    let states = match value.get("accounts") {
+        Some(json_Value::Array(states)) => states.clone(),
+        _ => vec![]
+    };
+
+    let accounts = accounts
+        .iter()
+        .zip(states.iter().chain(std::iter::repeat(&json_Value::Null)))
+        .map(|(address, state)| {
+            Ok(serde_json::json!({
+                "address" : format!("{}", address),
+                "state" : match state {
+                    json_Value::Object(state) => serde_json::json!({
+                        "lamports" : state.get("lamports"),
+                        "owner" : state.get("owner"),
+                        "executable" : state.get("executable"),
+                        "rent_epoch" : state.get("rentEpoch"),
+                        "data" : base64_to_hex(state.get("data").unwrap_or(&json_Value::Null))?
+                    }),
+                    _ => json_Value::Null
+                }
+            }))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let error = transaction_error_description(
+        value.get("err").unwrap_or(&json_Value::Null),
+        value.get("logs"),
+        transaction,
+        error_tables
+    );
+
+    Ok(serde_json::json!({
+        "err" : value.get("err"),
+        "error" : error,
+        "logs" : value.get("logs"),
+        "invocations" : invocations_json(value.get("logs"), error_tables),
+        "units_consumed" : value.get("unitsConsumed"),
+        "return_data" : return_data,
+        "accounts" : accounts
+    }))
+}
+
+// Completes the report of a submitted transaction's execution, which gives its signature, slot, err, and, when they
+// are known, units_consumed and logs, by adding the error that describes err and the invocations that logs records
+pub fn complete_submission_report(
+    mut report : json_Value,
+    transaction : &Transaction,
+    error_tables : &ErrorTables
+) -> json_Value
+{
+    let err = report.get("err").cloned().unwrap_or(json_Value::Null);
+
+    let description = transaction_error_description(&err, report.get("logs"), transaction, error_tables);
+
+    let invocations = invocations_json(report.get("logs"), error_tables);
+
+    if let json_Value::Object(map) = &mut report {
+        map.insert("error".to_string(), description);
+        map.insert("invocations".to_string(), invocations);
+    }
+
+    report
+}
+
+// Errors are either a bare name, or an object with a single key naming the error and a value giving its details
+fn error_text(err : &json_Value) -> String
+{
+    match err {
+        json_Value::String(s) => s.clone(),
+        json_Value::Object(map) if map.len() == 1 => match map.iter().next() {
+            Some((name, json_Value::String(detail))) => format!("{}: {}", name, detail),
+            Some((name, detail)) => format!("{}: {}", name, detail),
+            None => format!("{}", err)
+        },
+        _ => format!("{}", err)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::tests::{mock_rpc_node, system_transfer};
+    use crate::rpc::{simulate_transaction, SimulateOptions};
+    use crate::transaction::Instruction;
+    use crate::{SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+    const PROGRAM : &str = "4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM";
+
+    fn logs(lines : &[&str]) -> Vec<String>
+    {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn address(s : &str) -> Address
+    {
+        Address::from_str(s).unwrap()
+    }
+
+    // A System program transfer followed by an instruction of PROGRAM
+    fn transaction() -> Transaction
+    {
+        let mut transaction = system_transfer();
+
+        transaction.add_instruction(Instruction {
+            program_address : address(PROGRAM),
+            addresses : vec![],
+            data : vec![0]
+        });
+
+        transaction
+    }
+
+    #[test]
+    fn parses_nested_invocations()
+    {
+        let invocations = parse_invocations(&logs(&[
+            "Program 11111111111111111111111111111111 invoke [1]",
+            "Program 11111111111111111111111111111111 success",
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM invoke [1]",
+            "Program log: Instruction: Swap",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Program 11111111111111111111111111111111 success",
+            "Program data: AQI= Aw==",
+            "Program return: 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM BQY=",
+            "unrecognized line",
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM consumed 2400 of 200000 compute units",
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM success"
+        ]));
+
+        assert_eq!(invocations.len(), 2);
+
+        assert!(invocations[0].program_id == address(SYSTEM_PROGRAM_ID));
+        assert_eq!(invocations[0].result, InvocationResult::Success);
+        assert!(invocations[0].invocations.is_empty());
+
+        let program = &invocations[1];
+        assert!(program.program_id == address(PROGRAM));
+        assert_eq!(program.result, InvocationResult::Success);
+        assert_eq!(program.compute_units_consumed, Some(2400));
+        assert_eq!(program.logs, vec!["Instruction: Swap", "unrecognized line"]);
+        assert_eq!(program.data, vec![vec![vec![1, 2], vec![3]]]);
+        assert_eq!(program.return_data, Some(vec![5, 6]));
+        assert_eq!(program.invocations.len(), 1);
+        assert!(program.invocations[0].program_id == address(SYSTEM_PROGRAM_ID));
+        assert_eq!(program.invocations[0].result, InvocationResult::Success);
+    }
+
+    #[test]
+    fn parses_a_failed_invocation_within_its_caller()
+    {
+        let invocations = parse_invocations(&logs(&[
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM invoke [1]",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Transfer: insufficient lamports 5, need 10",
+            "Program 11111111111111111111111111111111 failed: custom program error: 0x1",
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM failed: custom program error: 0x1"
+        ]));
+
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].result, InvocationResult::Failed("custom program error: 0x1".to_string()));
+
+        let inner = &invocations[0].invocations[0];
+        assert_eq!(inner.result, InvocationResult::Failed("custom program error: 0x1".to_string()));
+        assert_eq!(inner.logs, vec!["Transfer: insufficient lamports 5, need 10"]);
+
+        // The custom error is named from the well-known program's errors
+        let json = inner.to_json(&ErrorTables::default());
+        assert_eq!(json["program_name"], "System");
+        assert_eq!(json["result"], "failed");
+        assert_eq!(json["custom_name"], "ResultWithNegativeLamports");
+    }
+
+    #[test]
+    fn leaves_invocations_incomplete_when_the_logs_are_truncated()
+    {
+        let invocations = parse_invocations(&logs(&[
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM invoke [1]",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Program 11111111111111111111111111111111 success",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Log truncated"
+        ]));
+
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].result, InvocationResult::Incomplete);
+        assert_eq!(invocations[0].invocations.len(), 2);
+        assert_eq!(invocations[0].invocations[0].result, InvocationResult::Success);
+        assert_eq!(invocations[0].invocations[1].result, InvocationResult::Incomplete);
+        assert_eq!(invocations[0].invocations[1].logs, vec!["Log truncated"]);
+
+        assert_eq!(invocations[0].to_json(&ErrorTables::default())["result"], "incomplete");
+
+        assert!(parse_invocations(&[]).is_empty());
+    }
+
+    #[test]
+    fn describes_instruction_errors_by_instruction_and_error_name()
+    {
+        let transaction = transaction();
+
+        let mut error_tables = ErrorTables::default();
+
+        let description = describe_transaction_error(
+            &serde_json::json!({ "InstructionError" : [0, { "Custom" : 1 }] }),
+            None,
+            &transaction,
+            &error_tables
+        );
+
+        assert_eq!(
+            description,
+            serde_json::json!({
+                "instruction_index" : 0,
+                "program_id" : SYSTEM_PROGRAM_ID,
+                "program_name" : "System",
+                "instruction_name" : "Transfer",
+                "error_program_id" : SYSTEM_PROGRAM_ID,
+                "error" : "Custom",
+                "custom_code" : 1,
+                "custom_name" : "ResultWithNegativeLamports",
+                "message" : concat!(
+                    "Instruction 0 (System Transfer) failed: custom program error 1 ",
+                    "(ResultWithNegativeLamports)"
+                )
+            })
+        );
+
+        let err = serde_json::json!({ "InstructionError" : [1, { "Custom" : 6000 }] });
+
+        assert_eq!(
+            describe_transaction_error(&err, None, &transaction, &error_tables)["message"],
+            format!("Instruction 1 ({}) failed: custom program error 6000", PROGRAM)
+        );
+
+        error_tables.add(address(PROGRAM), vec![(6000, "SlippageExceeded".to_string())]);
+
+        assert_eq!(
+            describe_transaction_error(&err, None, &transaction, &error_tables)["custom_name"],
+            "SlippageExceeded"
+        );
+
+        let description = describe_transaction_error(
+            &serde_json::json!({ "InstructionError" : [1, "InvalidAccountData"] }),
+            None,
+            &transaction,
+            &error_tables
+        );
+        assert_eq!(description["error"], "InvalidAccountData");
+        assert_eq!(description["message"], format!("Instruction 1 ({}) failed: InvalidAccountData", PROGRAM));
+    }
+
+    #[test]
+    fn describes_custom_errors_by_the_program_that_failed_with_them()
+    {
+        let transaction = transaction();
+
+        let error_tables = ErrorTables::default();
+
+        let err = serde_json::json!({ "InstructionError" : [1, { "Custom" : 1 }] });
+
+        let logs = serde_json::json!([
+            "Program 11111111111111111111111111111111 invoke [1]",
+            "Program 11111111111111111111111111111111 success",
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM invoke [1]",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Program 11111111111111111111111111111111 success",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]",
+            "Program log: Error: insufficient funds",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA failed: custom program error: 0x1",
+            "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM failed: custom program error: 0x1"
+        ]);
+
+        // The error came from the Token program, invoked by the instruction's program
+        let description = describe_transaction_error(&err, Some(&logs), &transaction, &error_tables);
+        assert_eq!(description["program_id"], PROGRAM);
+        assert_eq!(description["error_program_id"], TOKEN_PROGRAM_ID);
+        assert_eq!(description["custom_name"], "InsufficientFunds");
+        assert_eq!(
+            description["message"],
+            format!("Instruction 1 ({}) failed in Token: custom program error 1 (InsufficientFunds)", PROGRAM)
+        );
+
+        // Without logs, the error is taken to be the instruction's program's, which does not name it
+        let description = describe_transaction_error(&err, None, &transaction, &error_tables);
+        assert_eq!(description["error_program_id"], PROGRAM);
+        assert!(description.get("custom_name").is_none());
+    }
+
+    #[test]
+    fn describes_transaction_errors_that_are_not_of_an_instruction()
+    {
+        let transaction = transaction();
+
+        let error_tables = ErrorTables::default();
+
+        assert_eq!(
+            describe_transaction_error(&serde_json::json!("BlockhashNotFound"), None, &transaction, &error_tables),
+            serde_json::json!({ "error" : "BlockhashNotFound", "message" : "Transaction failed: BlockhashNotFound" })
+        );
+
+        assert_eq!(
+            describe_transaction_error(
+                &serde_json::json!({ "InsufficientFundsForRent" : { "account_index" : 0 } }),
+                None,
+                &transaction,
+                &error_tables
+            )["message"],
+            "Transaction failed: InsufficientFundsForRent: {\"account_index\":0}"
+        );
+
+        assert!(transaction_error_description(&json_Value::Null, None, &transaction, &error_tables).is_null());
+
+        assert!(check_execution_report(&serde_json::json!({ "err" : null })).is_ok());
+        assert_eq!(
+            check_execution_report(&serde_json::json!({
+                "err" : "BlockhashNotFound",
+                "error" : { "message" : "Transaction failed: BlockhashNotFound" }
+            }))
+            .err()
+            .unwrap()
+            .message(),
+            "Transaction failed: BlockhashNotFound"
+        );
+    }
+
+    #[test]
+    fn reads_error_tables_from_code_maps_and_anchor_idls()
+    {
+        let path = std::env::temp_dir().join(format!("solxact-execution-test-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        std::fs::write(path, r#"{ "6000" : "SlippageExceeded", "0x1771" : "Stale" }"#).unwrap();
+        let mut table = read_error_table_file(path).unwrap();
+        table.sort();
+        assert_eq!(table, vec![(6000, "SlippageExceeded".to_string()), (6001, "Stale".to_string())]);
+
+        std::fs::write(path, r#"{ "errors" : [ { "code" : 6000, "name" : "SlippageExceeded", "msg" : "x" } ] }"#)
+            .unwrap();
+        assert_eq!(read_error_table_file(path).unwrap(), vec![(6000, "SlippageExceeded".to_string())]);
+
+        std::fs::write(path, r#"{ "errors" : [ { "code" : "6000" } ] }"#).unwrap();
+        assert!(read_error_table_file(path).is_err());
+
+        std::fs::write(path, r#"{ "six" : "SlippageExceeded" }"#).unwrap();
+        assert!(read_error_table_file(path).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn simulation_report_decodes_return_data_and_account_states()
+    {
+        let value = serde_json::json!({
+            "err" : { "InstructionError" : [1, { "Custom" : 6000 }] },
+            "logs" : [
+                "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM invoke [1]",
+                "Program 4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM failed: custom program error: 0x1770"
+            ],
+            "unitsConsumed" : 2400,
+            "returnData" : { "programId" : PROGRAM, "data" : ["AQI=", "base64"] },
+            "accounts" : [
+                {
+                    "lamports" : 5,
+                    "owner" : SYSTEM_PROGRAM_ID,
+                    "executable" : false,
+                    "rentEpoch" : 18446744073709551615_u64,
+                    "data" : ["Aw==", "base64"]
+                },
+                null
+            ]
+        });
+
+        let accounts = [address(PROGRAM), address(SYSTEM_PROGRAM_ID)];
+
+        let mut error_tables = ErrorTables::default();
+        error_tables.add(address(PROGRAM), vec![(6000, "SlippageExceeded".to_string())]);
+
+        let report = simulation_report(value, &accounts, &transaction(), &error_tables).unwrap();
+
+        assert_eq!(report["units_consumed"], 2400);
+        assert_eq!(report["return_data"], serde_json::json!({ "program_id" : PROGRAM, "data" : "0102" }));
+        assert_eq!(
+            report["accounts"],
+            serde_json::json!([
+                {
+                    "address" : PROGRAM,
+                    "state" : {
+                        "lamports" : 5,
+                        "owner" : SYSTEM_PROGRAM_ID,
+                        "executable" : false,
+                        "rent_epoch" : 18446744073709551615_u64,
+                        "data" : "03"
+                    }
+                },
+                { "address" : SYSTEM_PROGRAM_ID, "state" : null }
+            ])
+        );
+        assert_eq!(report["error"]["custom_name"], "SlippageExceeded");
+        assert_eq!(report["invocations"][0]["custom_name"], "SlippageExceeded");
+        assert_eq!(report["logs"].as_array().unwrap().len(), 2);
+
+        assert!(check_execution_report(&report).is_err());
+    }
+
+    #[test]
+    fn simulation_report_of_a_successful_simulation_has_no_error()
+    {
+        let report = simulation_report(
+            serde_json::json!({ "err" : null, "logs" : [], "unitsConsumed" : 150 }),
+            &[],
+            &transaction(),
+            &ErrorTables::default()
+        )
+        .unwrap();
+
+        assert!(report["error"].is_null());
+        assert!(report["return_data"].is_null());
+        assert_eq!(report["accounts"], serde_json::json!([]));
+        assert!(check_execution_report(&report).is_ok());
+
+        let invalid = serde_json::json!({ "err" : null, "returnData" : { "programId" : PROGRAM, "data" : ["!"] } });
+        assert!(simulation_report(invalid, &[], &transaction(), &ErrorTables::default()).is_err());
+    }
+
+    #[test]
+    fn simulate_transaction_sends_the_simulation_options()
+    {
+        let endpoint = mock_rpc_node(|method, params, _| {
+            assert_eq!(method, "simulateTransaction");
+            assert_eq!(
+                params[1],
+                serde_json::json!({
+                    "encoding" : "base64",
+                    "sigVerify" : true,
+                    "replaceRecentBlockhash" : false,
+                    "commitment" : "processed",
+                    "minContextSlot" : 100,
+                    "accounts" : { "encoding" : "base64", "addresses" : [PROGRAM] }
+                })
+            );
+            serde_json::json!({ "result" : { "context" : { "slot" : 101 }, "value" : { "err" : null } } })
+        });
+
+        let options = SimulateOptions {
+            sig_verify : true,
+            replace_recent_blockhash : false,
+            commitment : Some("processed".to_string()),
+            min_context_slot : Some(100),
+            accounts : vec![address(PROGRAM)]
+        };
+
+        assert_eq!(
+            simulate_transaction(&endpoint, &transaction(), &options).unwrap(),
+            serde_json::json!({ "err" : null })
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use crate::transaction::Transaction;
+use crate::Error;
+
+// Formats in which encoded transactions may be read and written
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionFormat
+{
+    Raw,
+
+    Base64,
+
+    Base58,
+
+    Hex
+}
+
+// The formats that subcommands read transactions in and write transactions in.  An input format of None means that
+// the format of each transaction read is detected automatically.
+pub struct TransactionFormats
+{
+    pub input : Option<TransactionFormat>,
+
+    pub output : TransactionFormat
+}
+
+impl std::str::FromStr for TransactionFormat
+{
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err>
+    {
+        match s {
+            "raw" => Ok(TransactionFormat::Raw),
+            "base64" => Ok(TransactionFormat::Base64),
+            "base58" => Ok(TransactionFormat::Base58),
+            "hex" => Ok(TransactionFormat::Hex),
+            _ => Err(format!("Invalid transaction format: {}", s))
+        }
+    }
+}
+
+impl std::fmt::Display for TransactionFormat
+{
+    fn fmt(
+        &self,
+        f : &mut std::fmt::Formatter
+    ) -> std::fmt::Result
+    {
+        match self {
+            TransactionFormat::Raw => write!(f, "raw"),
+            TransactionFormat::Base64 => write!(f, "base64"),
+            TransactionFormat::Base58 => write!(f, "base58"),
+            TransactionFormat::Hex => write!(f, "hex")
+        }
+    }
+}
+
+impl Default for TransactionFormats
+{
+    fn default() -> Self
+    {
+        TransactionFormats { input : None, output : TransactionFormat::Raw }
+    }
+}
+
+// Decodes text in the given format into raw transaction bytes
+fn decode_text(
+    text : &str,
+    format : TransactionFormat
+) -> Result<Vec<u8>, Error>
+{
+    match format {
+        TransactionFormat::Raw => Ok(text.as_bytes().to_vec()),
+        TransactionFormat::Base64 => base64::decode(text).map_err(|e| Error::decode(&format!("Invalid base64: {}", e))),
+        TransactionFormat::Base58 => {
+            bs58::decode(text).into_vec().map_err(|e| Error::decode(&format!("Invalid base58: {}", e)))
+        },
+        TransactionFormat::Hex => hex::decode(text).map_err(|e| Error::decode(&format!("Invalid hex: {}", e)))
+    }
+}
+
+// Decodes a transaction from bytes, requiring that the transaction use all of the bytes
+fn decode_exact(bytes : &[u8]) -> Result<Transaction, Error>
+{
+    let mut remaining = bytes;
+
+    let transaction = Transaction::decode(&mut remaining)?;
+
+    if remaining.is_empty() {
+        Ok(transaction)
+    }
+    else {
+        Err(Error::decode(&format!("{} bytes remain after the end of the transaction", remaining.len())))
+    }
+}
+
+impl TransactionFormats
+{
+    pub fn read_transaction(
+        &self,
+        r : &mut dyn std::io::Read
+    ) -> Result<Transaction, Error>
+    {
+        let mut bytes = vec![];
+
+        r.read_to_end(&mut bytes).map_err(|e| Error::io(&format!("Failed to read transaction: {}", e)))?;
+
+        match self.input {
+            Some(TransactionFormat::Raw) => Transaction::decode(&mut bytes.as_slice()),
+            Some(format) => {
+                let text = std::str::from_utf8(&bytes)
+                    .map_err(|_| Error::decode(&format!("Invalid {} transaction", format)))?;
+                decode_exact(&decode_text(text.trim(), format)?)
+            },
+            None => Self::detect_transaction(&bytes)
+        }
+    }
+
+    // A raw transaction always begins with a signature count no greater than 18, which is never a printable
+    // character, so raw is tried first.  Otherwise the input is text, and each text format is tried in turn, from the
+    // most restrictive alphabet to the least; the first that decodes into a complete transaction is used.
+    fn detect_transaction(bytes : &[u8]) -> Result<Transaction, Error>
+    {
+        let raw_err = match Transaction::decode(&mut &bytes[..]) {
+            Ok(transaction) => return Ok(transaction),
+            Err(e) => e
+        };
+
+        let text = match std::str::from_utf8(bytes) {
+            Ok(text) => text.trim(),
+            Err(_) => return Err(raw_err)
+        };
+
+        for format in [TransactionFormat::Hex, TransactionFormat::Base58, TransactionFormat::Base64] {
+            if let Ok(transaction) = decode_text(text, format).and_then(|bytes| decode_exact(&bytes)) {
+                return Ok(transaction);
+            }
+        }
+
+        if text.is_empty() {
+            Err(Error::decode("No transaction was supplied"))
+        }
+        else {
+            Err(Error::decode("Transaction is not in any of the raw, base64, base58, or hex formats"))
+        }
+    }
+
+    // Text formats are written with a trailing newline
+    pub fn write_transaction(
+        &self,
+        transaction : &Transaction,
+        w : &mut dyn std::io::Write
+    ) -> Result<(), Error>
+    {
+        let mut bytes = vec![];
+
+        transaction.encode(&mut bytes)?;
+
+        let text = match self.output {
+            TransactionFormat::Raw => {
+                return w.write_all(&bytes).map_err(|e| Error::io(&format!("Failed to write transaction: {}", e)))
+            },
+            TransactionFormat::Base64 => base64::encode(&bytes),
+            TransactionFormat::Base58 => bs58::encode(&bytes).into_string(),
+            TransactionFormat::Hex => hex::encode(&bytes)
+        };
+
+        writeln!(w, "{}", text).map_err(|e| Error::io(&format!("Failed to write transaction: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::tests::system_transfer as transaction;
+
+    const FORMATS : [TransactionFormat; 4] =
+        [TransactionFormat::Raw, TransactionFormat::Base64, TransactionFormat::Base58, TransactionFormat::Hex];
+
+    fn encoded(transaction : &Transaction) -> Vec<u8>
+    {
+        let mut bytes = vec![];
+
+        transaction.encode(&mut bytes).unwrap();
+
+        bytes
+    }
+
+    fn written(format : TransactionFormat) -> Vec<u8>
+    {
+        let mut w = vec![];
+
+        TransactionFormats { input : None, output : format }.write_transaction(&transaction(), &mut w).unwrap();
+
+        w
+    }
+
+    fn read(
+        input : Option<TransactionFormat>,
+        bytes : &[u8]
+    ) -> Result<Transaction, Error>
+    {
+        TransactionFormats { input, output : TransactionFormat::Raw }.read_transaction(&mut &bytes[..])
+    }
+
+    #[test]
+    fn writes_each_format()
+    {
+        let bytes = encoded(&transaction());
+
+        assert_eq!(written(TransactionFormat::Raw), bytes);
+        assert_eq!(written(TransactionFormat::Base64), format!("{}\n", base64::encode(&bytes)).into_bytes());
+        assert_eq!(
+            written(TransactionFormat::Base58),
+            format!("{}\n", bs58::encode(&bytes).into_string()).into_bytes()
+        );
+        assert_eq!(written(TransactionFormat::Hex), format!("{}\n", hex::encode(&bytes)).into_bytes());
+    }
+
+    #[test]
+    fn reads_each_format_that_is_given()
+    {
+        for format in FORMATS {
+            assert_eq!(encoded(&read(Some(format), &written(format)).unwrap()), encoded(&transaction()));
+        }
+    }
+
+    #[test]
+    fn detects_each_format()
+    {
+        for format in FORMATS {
+            assert_eq!(encoded(&read(None, &written(format)).unwrap()), encoded(&transaction()));
+        }
+
+        // Surrounding whitespace is ignored in text formats
+        let text = format!("  {}\r\n\n", hex::encode(encoded(&transaction())));
+        assert_eq!(encoded(&read(None, text.as_bytes()).unwrap()), encoded(&transaction()));
+    }
+
+    #[test]
+    fn rejects_input_that_is_not_a_transaction()
+    {
+        assert_eq!(read(None, b"").err().unwrap().message(), "No transaction was supplied");
+        assert_eq!(read(None, b" \n").err().unwrap().message(), "No transaction was supplied");
+        assert_eq!(
+            read(None, b"not a transaction").err().unwrap().message(),
+            "Transaction is not in any of the raw, base64, base58, or hex formats"
+        );
+
+        // A truncated transaction is not mistaken for another format
+        let text = hex::encode(&encoded(&transaction())[..40]);
+        assert!(read(None, text.as_bytes()).is_err());
+
+        // A text format that is given must decode into exactly one transaction
+        let text = format!("{}00", hex::encode(encoded(&transaction())));
+        assert_eq!(
+            read(Some(TransactionFormat::Hex), text.as_bytes()).err().unwrap().message(),
+            "1 bytes remain after the end of the transaction"
+        );
+        assert!(read(Some(TransactionFormat::Base64), &written(TransactionFormat::Hex)).is_err());
+    }
+}
@@ -0,0 +1,517 @@
+use crate::bip39::{generate_mnemonic, validate_mnemonic};
+use crate::transaction::Pubkey;
+use crate::Error;
+use ed25519_dalek_bip32::{DerivationPath, ExtendedSecretKey};
+use sha2::Sha512;
+use std::fmt::Write;
+use std::io::Write as IoWrite;
+use std::str::FromStr;
+
+const BASE58_ALPHABET : &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// How often progress is reported while grinding for a keypair
+pub const GRIND_PROGRESS_INTERVAL_SECONDS : u64 = 5;
+
+// Keypair sources beginning with these prefixes derive the keypair from a BIP39 seed phrase that is prompted for
+pub const PROMPT_KEYPAIR_PREFIX : &str = "prompt://";
+pub const SEED_KEYPAIR_PREFIX : &str = "seed:";
+
+// BIP39 seeds are derived from seed phrases using PBKDF2-HMAC-SHA512 with this many rounds
+pub const BIP39_PBKDF2_ROUNDS : u32 = 2048;
+
+// Keypairs made from seed phrases, as (keypair source, keypair bytes), so that a keypair source that is used more than
+// once, for example as both the fee payer and a signer, is only prompted for once
+static SEED_PHRASE_KEYPAIRS : std::sync::Mutex<Vec<(String, [u8; 64])>> = std::sync::Mutex::new(vec![]);
+
+fn u8_list_to_vec(bytes : &str) -> Result<Vec<u8>, Error>
+{
+    bytes
+        .replace(" ", "")
+        .split(",")
+        .map(|s| s.parse::<u8>().map_err(|err| Error::keypair(&err.to_string())))
+        .collect::<Result<Vec<u8>, Error>>()
+}
+
+fn is_seed_phrase_keypair_source(s : &str) -> bool
+{
+    s.starts_with(PROMPT_KEYPAIR_PREFIX) || s.starts_with(SEED_KEYPAIR_PREFIX)
+}
+
+// Returns the derivation path given by a seed phrase keypair source, or None if the keypair is to be made directly from
+// the seed without derivation.  Sources are one of:
+//   prompt://                    -- no derivation
+//   prompt://?key=ACCOUNT        -- m/44'/501'/ACCOUNT'
+//   prompt://?key=ACCOUNT/CHANGE -- m/44'/501'/ACCOUNT'/CHANGE'
+//   prompt://?full-path=PATH     -- PATH
+//   seed:PATH                    -- PATH
+fn seed_phrase_derivation_path(s : &str) -> Result<Option<DerivationPath>, Error>
+{
+    let path = if let Some(path) = s.strip_prefix(SEED_KEYPAIR_PREFIX) {
+        path.to_string()
+    }
+    else {
+        match s.strip_prefix(PROMPT_KEYPAIR_PREFIX).unwrap_or(s) {
+            "" => return Ok(None),
+            query => {
+                if let Some(key) = query.strip_prefix("?key=") {
+                    let mut path = "m/44'/501'".to_string();
+                    for index in key.split('/') {
+                        let index = u32::from_str(index.trim_end_matches('\''))
+                            .map_err(|_| Error::keypair(&format!("Invalid key in keypair source {}", s)))?;
+                        write!(path, "/{}'", index)?;
+                    }
+                    path
+                }
+                else if let Some(full_path) = query.strip_prefix("?full-path=") {
+                    full_path.to_string()
+                }
+                else {
+                    return Err(Error::keypair(&format!("Invalid keypair source {}", s)));
+                }
+            },
+        }
+    };
+
+    DerivationPath::from_str(&path)
+        .map(Some)
+        .map_err(|e| Error::keypair(&format!("Invalid derivation path {}: {}", path, e)))
+}
+
+// Prompts for a BIP39 seed phrase and optional passphrase, without echoing them, and makes a keypair from the
+// resulting seed.  This produces the same keypairs as the Solana command line tools and common wallets do.  The seed
+// phrase must be a valid BIP39 English mnemonic, so that a mistyped word is not silently used to derive the wrong
+// keypair.  Each keypair source is only prompted for once.
+fn make_seed_phrase_keypair(s : &str) -> Result<ed25519_dalek::Keypair, Error>
+{
+    let mut keypairs = SEED_PHRASE_KEYPAIRS.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some((_, bytes)) = keypairs.iter().find(|(source, _)| source == s) {
+        return ed25519_dalek::Keypair::from_bytes(bytes).map_err(|e| Error::keypair(&e.to_string()));
+    }
+
+    let derivation_path = seed_phrase_derivation_path(s)?;
+
+    let seed_phrase = rpassword::prompt_password(format!("[{}] Seed phrase: ", s))?;
+
+    let seed_phrase = seed_phrase.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    if seed_phrase.is_empty() {
+        return Err(Error::keypair("Empty seed phrase"));
+    }
+
+    validate_mnemonic(&seed_phrase)?;
+
+    let passphrase = rpassword::prompt_password(format!("[{}] Passphrase (empty for none): ", s))?;
+
+    let keypair = keypair_from_seed(&seed_phrase_to_seed(&seed_phrase, &passphrase), derivation_path)?;
+
+    keypairs.push((s.to_string(), keypair.to_bytes()));
+
+    Ok(keypair)
+}
+
+pub fn seed_phrase_to_seed(
+    seed_phrase : &str,
+    passphrase : &str
+) -> [u8; 64]
+{
+    let mut seed = [0_u8; 64];
+
+    pbkdf2::pbkdf2::<hmac::Hmac<Sha512>>(
+        seed_phrase.as_bytes(),
+        format!("mnemonic{}", passphrase).as_bytes(),
+        BIP39_PBKDF2_ROUNDS,
+        &mut seed
+    );
+
+    seed
+}
+
+// Without a derivation path, the first 32 bytes of the seed are used as the secret key
+pub fn keypair_from_seed(
+    seed : &[u8; 64],
+    derivation_path : Option<DerivationPath>
+) -> Result<ed25519_dalek::Keypair, Error>
+{
+    let secret_key = match derivation_path {
+        Some(derivation_path) => {
+            ExtendedSecretKey::from_seed(seed)
+                .and_then(|extended| extended.derive(&derivation_path))
+                .map_err(|e| Error::keypair(&format!("Failed to derive key along {}: {}", derivation_path, e)))?
+                .secret_key
+        },
+        None => ed25519_dalek::SecretKey::from_bytes(&seed[0..32]).map_err(|e| Error::keypair(&e.to_string()))?
+    };
+
+    let public_key : ed25519_dalek::PublicKey = (&secret_key).into();
+
+    Ok(ed25519_dalek::Keypair { secret : secret_key, public : public_key })
+}
+
+pub fn make_keypair(s : &str) -> Result<ed25519_dalek::Keypair, Error>
+{
+    if is_seed_phrase_keypair_source(s) {
+        return make_seed_phrase_keypair(s);
+    }
+
+    std::fs::read_to_string(s).map_err(|err| Error::keypair(&err.to_string())).and_then(|bytes| {
+        if bytes.starts_with("[") && bytes.ends_with("]") {
+            let bytes = &bytes[1..(bytes.len() - 1)];
+            Ok(ed25519_dalek::Keypair::from_bytes(u8_list_to_vec(&bytes)?.as_slice())
+                .map_err(|err| Error::keypair(&err.to_string()))?)
+        }
+        else {
+            Err(Error::keypair(&format!("Invalid key file contents in {}", s)))
+        }
+    })
+}
+
+fn private_key_bytes_array_to_pubkey(bytes : &str) -> Result<Pubkey, Error>
+{
+    if bytes.starts_with("[") && bytes.ends_with("]") {
+        let bytes = &bytes[1..(bytes.len() - 1)];
+        Ok(Pubkey(
+            ed25519_dalek::Keypair::from_bytes(u8_list_to_vec(&bytes)?.as_slice())
+                .map_err(|e| Error::keypair(&e.to_string()))?
+                .public
+                .to_bytes()
+        ))
+    }
+    else {
+        Err(Error::keypair(&format!("Invalid private key byte array: {}", bytes)))
+    }
+}
+
+fn public_key_bytes_array_to_pubkey(bytes : &str) -> Result<Pubkey, Error>
+{
+    if bytes.starts_with("[") && bytes.ends_with("]") {
+        let bytes = &bytes[1..(bytes.len() - 1)];
+        Ok(Pubkey(
+            u8_list_to_vec(&bytes)?
+                .try_into()
+                .map_err(|_| Error::keypair("Incorrect number of bytes in public key"))?
+        ))
+    }
+    else {
+        Err(Error::keypair(&format!("Invalid public key byte array: {}", bytes)))
+    }
+}
+
+// Create a pubkey from a string which might represent an actual pubkey in base-58 encoded format, or a filename
+// containing an ed25519 keypair, or a JSON byte array of a keypair or pubkey, or a seed phrase keypair source
+pub fn make_pubkey(s : &str) -> Result<Pubkey, Error>
+{
+    if is_seed_phrase_keypair_source(s) {
+        return Ok(Pubkey(make_seed_phrase_keypair(s)?.public.to_bytes()));
+    }
+
+    std::fs::read_to_string(&s)
+        .map_err(|e| Error::keypair(e.to_string().as_str()))
+        .and_then(|pk_bytes| private_key_bytes_array_to_pubkey(&pk_bytes))
+        .or_else(|_| Pubkey::from_str(&s).map_err(|e| Error::keypair(&e)))
+        .or_else(|_| private_key_bytes_array_to_pubkey(&s))
+        .or_else(|_| public_key_bytes_array_to_pubkey(&s))
+}
+
+// Parses a PUBKEY=SIGNATURE argument, where SIGNATURE is Base58-encoded
+pub fn make_pubkey_signature(s : &str) -> Result<(Pubkey, ed25519_dalek::Signature), Error>
+{
+    let (pubkey, signature) =
+        s.rsplit_once('=').ok_or_else(|| Error::parse(&format!("Expected PUBKEY=SIGNATURE: {}", s)))?;
+
+    let signature = bs58::decode(signature)
+        .into_vec()
+        .map_err(|e| Error::parse(&format!("Invalid signature {}: {}", signature, e)))?;
+
+    Ok((
+        make_pubkey(pubkey)?,
+        ed25519_dalek::Signature::from_bytes(&signature).map_err(|e| Error::parse(&e.to_string()))?
+    ))
+}
+
+// Generates a new keypair, from a new random BIP39 mnemonic of the given number of words if one is requested, in
+// which case the mnemonic is also returned
+pub fn generate_keypair<R>(
+    mnemonic_word_count : Option<usize>,
+    rng : &mut R
+) -> Result<(ed25519_dalek::Keypair, Option<String>), Error>
+where
+    R : rand::RngCore + rand::CryptoRng
+{
+    match mnemonic_word_count {
+        Some(word_count) => {
+            let mnemonic = generate_mnemonic(word_count, rng)?;
+            Ok((keypair_from_seed(&seed_phrase_to_seed(&mnemonic, ""), None)?, Some(mnemonic)))
+        },
+        None => Ok((ed25519_dalek::Keypair::generate(rng), None))
+    }
+}
+
+// Generates keypairs on thread_count threads until one is found whose Base58-encoded pubkey begins with prefix and
+// ends with suffix.  progress is called every GRIND_PROGRESS_INTERVAL_SECONDS seconds with the number of keypairs
+// generated so far and the time taken to generate them.
+pub fn grind_keypair(
+    mnemonic_word_count : Option<usize>,
+    prefix : &str,
+    suffix : &str,
+    ignore_case : bool,
+    thread_count : usize,
+    progress : impl Fn(u64, std::time::Duration)
+) -> Result<(ed25519_dalek::Keypair, Option<String>), Error>
+{
+    for c in prefix.chars().chain(suffix.chars()) {
+        let is_base58 = |c : char| BASE58_ALPHABET.contains(c);
+        if !(is_base58(c) || (ignore_case && (is_base58(c.to_ascii_lowercase()) || is_base58(c.to_ascii_uppercase()))))
+        {
+            return Err(Error::usage(&format!(
+                "Invalid character {} in grind prefix or suffix; it is not a Base58 digit",
+                c
+            )));
+        }
+    }
+
+    let (prefix, suffix) = if ignore_case {
+        (prefix.to_lowercase(), suffix.to_lowercase())
+    }
+    else {
+        (prefix.to_string(), suffix.to_string())
+    };
+
+    let attempts = std::sync::atomic::AtomicU64::new(0);
+
+    let found = std::sync::atomic::AtomicBool::new(false);
+
+    let start = std::time::Instant::now();
+
+    let results = std::thread::scope(|scope| {
+        let threads : Vec<_> = (0..thread_count)
+            .map(|_| {
+                scope.spawn(|| -> Result<Option<(ed25519_dalek::Keypair, Option<String>)>, String> {
+                    let mut rng = rand::thread_rng();
+                    while !found.load(std::sync::atomic::Ordering::Relaxed) {
+                        let (keypair, mnemonic) =
+                            generate_keypair(mnemonic_word_count, &mut rng).map_err(|e| e.to_string())?;
+                        attempts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        let pubkey = bs58::encode(keypair.public.to_bytes()).into_string();
+                        let pubkey = if ignore_case { pubkey.to_lowercase() } else { pubkey };
+                        // Only the first thread to find a match returns it
+                        if pubkey.starts_with(&prefix) &&
+                            pubkey.ends_with(&suffix) &&
+                            !found.swap(true, std::sync::atomic::Ordering::SeqCst)
+                        {
+                            return Ok(Some((keypair, mnemonic)));
+                        }
+                    }
+                    Ok(None)
+                })
+            })
+            .collect();
+
+        let mut last_report = std::time::Instant::now();
+
+        // Any thread finishing means that either a match was found, or that an error occurred
+        while !threads.iter().any(|t| t.is_finished()) {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            if last_report.elapsed().as_secs() >= GRIND_PROGRESS_INTERVAL_SECONDS {
+                progress(attempts.load(std::sync::atomic::Ordering::Relaxed), start.elapsed());
+                last_report = std::time::Instant::now();
+            }
+        }
+
+        found.store(true, std::sync::atomic::Ordering::SeqCst);
+
+        threads
+            .into_iter()
+            .map(|t| t.join().unwrap_or_else(|_| Err("Grind thread panicked".to_string())))
+            .collect::<Vec<_>>()
+    });
+
+    let mut error = None;
+
+    for result in results {
+        match result {
+            Ok(Some(found)) => return Ok(found),
+            Ok(None) => (),
+            Err(e) => error = Some(e)
+        }
+    }
+
+    Err(Error::keypair(&error.unwrap_or_else(|| "No keypair found".to_string())))
+}
+
+// Writes a keypair in the JSON byte array format that make_keypair reads.  An existing file is only overwritten if
+// force is true.
+pub fn write_keypair_file(
+    path : &str,
+    keypair : &ed25519_dalek::Keypair,
+    force : bool
+) -> Result<(), Error>
+{
+    let mut options = std::fs::OpenOptions::new();
+
+    options.write(true);
+
+    if force {
+        options.create(true).truncate(true);
+    }
+    else {
+        options.create_new(true);
+    }
+
+    // Only the owner may read the private key
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::AlreadyExists {
+            Error::io(&format!("{} already exists; use force to overwrite it", path))
+        }
+        else {
+            Error::io(&format!("Failed to create {}: {}", path, e))
+        }
+    })?;
+
+    let bytes : Vec<String> = keypair.to_bytes().iter().map(|b| b.to_string()).collect();
+
+    write!(file, "[{}]", bytes.join(",")).map_err(|e| Error::io(&format!("Failed to write {}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const ABANDON_ABOUT : &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn path(source : &str) -> Option<String>
+    {
+        seed_phrase_derivation_path(source).unwrap().map(|path| format!("{}", path))
+    }
+
+    #[test]
+    fn keypair_sources_give_derivation_paths()
+    {
+        assert_eq!(path("prompt://"), None);
+        assert_eq!(path("prompt://?key=7"), Some("m/44'/501'/7'".to_string()));
+        assert_eq!(path("prompt://?key=0/0"), Some("m/44'/501'/0'/0'".to_string()));
+        assert_eq!(path("prompt://?key=1'/2'"), Some("m/44'/501'/1'/2'".to_string()));
+        assert_eq!(path("prompt://?full-path=m/44'/501'/3'"), Some("m/44'/501'/3'".to_string()));
+        assert_eq!(path("seed:m/44'/501'/0'/0'"), Some("m/44'/501'/0'/0'".to_string()));
+
+        assert!(seed_phrase_derivation_path("prompt://?key=x").is_err());
+        assert!(seed_phrase_derivation_path("prompt://?account=0").is_err());
+        assert!(seed_phrase_derivation_path("seed:44/501").is_err());
+    }
+
+    #[test]
+    fn seed_phrase_to_seed_matches_bip39_vector()
+    {
+        assert_eq!(
+            hex::encode(seed_phrase_to_seed(ABANDON_ABOUT, "TREZOR")),
+            concat!(
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141",
+                "630c7a3c4ab7c81b2f001698e7463b04"
+            )
+        );
+    }
+
+    #[test]
+    fn keypair_from_seed_derives_solana_cli_keypair()
+    {
+        let seed = seed_phrase_to_seed(ABANDON_ABOUT, "");
+
+        let keypair = keypair_from_seed(&seed, seed_phrase_derivation_path("prompt://?key=0/0").unwrap()).unwrap();
+
+        assert_eq!(format!("{}", Pubkey(keypair.public.to_bytes())), "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+    }
+
+    #[test]
+    fn keypair_from_seed_without_derivation_uses_first_half_of_seed()
+    {
+        let seed = seed_phrase_to_seed(ABANDON_ABOUT, "");
+
+        let keypair = keypair_from_seed(&seed, None).unwrap();
+
+        assert_eq!(keypair.secret.to_bytes(), seed[0..32]);
+    }
+
+    #[test]
+    fn make_pubkey_reads_base58_and_byte_arrays()
+    {
+        let keypair = keypair_from_seed(&seed_phrase_to_seed(ABANDON_ABOUT, ""), None).unwrap();
+
+        let pubkey = Pubkey(keypair.public.to_bytes());
+
+        let list = |bytes : &[u8]| format!("[{}]", bytes.iter().map(|b| b.to_string()).collect::<Vec<_>>().join(","));
+
+        assert!(make_pubkey(&format!("{}", pubkey)).unwrap() == pubkey);
+        assert!(make_pubkey(&list(&keypair.public.to_bytes())).unwrap() == pubkey);
+        assert!(make_pubkey(&list(&keypair.to_bytes())).unwrap() == pubkey);
+        assert!(make_pubkey("not a pubkey").is_err());
+    }
+
+    #[test]
+    fn generated_mnemonic_keypair_is_the_seed_phrase_keypair()
+    {
+        use rand::SeedableRng;
+
+        let (keypair, mnemonic) = generate_keypair(Some(24), &mut rand::rngs::StdRng::seed_from_u64(1)).unwrap();
+
+        let mnemonic = mnemonic.unwrap();
+
+        let expected = keypair_from_seed(&seed_phrase_to_seed(&mnemonic, ""), None).unwrap();
+
+        assert_eq!(keypair.to_bytes(), expected.to_bytes());
+    }
+
+    #[test]
+    fn grind_keypair_finds_prefix_and_suffix()
+    {
+        let (keypair, mnemonic) = grind_keypair(None, "a", "B", true, 2, |_, _| ()).unwrap();
+
+        let pubkey = format!("{}", Pubkey(keypair.public.to_bytes())).to_lowercase();
+
+        assert!(pubkey.starts_with('a') && pubkey.ends_with('b'));
+        assert!(mnemonic.is_none());
+    }
+
+    #[test]
+    fn grind_keypair_rejects_characters_that_are_not_base58()
+    {
+        // 0 and l are not Base58 digits, but L is, so l is allowed when ignoring case
+        assert!(grind_keypair(None, "0", "", false, 1, |_, _| ()).is_err());
+        assert!(grind_keypair(None, "", "l", false, 1, |_, _| ()).is_err());
+        assert!(grind_keypair(None, "", "l", true, 1, |_, _| ()).is_ok());
+    }
+
+    #[test]
+    fn keypair_file_round_trips_and_is_not_overwritten_without_force()
+    {
+        let path = std::env::temp_dir().join(format!("solxact-keys-test-{}.json", std::process::id()));
+
+        let path = path.to_str().unwrap();
+
+        let _ = std::fs::remove_file(path);
+
+        let (keypair, _) = generate_keypair(None, &mut rand::rngs::OsRng).unwrap();
+
+        write_keypair_file(path, &keypair, false).unwrap();
+
+        assert_eq!(make_keypair(path).unwrap().to_bytes(), keypair.to_bytes());
+        assert!(make_pubkey(path).unwrap() == Pubkey(keypair.public.to_bytes()));
+
+        let (other, _) = generate_keypair(None, &mut rand::rngs::OsRng).unwrap();
+
+        assert!(write_keypair_file(path, &other, false).is_err());
+        assert_eq!(make_keypair(path).unwrap().to_bytes(), keypair.to_bytes());
+
+        write_keypair_file(path, &other, true).unwrap();
+        assert_eq!(make_keypair(path).unwrap().to_bytes(), other.to_bytes());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+//! The solxact library provides the transaction handling that the solxact utility program is built on: building,
+//! encoding, decoding, and signing solana transactions, encoding instruction data from data values, deriving program
+//! derived addresses, and fetching what transactions need from RPC nodes and submitting them.
+//!
+//! Transactions can be built up with a TransactionBuilder, and are encoded in the wire format that RPC nodes accept:
+//!
+//! ```
+//! use solxact::{DataValue, Encoding, Pubkey, Sha256Digest, Transaction, TransactionBuilder, SYSTEM_PROGRAM_ID};
+//! use std::str::FromStr;
+//!
+//! # fn main() -> Result<(), solxact::Error> {
+//! let fee_payer = Pubkey::from_str("HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk").unwrap();
+//! let destination = Pubkey::from_str("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").unwrap();
+//! let recent_blockhash = Sha256Digest::from_str("4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM").unwrap();
+//!
+//! // A System program Transfer of 1000000 lamports
+//! let transaction = TransactionBuilder::new(fee_payer.clone())
+//!     .encoding(Encoding::RustBincodeFixedInt)
+//!     .program(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap())
+//!     .account(fee_payer.clone(), true, true)
+//!     .account(destination, false, true)
+//!     .data(DataValue::U32List(vec![2]))
+//!     .data(DataValue::U64List(vec![1000000]))
+//!     .recent_blockhash(recent_blockhash)
+//!     .build()?;
+//!
+//! assert!(transaction.needed_signatures().eq(std::iter::once(fee_payer)));
+//!
+//! let mut encoded = vec![];
+//! transaction.encode(&mut encoded)?;
+//!
+//! let mut reencoded = vec![];
+//! Transaction::decode(&mut encoded.as_slice())?.encode(&mut reencoded)?;
+//! assert_eq!(encoded, reencoded);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The other modules cover what the solxact subcommands do with transactions: [`keys`] reads and generates keypairs,
+//! [`schema`] and [`programs`] decode instruction data, [`rpc`] talks to RPC nodes, [`compute_budget`] sets compute
+//! unit limits and priority fees, [`balances`] previews balance changes, and [`submit`] sends transactions and waits
+//! for them to be executed.
+pub mod balances;
+pub mod bip39;
+pub mod builder;
+pub mod compute_budget;
+pub mod config;
+pub mod data_value;
+pub mod error;
+pub mod execution;
+pub mod format;
+pub mod keys;
+pub mod pda;
+pub mod programs;
+pub mod rpc;
+pub mod schema;
+pub mod submit;
+pub mod transaction;
+
+pub use builder::TransactionBuilder;
+pub use data_value::{DataValue, Encoding};
+pub use error::Error;
+pub use format::{TransactionFormat, TransactionFormats};
+pub use pda::{find_pda, try_find_pda};
+pub use transaction::{Address, Instruction, InstructionAddress, Pubkey, Sha256Digest, Transaction};
+
+pub const SYSTEM_PROGRAM_ID : &str = "11111111111111111111111111111111";
+pub const COMPUTE_BUDGET_PROGRAM_ID : &str = "ComputeBudget111111111111111111111111111111";
+pub const TOKEN_PROGRAM_ID : &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID : &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM_ID : &str = "AddressLookupTab1e1111111111111111111111111";
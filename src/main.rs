@@ -6,133 +6,66 @@
  *
  * solxact help encode        -- for encoding a transaction
  * solxact help decode        -- for decoding a transaction
+ * solxact help encode-json   -- for encoding a transaction from decoded json
+ * solxact help format        -- for reading and writing transactions as text
+ * solxact help errors        -- for exit codes and reporting errors as JSON
+ * solxact help config        -- for config files, profiles, and environment variables
+ * solxact help headers       -- for HTTP headers and authentication for RPC nodes
  * solxact help hash          -- for setting the recent blockhash of a transaction
+ * solxact help nonce         -- for using a durable nonce as the recent blockhash
+ * solxact help priority-fee  -- for setting the compute unit limit and priority fee
  * solxact help sign          -- for signing a transaction
  * solxact help show-unsigned -- for showing which signatures are still required
  * solxact help signature     -- for showing a transaction's signature
+ * solxact help verify        -- for verifying a transaction's signatures
+ * solxact help merge         -- for merging signatures from copies of a transaction
+ * solxact help message       -- for showing the message bytes that signers sign
+ * solxact help attach        -- for adding signatures that were made elsewhere
  * solxact help simulate      -- for simulating a transaction
+ * solxact help preview       -- for previewing a transaction's balance changes
  * solxact help submit        -- for submitting a transaction
  * solxact help pda           -- for computing program derived addresses
  * solxact help pubkey        -- for displaying pubkeys
+ * solxact help keygen        -- for generating keypairs
  **/
-mod transaction;
 mod usage;
 
-use bincode::Options;
-use ed25519_dalek::Signer;
-use sha2::{Digest, Sha256};
-use std::fmt::Write;
+use solxact::balances::preview_transaction;
+use solxact::builder::use_durable_nonce;
+use solxact::compute_budget::{
+    estimate_priority_fee, set_compute_budget_instruction, set_compute_unit_limit_instruction,
+    set_compute_unit_price_instruction, set_simulated_compute_unit_limit, DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT,
+    MAXIMUM_COMPUTE_UNIT_LIMIT
+};
+use solxact::config::{basic_auth_header, bearer_token_header, make_commitment, make_header, read_secret_arg, Config};
+use solxact::data_value::{
+    make_encoding, make_words, pubkey_from_words, read_accounts, read_data_value, read_data_values, read_single_value,
+    skip_comments, write_c_value
+};
+use solxact::execution::{
+    check_execution_report, complete_submission_report, read_error_table_file, simulation_report,
+    transaction_error_description, transaction_rejected, ErrorTables
+};
+use solxact::keys::{
+    generate_keypair, grind_keypair, make_keypair, make_pubkey, make_pubkey_signature, write_keypair_file
+};
+use solxact::rpc::{
+    apply_latest_blockhash, check_clusters, fetch_lookup_table, fetch_nonce, genesis_hash_from_arg,
+    read_blockhash_expiry_file, read_blockhash_record, read_lookup_table_file, simulate_transaction,
+    write_blockhash_expiry_file, write_blockhash_record, SimulateOptions
+};
+use solxact::submit::{
+    check_recent_blockhash, check_signed, fetch_execution_report, send_submission, wait_for_commitment, Sent,
+    SubmitOptions
+};
+use solxact::transaction::SignatureStatus;
+use solxact::{
+    find_pda, programs, schema, try_find_pda, Address, DataValue, Encoding, Error, InstructionAddress, Pubkey,
+    Sha256Digest, Transaction, TransactionBuilder, TransactionFormat, TransactionFormats
+};
 use std::io::BufRead;
 use std::io::Write as IoWrite;
 use std::str::FromStr;
-use transaction::{Address, Instruction, Pubkey, Sha256Digest, Transaction};
-
-const DEFAULT_MAINNET_RPC_URL : &str = "https://api.mainnet-beta.solana.com";
-const DEFAULT_TESTNET_RPC_URL : &str = "https://api.testnet.solana.com";
-const DEFAULT_DEVNET_RPC_URL : &str = "https://api.devnet.solana.com";
-const DEFAULT_LOCALHOST_RPC_URL : &str = "http://localhost:8899";
-
-type Error = Box<dyn std::error::Error>;
-
-#[derive(Debug)]
-pub struct StringError
-{
-    pub msg : String
-}
-
-#[derive(Debug)]
-enum Encoding
-{
-    RustBincodeVarInt,
-
-    RustBincodeFixedInt,
-
-    RustBorsh,
-
-    C
-}
-
-#[derive(Clone)]
-enum DataValue
-{
-    BoolList(Vec<bool>),
-
-    U8List(Vec<u8>),
-
-    U16List(Vec<u16>),
-
-    U32List(Vec<u32>),
-
-    U64List(Vec<u64>),
-
-    I8List(Vec<i8>),
-
-    I16List(Vec<i16>),
-
-    I32List(Vec<i32>),
-
-    I64List(Vec<i64>),
-
-    F32List(Vec<f32>),
-
-    F64List(Vec<f64>),
-
-    String(String),
-
-    CString
-    {
-        max_length : u16,
-        string : String
-    },
-
-    Pubkey(Pubkey),
-
-    Sha256([u8; 32]),
-
-    Pda(Pubkey, Vec<Box<DataValue>>),
-
-    Bump(Pubkey, Vec<Box<DataValue>>),
-
-    PdaNoBump(Pubkey, Vec<Box<DataValue>>),
-
-    Vector(Vec<Box<DataValue>>),
-
-    Struct(Vec<Box<DataValue>>),
-
-    Enum
-    {
-        index : usize,
-        params : Option<Vec<Box<DataValue>>>
-    },
-
-    Some(Box<DataValue>),
-
-    None
-}
-
-impl std::error::Error for StringError
-{
-    fn description(&self) -> &str
-    {
-        &self.msg
-    }
-}
-
-impl std::fmt::Display for StringError
-{
-    fn fmt(
-        &self,
-        f : &mut std::fmt::Formatter
-    ) -> std::fmt::Result
-    {
-        write!(f, "{}", self.msg)
-    }
-}
-
-fn stre(msg : &str) -> Error
-{
-    Box::new(StringError { msg : msg.to_string() })
-}
 
 fn usage_exit(
     msg : &str,
@@ -151,1574 +84,861 @@ fn usage_exit(
     }
 }
 
-fn u8_list_to_vec(bytes : &str) -> Result<Vec<u8>, Error>
-{
-    bytes
-        .replace(" ", "")
-        .split(",")
-        .map(|s| s.parse::<u8>().map_err(|err| stre(&err.to_string())))
-        .collect::<Result<Vec<u8>, Error>>()
-}
-
-fn make_keypair(s : &str) -> Result<ed25519_dalek::Keypair, Error>
-{
-    std::fs::read_to_string(s).map_err(|err| stre(&err.to_string())).and_then(|bytes| {
-        if bytes.starts_with("[") && bytes.ends_with("]") {
-            let bytes = &bytes[1..(bytes.len() - 1)];
-            Ok(ed25519_dalek::Keypair::from_bytes(u8_list_to_vec(&bytes)?.as_slice())
-                .map_err(|err| stre(&err.to_string()))?)
-        }
-        else {
-            Err(stre(&format!("Invalid key file contents in {}", s)))
-        }
-    })
-}
-
-fn private_key_bytes_array_to_pubkey(bytes : &str) -> Result<Pubkey, Error>
-{
-    if bytes.starts_with("[") && bytes.ends_with("]") {
-        let bytes = &bytes[1..(bytes.len() - 1)];
-        Ok(Pubkey(
-            ed25519_dalek::Keypair::from_bytes(u8_list_to_vec(&bytes)?.as_slice())
-                .map_err(|e| e.to_string())?
-                .public
-                .to_bytes()
-        ))
-    }
-    else {
-        Err(stre(&format!("Invalid private key byte array: {}", bytes)))
-    }
-}
-
-fn public_key_bytes_array_to_pubkey(bytes : &str) -> Result<Pubkey, Error>
-{
-    if bytes.starts_with("[") && bytes.ends_with("]") {
-        let bytes = &bytes[1..(bytes.len() - 1)];
-        Ok(Pubkey(
-            u8_list_to_vec(&bytes)?.try_into().map_err(|_| "Incorrect number of bytes in public key".to_string())?
-        ))
-    }
-    else {
-        Err(stre(&format!("Invalid public key byte array: {}", bytes)))
-    }
-}
-
-// Create a pubkey from a string which might represent an actual pubkey in base-58 encoded format, or a filename
-// containing an ed25519 keypair, or a JSON byte array of a keypair or pubkey
-fn make_pubkey(s : &str) -> Result<Pubkey, Error>
+fn do_encode(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
+) -> Result<(), Error>
 {
-    std::fs::read_to_string(&s)
-        .map_err(|e| stre(e.to_string().as_str()))
-        .and_then(|pk_bytes| private_key_bytes_array_to_pubkey(&pk_bytes))
-        .or_else(|_| Pubkey::from_str(&s).map_err(|e| stre(&e)))
-        .or_else(|_| private_key_bytes_array_to_pubkey(&s))
-        .or_else(|_| public_key_bytes_array_to_pubkey(&s))
-}
+    // If args is empty, then read from stdin
+    let args : Vec<String> = args.collect();
 
-fn make_sha256(s : &str) -> Result<[u8; 32], Error>
-{
-    let v = hex::decode(s)?;
+    let mut words = Vec::<String>::new();
 
-    if v.len() == 32 {
-        Ok(v.try_into().unwrap())
+    if args.len() == 0 {
+        // Read args from stdin.  Split all [ and ] off into separate word to make parsing easier
+        for line in std::io::BufReader::new(std::io::stdin()).lines() {
+            line?.split_whitespace().for_each(|e| {
+                words.extend(make_words(e));
+            });
+        }
     }
     else {
-        Err(stre(&format!("Invalid sha256: {}", s)))
-    }
-}
-
-fn bytes_are_curve_point(bytes : &[u8; 32]) -> bool
-{
-    curve25519_dalek::edwards::CompressedEdwardsY::from_slice(bytes.as_ref()).decompress().is_some()
-}
-
-fn try_find_pda(
-    pubkey : &Pubkey,
-    seed : &[u8],
-    bump_seed : Option<u8>
-) -> Option<Pubkey>
-{
-    let mut hasher = Sha256::new();
-
-    hasher.update(&seed);
-    if let Some(bump_seed) = bump_seed {
-        hasher.update(&[bump_seed]);
+        args.iter().for_each(|a| words.extend(make_words(&a)));
     }
-    hasher.update(&pubkey.0);
-    hasher.update(b"ProgramDerivedAddress");
 
-    let hash = <[u8; 32]>::try_from(hasher.finalize().as_slice()).unwrap();
-
-    if bytes_are_curve_point(&hash) {
-        None
-    }
-    else {
-        Some(Pubkey(hash))
+    // If first element is encoding, then set the encoding from it; else use a default
+    if words.len() == 0 {
+        return Err(Error::parse("No encode parameters"));
     }
-}
 
-fn find_pda(
-    program_id : &Pubkey,
-    seed : &[u8]
-) -> Option<(Pubkey, u8)>
-{
-    // Use the same algorithm as Solana's seed finding algorithm: start the bump seed at 255 and work backwards
-    let mut bump_seed = (std::u8::MAX) as i16;
+    let encoding = {
+        if words[0] == "encoding" {
+            make_encoding(&read_single_value(&mut words)?)?
+        }
+        else {
+            Encoding::RustBincodeVarInt
+        }
+    };
 
-    while bump_seed >= 0 {
-        if let Some(pubkey) = try_find_pda(&program_id, seed, Some(bump_seed as u8)) {
-            return Some((pubkey, bump_seed as u8));
+    // Read fee payer
+    let fee_payer = {
+        if words.len() == 0 {
+            return Err(Error::parse("Missing fee payer"));
         }
-        bump_seed -= 1;
-    }
 
-    None
-}
+        if words[0] != "fee_payer" {
+            return Err(Error::parse("Expected fee_payer before instructions"));
+        }
 
-fn skip_comments(words : &mut Vec<String>) -> Result<(), Error>
-{
-    while (words.len() > 0) && (words[0] == "//") {
-        // This is a comment, ignore it
         words.remove(0);
-        loop {
-            if words.len() == 0 {
-                return Err(stre("The final comment is incomplete"));
-            }
-            else {
-                let word = words.remove(0);
-                if word == "//" {
-                    break;
-                }
-            }
-        }
-    }
 
-    Ok(())
-}
+        make_pubkey(&pubkey_from_words(&mut words)?)?
+    };
 
-// Turn words into a pubkey identifier, which is either a single String (being a path, or a Base58-encoded pubkey),
-// or is an JSON array (which is turned back into a string)
-fn pubkey_from_words(words : &mut Vec<String>) -> Result<String, Error>
-{
-    if words.len() == 0 {
-        Err(stre("Missing pubkey value"))
-    }
-    else {
-        if words[0] == "[" {
-            let mut array = "[".to_string();
-            words.remove(0);
-            loop {
-                if words.len() == 0 {
-                    return Err(stre("The final pubkey value array is incomplete"));
-                }
-                let word = words.remove(0);
-                array = format!("{}{}", array, word);
-                if word == "]" {
-                    return Ok(array);
-                }
-            }
-        }
-        else {
-            Ok(words.remove(0))
-        }
-    }
-}
+    let mut builder = TransactionBuilder::new(fee_payer).encoding(encoding);
 
-// encoding is used for pda and pda_nobump accounts
-fn read_accounts(
-    words : &mut Vec<String>,
-    into : &mut Vec<(Address, bool, bool)>
-) -> Result<(), Error>
-{
+    // Read address lookup tables
     loop {
-        skip_comments(words)?;
+        skip_comments(&mut words)?;
 
-        if (words.len() == 0) || (words[0] != "account") {
+        if words.is_empty() || (words[0] != "lookup_table") {
             break;
         }
 
         words.remove(0);
 
-        if words.len() == 0 {
-            return Err(stre("Missing account pubkey"));
-        }
-
-        // Account may come from a pda or pda_nobump value
-        let pubkey = match words[0].as_str() {
-            "pda" | "pda_nobump" => {
-                let dv = read_data_value(words)?.unwrap();
-                let mut bytes = vec![];
-                write_data_value(dv, &Encoding::C, &mut bytes)?;
-                Pubkey(bytes.as_slice().try_into()?)
-            },
-            _ => make_pubkey(&pubkey_from_words(words)?)?
-        };
+        let table : Address = make_pubkey(&pubkey_from_words(&mut words)?)?.into();
 
-        let mut is_signed = false;
-
-        let mut is_write = false;
-
-        if words.len() > 0 {
-            match words[0].as_str() {
-                "s" => {
-                    words.remove(0);
-                    is_signed = true;
-                },
-                "w" => {
-                    words.remove(0);
-                    is_write = true;
-                },
-                "sw" | "ws" => {
-                    words.remove(0);
-                    is_signed = true;
-                    is_write = true;
-                },
-                _ => ()
-            }
+        if words.len() < 2 {
+            return Err(Error::parse("The final lookup_table parameter is incomplete"));
         }
 
-        into.push((pubkey.into(), is_signed, is_write));
-    }
+        let source = words.remove(0);
 
-    Ok(())
-}
+        let location = words.remove(0);
 
-fn is_data_value_terminator(s : &str) -> bool
-{
-    match s {
-        "program" | "bool" | "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "f32" | "f64" |
-        "string" | "c_string" | "pubkey" | "sha256" | "pda" | "bump" | "pda_nobump" | "vector" | "struct" |
-        "enum" | "some" | "none" | "]" | "//" => true,
-        _ => false
-    }
-}
-
-fn read_list<T, F>(
-    words : &mut Vec<String>,
-    mut f : F
-) -> Result<Vec<T>, Error>
-where
-    F : FnMut(&mut Vec<String>) -> Result<T, Error>
-{
-    let prefix = words.remove(0);
+        let addresses = match source.as_str() {
+            "file" => read_lookup_table_file(&location)?,
+            "rpc" => fetch_lookup_table(&config.rpc_endpoint(Some(&location))?, &table)?,
+            _ => return Err(Error::parse(&format!("Invalid lookup_table source: {}", source)))
+        };
 
-    let mut ret = vec![];
+        builder = builder.lookup_table(table, addresses);
+    }
 
+    // Read and add instructions
     loop {
-        // Stop at the end or when the next word is one of the value prefixes
-        if words.len() == 0 || is_data_value_terminator(&words[0]) {
+        skip_comments(&mut words)?;
+
+        if words.len() == 0 {
             break;
         }
-        ret.push(f(words)?);
-    }
-
-    if ret.len() == 0 {
-        Err(stre(&format!("Empty list of values after {}", prefix)))
-    }
-    else {
-        Ok(ret)
-    }
-}
 
-fn read_single_value(words : &mut Vec<String>) -> Result<String, Error>
-{
-    // Assume prefix is at first element of vector
-    let prefix = words.remove(0);
+        if words[0] != "program" {
+            return Err(Error::parse("First line of instruction is expected to be program"));
+        }
 
-    if words.len() == 0 {
-        return Err(stre(&format!("The final {} parameter is incomplete", prefix)));
-    }
+        words.remove(0);
 
-    Ok(words.remove(0))
-}
+        builder = builder.program(make_pubkey(&pubkey_from_words(&mut words)?)?);
 
-fn unescape_string(s : &str) -> String
-{
-    s.replace("\\\"", "\"").replace("\\\\", "\\")
-}
+        let mut accounts : Vec<(InstructionAddress, bool, bool)> = vec![];
 
-fn read_string_value(words : &mut Vec<String>) -> Result<String, Error>
-{
-    if words.len() == 0 {
-        return Err(stre("The final string parameter is incomplete"));
-    }
-
-    let word = words.remove(0);
+        read_accounts(&mut words, &mut accounts)?;
 
-    if word.starts_with("\"") {
-        if word.ends_with("\"") {
-            Ok(unescape_string(&word))
+        for (address, is_signed, is_read_write) in accounts.into_iter() {
+            builder = builder.account(address, is_signed, is_read_write);
         }
-        else {
-            let mut ret = String::new();
 
-            write!(ret, "{}", unescape_string(&word[1..]))?;
-
-            loop {
-                if words.len() == 0 {
-                    return Err(stre("The final string parameter is incomplete"));
-                }
+        let mut data_values = Vec::<DataValue>::new();
 
-                let word = words.remove(0);
+        read_data_values(&mut words, &mut data_values)?;
 
-                if word.ends_with("\"") {
-                    write!(ret, "{}", unescape_string(&word[0..(word.len() - 1)]))?;
-                    return Ok(ret);
-                }
-                else {
-                    write!(ret, "{}", unescape_string(&word))?;
-                }
-            }
+        for dv in data_values.into_iter() {
+            builder = builder.data(dv);
         }
     }
-    else {
-        Ok(word)
-    }
+
+    let transaction = builder.build()?;
+
+    formats.write_transaction(&transaction, &mut std::io::stdout())
 }
 
-fn read_vector(
-    prefix : &str,
-    words : &mut Vec<String>
-) -> Result<Vec<Box<DataValue>>, Error>
+fn do_decode(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats
+) -> Result<(), Error>
 {
-    if words.len() == 0 {
-        return Err(stre(&format!("The final {} parameter is incomplete", prefix)));
-    }
+    let mut words = Vec::<String>::new();
 
-    let word = words.remove(0);
+    args.for_each(|a| words.extend(make_words(&a)));
 
-    if word != "[" {
-        return Err(stre(&format!("Expected [ after {}", prefix)));
-    }
+    // Read the data schemas to apply to the instructions of each program, as (program, encoding, schemas)
+    let mut programs = vec![];
 
-    let mut v = vec![];
+    let mut encoding = Encoding::RustBincodeVarInt;
 
     loop {
-        skip_comments(words)?;
-
-        if words.len() == 0 {
-            return Err(stre(&format!("The final {} parameter is incomplete", prefix)));
-        }
+        skip_comments(&mut words)?;
 
-        if words[0] == "]" {
-            words.remove(0);
+        if words.is_empty() {
             break;
         }
 
-        if let Some(dv) = read_data_value(words)? {
-            v.push(Box::new(dv));
-        }
-        else {
-            break;
+        match words[0].as_str() {
+            "encoding" => encoding = make_encoding(&read_single_value(&mut words)?)?,
+            "program" => {
+                words.remove(0);
+                let program = make_pubkey(&pubkey_from_words(&mut words)?)?;
+                programs.push((Address(program.0), encoding.clone(), schema::read_data_schemas(&mut words)?));
+            },
+            _ => return Err(Error::parse(&format!("Invalid decode parameter: {}", words[0])))
         }
     }
 
-    if v.len() == 0 {
-        Err(stre(&format!("Empty {}", prefix)))
-    }
-    else {
-        Ok(v)
-    }
+    let json = programs::decode_transaction_json(&formats.read_transaction(&mut std::io::stdin())?, &programs);
+
+    write!(std::io::stdout(), "{}", json).map_err(|err| err.into())
 }
 
-fn read_data_value(words : &mut Vec<String>) -> Result<Option<DataValue>, Error>
+fn do_encode_json(formats : &TransactionFormats) -> Result<(), Error>
 {
-    match words[0].as_str() {
-        "bool" => Ok(Some(DataValue::BoolList(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(bool::from_str(&word).map_err(|_| stre(&format!("Invalid bool value: {}", word)))?)
-        })?))),
-        "u8" => Ok(Some(DataValue::U8List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(u8::from_str(&word).map_err(|_| stre(&format!("Invalid u8 value: {}", word)))?)
-        })?))),
-        "u16" => Ok(Some(DataValue::U16List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(u16::from_str(&word).map_err(|_| stre(&format!("Invalid u16 value: {}", word)))?)
-        })?))),
-        "u32" => Ok(Some(DataValue::U32List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(u32::from_str(&word).map_err(|_| stre(&format!("Invalid u32 value: {}", word)))?)
-        })?))),
-        "u64" => Ok(Some(DataValue::U64List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(u64::from_str(&word).map_err(|_| stre(&format!("Invalid u64 value: {}", word)))?)
-        })?))),
-        "i8" => Ok(Some(DataValue::I8List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(i8::from_str(&word).map_err(|_| stre(&format!("Invalid i8 value: {}", word)))?)
-        })?))),
-        "i16" => Ok(Some(DataValue::I16List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(i16::from_str(&word).map_err(|_| stre(&format!("Invalid i16 value: {}", word)))?)
-        })?))),
-        "i32" => Ok(Some(DataValue::I32List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(i32::from_str(&word).map_err(|_| stre(&format!("Invalid i32 value: {}", word)))?)
-        })?))),
-        "i64" => Ok(Some(DataValue::I64List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(i64::from_str(&word).map_err(|_| stre(&format!("Invalid i64 value: {}", word)))?)
-        })?))),
-        "f32" => Ok(Some(DataValue::F32List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(f32::from_str(&word).map_err(|_| stre(&format!("Invalid f32 value: {}", word)))?)
-        })?))),
-        "f64" => Ok(Some(DataValue::F64List(read_list(words, |ws| {
-            let word = ws.remove(0);
-            Ok(f64::from_str(&word).map_err(|_| stre(&format!("Invalid f64 value: {}", word)))?)
-        })?))),
-        "string" => {
-            words.remove(0); // string
-            Ok(Some(DataValue::String(read_string_value(words)?)))
-        },
-        "c_string" => {
-            words.remove(0); // c_string
-            if words.len() == 0 {
-                return Err(stre(&format!("The final c_string parameter is incomplete")));
-            }
-            let word = words.remove(0);
-            let max_length =
-                u16::from_str(&word).map_err(|_| stre(&format!("Invalid max_length in c_string value: {}", word)))?;
-            Ok(Some(DataValue::CString { max_length, string : read_string_value(words)? }))
-        },
-        "pubkey" => {
-            words.remove(0);
-            Ok(Some(DataValue::Pubkey(make_pubkey(&pubkey_from_words(words)?)?)))
-        },
-        "sha256" => Ok(Some(DataValue::Sha256(make_sha256(&read_single_value(words)?)?))),
-        "pda" => {
-            words.remove(0);
-            Ok(Some(DataValue::Pda(make_pubkey(&pubkey_from_words(words)?)?, read_vector("pda", words)?)))
-        },
-        "bump" => {
-            words.remove(0);
-            Ok(Some(DataValue::Bump(make_pubkey(&pubkey_from_words(words)?)?, read_vector("bump", words)?)))
-        },
-        "pda_nobump" => {
-            words.remove(0);
-            Ok(Some(DataValue::PdaNoBump(make_pubkey(&pubkey_from_words(words)?)?, read_vector("pda_nobump", words)?)))
-        },
-        "vector" => {
-            words.remove(0); // vector
-            if words.len() == 0 {
-                return Err(stre("The final vector parameter is incomplete"));
-            }
-            Ok(Some(DataValue::Vector(read_vector("vector", words)?)))
-        },
-        "struct" => {
-            words.remove(0); // struct
-            if words.len() == 0 {
-                return Err(stre("The final struct parameter is incomplete"));
-            }
-            Ok(Some(DataValue::Struct(read_vector("struct", words)?)))
-        },
-        "enum" => {
-            words.remove(0); // enum
-            if words.len() == 0 {
-                return Err(stre("The final enum parameter is incomplete"));
-            }
-            let index = words.remove(0);
-            let index =
-                usize::from_str(&index).map_err(|err| stre(&format!("Invalid enum index {}: {}", index, err)))?;
-            Ok(Some(DataValue::Enum {
-                index,
-                params : {
-                    if (words.len() == 0) || (words[0] != "[") {
-                        None
-                    }
-                    else {
-                        Some(read_vector("enum", words)?)
-                    }
-                }
-            }))
-        },
-        "some" => {
-            words.remove(0); // some
-            Ok(Some(DataValue::Some(Box::new(
-                read_data_value(words)?.ok_or_else(|| stre("The final some parameter is incomplete"))?
-            ))))
-        },
-        "none" => Ok(Some(DataValue::None)),
-        "program" => Ok(None),
-        _ => Err(stre(&format!("Invalid data: {}", words[0])))
-    }
+    let json : serde_json::Value = serde_json::from_reader(std::io::stdin())
+        .map_err(|err| Error::parse(&format!("Invalid JSON transaction: {}", err)))?;
+
+    formats.write_transaction(&Transaction::from_json(&json)?, &mut std::io::stdout())
 }
 
-fn read_data_values(
-    words : &mut Vec<String>,
-    into : &mut Vec<DataValue>
+fn do_hash(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
 ) -> Result<(), Error>
 {
-    loop {
-        skip_comments(words)?;
-
-        if words.len() == 0 {
-            break;
+    let mut expiry_file = None;
+    let mut record = false;
+    let mut genesis_hash = None;
+    let mut rpc_url = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "record" => record = true,
+            "expiry" => {
+                expiry_file = Some(args.next().ok_or_else(|| Error::usage("The final expiry parameter is incomplete"))?)
+            },
+            "cluster" => genesis_hash = Some(read_cluster_arg(args)?),
+            _ => {
+                if rpc_url.is_some() {
+                    return Err(Error::usage(&format!("Invalid argument: {}", arg)));
+                }
+                rpc_url = Some(arg);
+            }
         }
+    }
 
-        let data_value = read_data_value(words)?;
+    let endpoint = config.rpc_endpoint(rpc_url.as_deref())?;
 
-        if let Some(data_value) = data_value {
-            into.push(data_value);
-        }
-        else {
-            break;
-        }
-    }
+    let mut transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-    Ok(())
-}
+    check_clusters(&endpoint, &[&genesis_hash, &endpoint.genesis_hash])?;
 
-fn vector_normalize(v : &Vec<Box<DataValue>>) -> Vec<Box<DataValue>>
-{
-    if v.len() == 1 {
-        match &*v[0] {
-            DataValue::U8List(v) => v.iter().map(|e| Box::new(DataValue::U8List(vec![*e]))).collect(),
-            DataValue::U16List(v) => v.iter().map(|e| Box::new(DataValue::U16List(vec![*e]))).collect(),
-            DataValue::U32List(v) => v.iter().map(|e| Box::new(DataValue::U32List(vec![*e]))).collect(),
-            DataValue::U64List(v) => v.iter().map(|e| Box::new(DataValue::U64List(vec![*e]))).collect(),
-            DataValue::I8List(v) => v.iter().map(|e| Box::new(DataValue::I8List(vec![*e]))).collect(),
-            DataValue::I16List(v) => v.iter().map(|e| Box::new(DataValue::I16List(vec![*e]))).collect(),
-            DataValue::I32List(v) => v.iter().map(|e| Box::new(DataValue::I32List(vec![*e]))).collect(),
-            DataValue::I64List(v) => v.iter().map(|e| Box::new(DataValue::I64List(vec![*e]))).collect(),
-            DataValue::F32List(v) => v.iter().map(|e| Box::new(DataValue::F32List(vec![*e]))).collect(),
-            DataValue::F64List(v) => v.iter().map(|e| Box::new(DataValue::F64List(vec![*e]))).collect(),
-            _ => v.clone()
-        }
-    }
-    else {
-        v.clone()
-    }
-}
+    let recent_blockhash = apply_latest_blockhash(&endpoint, &mut transaction, genesis_hash)?;
 
-fn bincode_encode<T : serde::ser::Serialize>(
-    v : T,
-    varint : bool,
-    w : &mut dyn std::io::Write
-) -> Result<(), Error>
-where
-{
-    if varint {
-        bincode::DefaultOptions::new().with_varint_encoding().serialize_into(w, &v).map_err(|e| e.into())
+    if record {
+        write_blockhash_record(&recent_blockhash)?;
     }
-    else {
-        bincode::DefaultOptions::new().with_fixint_encoding().serialize_into(w, &v).map_err(|e| e.into())
+
+    if let Some(expiry_file) = expiry_file {
+        write_blockhash_expiry_file(&expiry_file, &recent_blockhash)?;
     }
+
+    formats.write_transaction(&transaction, &mut std::io::stdout())
 }
 
-fn write_rust_bincode_value(
-    data_value : DataValue,
-    varint : bool,
-    into : &mut Vec<u8>
+fn do_nonce(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
 ) -> Result<(), Error>
 {
-    match data_value {
-        DataValue::BoolList(v) => {
-            for u in v.into_iter() {
-                bincode_encode(u, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::U8List(v) => {
-            for u in v.into_iter() {
-                bincode_encode(u, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::U16List(v) => {
-            for u in v.into_iter() {
-                bincode_encode(u, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::U32List(v) => {
-            for u in v.into_iter() {
-                bincode_encode(u, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::U64List(v) => {
-            for u in v.into_iter() {
-                bincode_encode(u, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::I8List(v) => {
-            for i in v.into_iter() {
-                bincode_encode(i, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::I16List(v) => {
-            for i in v.into_iter() {
-                bincode_encode(i, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::I32List(v) => {
-            for i in v.into_iter() {
-                bincode_encode(i, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::I64List(v) => {
-            for i in v.into_iter() {
-                bincode_encode(i, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::F32List(v) => {
-            for f in v.into_iter() {
-                bincode_encode(f, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::F64List(v) => {
-            for f in v.into_iter() {
-                bincode_encode(f, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::String(s) => bincode_encode(s, varint, into),
-
-        DataValue::CString { max_length, string } => {
-            let zeroes = (max_length as usize).checked_sub(string.len()).ok_or_else(|| {
-                stre(&format!("c_string {} has length greater than max_length {}", string.len(), max_length))
-            })?;
-            into.extend(string.as_bytes());
-            for _ in 0..zeroes {
-                into.push(0);
-            }
-            Ok(())
-        },
-
-        DataValue::Pubkey(p) => bincode_encode(p.0, varint, into),
-
-        DataValue::Sha256(a) => {
-            for u in a {
-                bincode_encode(u, varint, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::Pda(program_id, v) => {
-            // Encode v into a vector of bytes, which is the base seed.  Fixed int is used as varint doesn't
-            // make sense for seed values.
-            let mut seed = vec![];
-            write_rust_bincode_value(DataValue::Vector(v), false, &mut seed)?;
-            // Compute the address and bump seed, which must succeed since a
-            // bump seed is being used
-            let (pubkey, _) = find_pda(&program_id, &seed).unwrap();
-            // Encode the pubkey
-            bincode_encode(pubkey.0, false, into)
-        },
-
-        DataValue::Bump(program_id, v) => {
-            // Encode v into a vector of bytes, which is the base seed.  Fixed int is used as varint doesn't
-            // make sense for seed values.
-            let mut seed = vec![];
-            write_rust_bincode_value(DataValue::Vector(v), false, &mut seed)?;
-            // Compute the address and bump seed, which must succeed since a
-            // bump seed is being used
-            let (_, bump_seed) = find_pda(&program_id, &seed).unwrap();
-            // Encode the pubkey.
-            bincode_encode(bump_seed, varint, into)
-        },
+    let mut args = args.peekable();
 
-        DataValue::PdaNoBump(program_id, v) => {
-            // Encode v into a vector of bytes, which is the base seed.  Fixed int is used as varint doesn't
-            // make sense for seed values.
-            let mut seed = vec![];
-            write_rust_bincode_value(DataValue::Vector(v), false, &mut seed)?;
-            // Compute the address and bump seed, which may fail since a bump seed is not being used
-            match try_find_pda(&program_id, &seed, None) {
-                Some(pubkey) => {
-                    // Encode the pubkey
-                    bincode_encode(pubkey.0, false, into)
-                },
-                None => Err(stre("PDA could not be derived"))
-            }
-        },
+    // First arguments may be "value <NONCE>"
+    let nonce = if args.next_if_eq("value").is_some() {
+        Some(
+            Sha256Digest::from_str(&args.next().ok_or_else(|| Error::usage("Missing nonce value"))?)
+                .map_err(|e| Error::parse(&e))?
+        )
+    }
+    else {
+        None
+    };
 
-        DataValue::Vector(v) => {
-            let v = vector_normalize(&v);
-            bincode_encode(v.len(), varint, into)?;
-            for v in v.into_iter() {
-                write_rust_bincode_value(*v, varint, into)?;
-            }
-            Ok(())
-        },
+    let nonce_account = make_pubkey(&args.next().ok_or_else(|| Error::usage("Missing nonce account"))?)?;
 
-        DataValue::Struct(v) => {
-            for v in v.into_iter() {
-                write_rust_bincode_value(*v, varint, into)?;
-            }
-            Ok(())
-        },
+    let nonce_authority = make_pubkey(&args.next().ok_or_else(|| Error::usage("Missing nonce authority"))?)?;
 
-        DataValue::Enum { index, params } => {
-            if index > (u32::MAX as usize) {
-                return Err(stre(&format!(
-                    "enum index {} is greater than max of {} supported by borsh encoding",
-                    index,
-                    u32::MAX
-                )));
-            }
-            bincode_encode(index as u32, varint, into)?;
-            if let Some(params) = params {
-                write_rust_bincode_value(DataValue::Struct(params), varint, into)
-            }
-            else {
-                Ok(())
+    let nonce = match nonce {
+        Some(nonce) => {
+            if let Some(arg) = args.next() {
+                return Err(Error::usage(&format!("Invalid argument: {}", arg)));
             }
+            nonce
         },
+        None => {
+            let args : Vec<String> = args.collect();
+            let endpoint = match args.len() {
+                0 => config.rpc_endpoint(None)?,
+                1 => config.rpc_endpoint(Some(&args[0]))?,
+                _ => return Err(Error::usage(&format!("Invalid argument: {}", args[1])))
+            };
+            check_clusters(&endpoint, &[&endpoint.genesis_hash])?;
+            fetch_nonce(&endpoint, &nonce_account, &nonce_authority)?
+        }
+    };
 
-        DataValue::Some(v) => {
-            write_rust_bincode_value(DataValue::Enum { index : 1, params : Some(vec![v]) }, varint, into)
-        },
+    let mut transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-        DataValue::None => write_rust_bincode_value(DataValue::Enum { index : 0, params : None }, varint, into)
-    }
-}
+    use_durable_nonce(&mut transaction, &nonce_account, &nonce_authority, nonce)?;
 
-fn borsh_encode<T : borsh::BorshSerialize>(
-    v : T,
-    w : &mut dyn std::io::Write
-) -> Result<(), Error>
-{
-    borsh::to_writer(w, &v).map_err(|e| e.into())
+    formats.write_transaction(&transaction, &mut std::io::stdout())
 }
 
-fn write_rust_borsh_value(
-    data_value : DataValue,
-    into : &mut Vec<u8>
+fn do_priority_fee(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
 ) -> Result<(), Error>
 {
-    match data_value {
-        DataValue::BoolList(v) => {
-            for u in v.into_iter() {
-                borsh_encode(u, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::U8List(v) => {
-            for u in v.into_iter() {
-                borsh_encode(u, into)?;
-            }
-            Ok(())
-        },
+    let mut args = args.peekable();
 
-        DataValue::U16List(v) => {
-            for u in v.into_iter() {
-                borsh_encode(u, into)?;
-            }
-            Ok(())
-        },
+    let mut limit = None;
 
-        DataValue::U32List(v) => {
-            for u in v.into_iter() {
-                borsh_encode(u, into)?;
-            }
-            Ok(())
-        },
+    let mut price = None;
 
-        DataValue::U64List(v) => {
-            for u in v.into_iter() {
-                borsh_encode(u, into)?;
-            }
-            Ok(())
-        },
+    let mut percentile = None;
 
-        DataValue::I8List(v) => {
-            for i in v.into_iter() {
-                borsh_encode(i, into)?;
-            }
-            Ok(())
-        },
+    // If the limit is auto, it is sized by simulation, with this margin as a percentage of the compute units consumed
+    let mut limit_auto = false;
 
-        DataValue::I16List(v) => {
-            for i in v.into_iter() {
-                borsh_encode(i, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::I32List(v) => {
-            for i in v.into_iter() {
-                borsh_encode(i, into)?;
-            }
-            Ok(())
-        },
+    let mut margin = None;
 
-        DataValue::I64List(v) => {
-            for i in v.into_iter() {
-                borsh_encode(i, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::F32List(v) => {
-            for f in v.into_iter() {
-                borsh_encode(f, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::F64List(v) => {
-            for f in v.into_iter() {
-                borsh_encode(f, into)?;
-            }
-            Ok(())
-        },
-
-        DataValue::String(s) => borsh_encode(s, into),
-
-        DataValue::CString { max_length, string } => {
-            let zeroes = (max_length as usize).checked_sub(string.len()).ok_or_else(|| {
-                stre(&format!("c_string {} has length greater than max_length {}", string.len(), max_length))
-            })?;
-            into.extend(string.as_bytes());
-            for _ in 0..zeroes {
-                into.push(0);
-            }
-            Ok(())
-        },
-
-        DataValue::Pubkey(p) => borsh_encode(p.0, into),
+    while let Some(option) = args.next_if(|arg| matches!(arg.as_str(), "limit" | "price" | "percentile" | "margin")) {
+        let value =
+            args.next().ok_or_else(|| Error::usage(&format!("The final {} parameter is incomplete", option)))?;
+        let invalid = || Error::usage(&format!("Invalid {}: {}", option, value));
+        match option.as_str() {
+            "limit" if value == "auto" => limit_auto = true,
+            "limit" => {
+                limit = Some(
+                    u32::from_str(&value)
+                        .ok()
+                        .filter(|units| *units <= MAXIMUM_COMPUTE_UNIT_LIMIT)
+                        .ok_or_else(invalid)?
+                )
+            },
+            "price" => price = Some(u64::from_str(&value).map_err(|_| invalid())?),
+            "margin" => margin = Some(u32::from_str(&value).map_err(|_| invalid())?),
+            _ => percentile = Some(u8::from_str(&value).ok().filter(|p| *p <= 100).ok_or_else(invalid)?)
+        }
+    }
 
-        DataValue::Sha256(a) => {
-            for u in a {
-                borsh_encode(u, into)?;
-            }
-            Ok(())
-        },
+    if price.is_some() && percentile.is_some() {
+        return Err(Error::usage("Only one of price and percentile may be given"));
+    }
 
-        DataValue::Pda(program_id, v) => {
-            // Encode v into a vector of bytes, which is the base seed
-            let mut seed = vec![];
-            write_rust_borsh_value(DataValue::Vector(v), &mut seed)?;
-            // Compute the address and bump seed, which must succeed since a
-            // bump seed is being used
-            let (pubkey, _) = find_pda(&program_id, &seed).unwrap();
-            // Encode the pubkey
-            borsh_encode(pubkey.0, into)
-        },
+    if margin.is_some() && !limit_auto {
+        return Err(Error::usage("margin may only be given with limit auto"));
+    }
 
-        DataValue::Bump(program_id, v) => {
-            // Encode v into a vector of bytes, which is the base seed
-            let mut seed = vec![];
-            write_rust_borsh_value(DataValue::Vector(v), &mut seed)?;
-            // Compute the address and bump seed, which must succeed since a
-            // bump seed is being used
-            let (_, bump_seed) = find_pda(&program_id, &seed).unwrap();
-            // Encode the pubkey
-            borsh_encode(bump_seed, into)
-        },
+    if limit.is_none() && !limit_auto && price.is_none() && percentile.is_none() {
+        return Err(Error::usage("Expected limit, price, or percentile"));
+    }
 
-        DataValue::PdaNoBump(program_id, v) => {
-            // Encode v into a vector of bytes, which is the base seed
-            let mut seed = vec![];
-            write_rust_borsh_value(DataValue::Vector(v), &mut seed)?;
-            // Compute the address and bump seed, which may fail since a bump seed is not being used
-            match try_find_pda(&program_id, &seed, None) {
-                Some(pubkey) => {
-                    // Encode the pubkey
-                    borsh_encode(pubkey.0, into)
-                },
-                None => Err(stre("PDA could not be derived"))
-            }
-        },
+    // An RPC node is only needed to look up recent fees or to simulate the transaction
+    let args : Vec<String> = args.collect();
 
-        DataValue::Vector(v) => {
-            let v = vector_normalize(&v);
-            if v.len() > (u32::MAX as usize) {
-                return Err(stre(&format!(
-                    "vector length {} is greater than max of {} supported by borsh encoding",
-                    v.len(),
-                    u32::MAX
-                )));
-            }
-            borsh_encode(v.len() as u32, into)?;
-            for v in v.into_iter() {
-                write_rust_borsh_value(*v, into)?;
-            }
-            Ok(())
-        },
+    let endpoint = match (percentile.is_some() || limit_auto, args.len()) {
+        (true, 0) => Some(config.rpc_endpoint(None)?),
+        (true, 1) => Some(config.rpc_endpoint(Some(&args[0]))?),
+        (true, _) => return Err(Error::usage(&format!("Invalid argument: {}", args[1]))),
+        (false, 0) => None,
+        (false, _) => return Err(Error::usage(&format!("Invalid argument: {}", args[0])))
+    };
 
-        DataValue::Struct(v) => {
-            for v in v.into_iter() {
-                write_rust_borsh_value(*v, into)?;
-            }
-            Ok(())
-        },
+    if let Some(endpoint) = &endpoint {
+        check_clusters(endpoint, &[&endpoint.genesis_hash])?;
+    }
 
-        DataValue::Enum { index, params } => {
-            if index > (u8::MAX as usize) {
-                return Err(stre(&format!(
-                    "enum index {} is greater than max of {} supported by borsh encoding",
-                    index,
-                    u8::MAX
-                )));
-            }
-            borsh_encode(index as u8, into)?;
-            if let Some(params) = params {
-                write_rust_borsh_value(DataValue::Struct(params), into)
-            }
-            else {
-                Ok(())
-            }
-        },
+    let mut transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-        DataValue::Some(v) => write_rust_borsh_value(DataValue::Enum { index : 1, params : Some(vec![v]) }, into),
+    if let (Some(percentile), Some(endpoint)) = (percentile, &endpoint) {
+        price = Some(estimate_priority_fee(endpoint, &transaction, percentile)?);
+    }
 
-        DataValue::None => write_rust_borsh_value(DataValue::Enum { index : 0, params : None }, into)
+    // Each is inserted at the beginning of the transaction, so the price is set first in order for the limit to come
+    // first
+    if let Some(micro_lamports) = price {
+        set_compute_budget_instruction(&mut transaction, set_compute_unit_price_instruction(micro_lamports));
     }
-}
 
-fn c_align(
-    alignment : usize,
-    should_align : bool,
-    into : &mut Vec<u8>
-)
-{
-    if should_align {
-        while (into.len() % alignment) != 0 {
-            into.push(0);
-        }
+    // The limit is sized last so that the simulation includes the compute units of every other instruction
+    if let (true, Some(endpoint)) = (limit_auto, &endpoint) {
+        set_simulated_compute_unit_limit(
+            endpoint,
+            &mut transaction,
+            margin.unwrap_or(DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT)
+        )?;
+    }
+    else if let Some(units) = limit {
+        set_compute_budget_instruction(&mut transaction, set_compute_unit_limit_instruction(units));
     }
+
+    formats.write_transaction(&transaction, &mut std::io::stdout())
 }
 
-fn c_alignment(dv : &DataValue) -> usize
+fn do_show_unsigned(formats : &TransactionFormats) -> Result<(), Error>
 {
-    match dv {
-        DataValue::BoolList(_) => 1,
-        DataValue::U8List(_) => 1,
-        DataValue::U16List(_) => 2,
-        DataValue::U32List(_) => 4,
-        DataValue::U64List(_) => 8,
-        DataValue::I8List(_) => 1,
-        DataValue::I16List(_) => 2,
-        DataValue::I32List(_) => 4,
-        DataValue::I64List(_) => 8,
-        DataValue::F32List(_) => 4,
-        DataValue::F64List(_) => 8,
-        DataValue::String(_) => 1,
-        DataValue::CString { max_length: _, string: _ } => 1,
-        DataValue::Pubkey(_) => 1,
-        DataValue::Sha256(_) => 1,
-        DataValue::Pda(_, _) => 1,
-        DataValue::Bump(_, _) => 1,
-        DataValue::PdaNoBump(_, _) => 1,
-        DataValue::Vector(_) => 1,
-        DataValue::Struct(v) => c_max_alignment(v),
-        DataValue::Enum { index: _, params } => {
-            if let Some(p) = params {
-                c_max_alignment(&p)
-            }
-            else {
-                0
-            }
-        },
-        DataValue::Some(v) => c_alignment(v),
-        DataValue::None => 1
-    }
+    Ok(formats.read_transaction(&mut std::io::stdin())?.needed_signatures().for_each(|p| println!("{}", p)))
 }
 
-fn c_max_alignment(v : &Vec<Box<DataValue>>) -> usize
+fn do_signature(formats : &TransactionFormats) -> Result<(), Error>
 {
-    let mut max = 1;
+    let tx = formats.read_transaction(&mut std::io::stdin())?;
 
-    for dv in v {
-        let alignment = c_alignment(dv);
-        if alignment > max {
-            max = alignment;
+    if tx.signed_read_write_addresses.len() > 0 {
+        if let Some(signature) = tx.signed_read_write_addresses[0].signature {
+            println!("{}", bs58::encode(signature.to_bytes()).into_string());
+            return Ok(());
         }
     }
 
-    max
+    Err(Error::signature("Transaction is not signed and thus has no signature"))
 }
 
-fn write_c_value(
-    data_value : DataValue,
-    align : bool,
-    into : &mut Vec<u8>
+fn do_sign(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
 ) -> Result<(), Error>
 {
-    match data_value {
-        DataValue::BoolList(v) => v.into_iter().for_each(|b| {
-            into.push(if b { 1 } else { 0 });
-        }),
-
-        DataValue::U8List(mut v) => {
-            into.append(&mut v);
-        },
-
-        DataValue::U16List(v) => v.into_iter().for_each(|u| {
-            c_align(2, align, into);
-            into.extend(u.to_le_bytes());
-        }),
-
-        DataValue::U32List(v) => v.into_iter().for_each(|u| {
-            c_align(4, align, into);
-            into.extend(u.to_le_bytes());
-        }),
-
-        DataValue::U64List(v) => v.into_iter().for_each(|u| {
-            c_align(8, align, into);
-            into.extend(u.to_le_bytes());
-        }),
-
-        DataValue::I8List(v) => v.into_iter().for_each(|i| {
-            into.extend(i.to_le_bytes());
-        }),
-
-        DataValue::I16List(v) => v.into_iter().for_each(|i| {
-            c_align(2, align, into);
-            into.extend(i.to_le_bytes());
-        }),
-
-        DataValue::I32List(v) => v.into_iter().for_each(|i| {
-            c_align(4, align, into);
-            into.extend(i.to_le_bytes());
-        }),
-
-        DataValue::I64List(v) => v.into_iter().for_each(|i| {
-            c_align(8, align, into);
-            into.extend(i.to_le_bytes());
-        }),
-
-        DataValue::F32List(v) => v.into_iter().for_each(|f| {
-            c_align(4, align, into);
-            into.extend(f.to_le_bytes());
-        }),
-
-        DataValue::F64List(v) => v.into_iter().for_each(|f| {
-            c_align(8, align, into);
-            into.extend(f.to_le_bytes());
-        }),
-
-        DataValue::String(s) => into.extend(s.as_bytes()),
-
-        DataValue::CString { max_length, string } => {
-            let zeroes = (max_length as usize).checked_sub(string.len()).ok_or_else(|| {
-                stre(&format!("c_string {} has length greater than max_length {}", string.len(), max_length))
-            })?;
-            into.extend(string.as_bytes());
-            for _ in 0..zeroes {
-                into.push(0);
-            }
-        },
+    let mut keypairs = vec![];
 
-        DataValue::Pubkey(p) => write_c_value(DataValue::U8List(p.0.into()), align, into)?,
+    for a in args {
+        keypairs.push(make_keypair(&a)?);
+    }
 
-        DataValue::Sha256(p) => write_c_value(DataValue::U8List(p.into()), align, into)?,
+    // With no keypairs given, the profile's keypair is used
+    if keypairs.is_empty() {
+        if let Some(keypair) = &config.profile.keypair {
+            keypairs.push(make_keypair(keypair)?);
+        }
+    }
 
-        DataValue::Pda(program_id, v) => {
-            // Encode v into a vector of data values.  No alignment is used since seeds should be directly
-            // concatenated.
-            let mut seed = vec![];
-            for dv in v.into_iter() {
-                write_c_value(*dv, false, &mut seed)?;
-            }
-            // Compute the address and bump seed, which must succeed since a
-            // bump seed is being used
-            let (pubkey, _) = find_pda(&program_id, &seed).unwrap();
-            // Encode the pubkey
-            write_c_value(DataValue::Pubkey(pubkey), false, into)?
-        },
+    let mut transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-        DataValue::Bump(program_id, v) => {
-            // Encode v into a vector of data values.  No alignment is used since seeds should be directly
-            // concatenated.
-            let mut seed = vec![];
-            for dv in v.into_iter() {
-                write_c_value(*dv, false, &mut seed)?;
-            }
-            // Compute the address and bump seed, which must succeed since a
-            // bump seed is being used
-            let (_, bump_seed) = find_pda(&program_id, &seed).unwrap();
-            // Encode the pubkey
-            into.extend(bump_seed.to_le_bytes())
-        },
+    transaction.sign_with_keypairs(&keypairs)?;
 
-        DataValue::PdaNoBump(program_id, v) => {
-            // Encode v into a vector of data values.  No alignment is used since seeds should be directly
-            // concatenated.
-            let mut seed = vec![];
-            for dv in v.into_iter() {
-                write_c_value(*dv, false, &mut seed)?;
-            }
-            // Compute the address and bump seed, which may fail since a bump seed is not being used
-            match try_find_pda(&program_id, &seed, None) {
-                Some(pubkey) => {
-                    // Encode the pubkey
-                    write_c_value(DataValue::Pubkey(pubkey), false, into)?
-                },
-                None => return Err(stre("PDA could not be derived"))
-            }
-        },
+    formats.write_transaction(&transaction, &mut std::io::stdout())
+}
 
-        DataValue::Vector(_) => return Err(stre("vector value cannot be used with c encoding")),
+fn do_message(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats
+) -> Result<(), Error>
+{
+    let format = args.next();
 
-        DataValue::Struct(v) => {
-            let alignment = c_max_alignment(&v);
-            c_align(alignment, align, into);
-            for v in v.into_iter() {
-                write_c_value(*v, align, into)?;
-            }
-            c_align(alignment, align, into);
-        },
+    if let Some(arg) = args.next() {
+        return Err(Error::usage(&format!("Invalid argument: {}", arg)));
+    }
 
-        DataValue::Enum { index, params } => {
-            if index > (u8::MAX as usize) {
-                return Err(stre(&format!(
-                    "enum index {} is greater than max of {} supported by c encoding",
-                    index,
-                    u8::MAX
-                )));
-            }
-            into.push(index as u8);
-            if let Some(params) = params {
-                write_c_value(DataValue::Struct(params), align, into)?
-            }
-        },
+    let mut message = vec![];
 
-        DataValue::Some(v) => write_c_value(DataValue::Enum { index : 1, params : Some(vec![v]) }, align, into)?,
+    formats.read_transaction(&mut std::io::stdin())?.message(&mut message)?;
 
-        DataValue::None => write_c_value(DataValue::Enum { index : 0, params : None }, align, into)?
+    match format.as_deref() {
+        None | Some("raw") => std::io::stdout()
+            .write_all(&message)
+            .map_err(|e| Error::io(&format!("Failed to write message to stdout: {}", e)))?,
+        Some("hex") => println!("{}", hex::encode(&message)),
+        Some("base58") => println!("{}", bs58::encode(&message).into_string()),
+        Some("base64") => println!("{}", base64::encode(&message)),
+        Some(format) => return Err(Error::usage(&format!("Invalid message format: {}", format)))
     }
 
     Ok(())
 }
 
-fn write_data_value(
-    data_value : DataValue,
-    encoding : &Encoding,
-    into : &mut Vec<u8>
+fn do_attach(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats
 ) -> Result<(), Error>
 {
-    match encoding {
-        Encoding::RustBincodeVarInt => write_rust_bincode_value(data_value, true, into),
-        Encoding::RustBincodeFixedInt => write_rust_bincode_value(data_value, false, into),
-        Encoding::RustBorsh => write_rust_borsh_value(data_value, into),
-        Encoding::C => write_c_value(data_value, true, into)
+    let mut signatures = vec![];
+
+    for a in args {
+        signatures.push(make_pubkey_signature(&a)?);
     }
-}
 
-fn split_proper(
-    mut w : &str,
-    delim : char
-) -> Vec<&str>
-{
-    let mut ret = vec![];
+    let mut transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-    loop {
-        if let Some(index) = w.find(delim) {
-            if index > 0 {
-                ret.push(&w[0..index]);
-            }
-            ret.push(&w[index..(index + 1)]);
-            w = &w[(index + 1)..];
-        }
-        else {
-            if w.len() > 0 {
-                ret.push(&w);
-            }
-            break;
-        }
+    for (pubkey, signature) in signatures {
+        transaction.attach_signature(&pubkey, signature)?;
     }
 
-    ret
-}
-
-fn make_words(w : &str) -> Vec<String>
-{
-    split_proper(w, '[')
-        .iter()
-        .map(|s| split_proper(s, ']'))
-        .flatten()
-        .collect::<Vec<&str>>()
-        .iter()
-        .map(|s| s.to_string())
-        .collect::<Vec<String>>()
+    formats.write_transaction(&transaction, &mut std::io::stdout())
 }
 
-fn do_encode(args : &mut std::env::Args) -> Result<(), Error>
+fn do_merge(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats
+) -> Result<(), Error>
 {
-    // If args is empty, then read from stdin
-    let args : Vec<String> = args.collect();
+    let mut merged : Option<(String, Transaction)> = None;
 
-    let mut words = Vec::<String>::new();
+    for path in args {
+        // "-" reads the transaction from standard input instead of from a file
+        let transaction = if path == "-" {
+            formats.read_transaction(&mut std::io::stdin())?
+        }
+        else {
+            formats.read_transaction(
+                &mut std::fs::File::open(&path).map_err(|e| Error::io(&format!("Failed to open {}: {}", path, e)))?
+            )?
+        };
 
-    if args.len() == 0 {
-        // Read args from stdin.  Split all [ and ] off into separate word to make parsing easier
-        for line in std::io::BufReader::new(std::io::stdin()).lines() {
-            line?.split_whitespace().for_each(|e| {
-                words.extend(make_words(e));
-            });
+        match &mut merged {
+            None => merged = Some((path, transaction)),
+            Some((merged_path, merged_transaction)) => {
+                merged_transaction.merge_signatures(&transaction).map_err(|e| {
+                    e.with_context(&format!("Cannot merge the transaction in {} into {}", path, merged_path))
+                })?
+            },
         }
     }
-    else {
-        args.iter().for_each(|a| words.extend(make_words(&a)));
-    }
 
-    // If first element is encoding, then set the encoding from it; else use a default
-    if words.len() == 0 {
-        return Err(stre("No encode parameters"));
+    match merged {
+        Some((_, transaction)) => formats.write_transaction(&transaction, &mut std::io::stdout()),
+        None => Err(Error::usage("No transactions to merge"))
     }
+}
 
-    let encoding = {
-        if words[0] == "encoding" {
-            let encoding = read_single_value(&mut words)?;
-            match encoding.as_str() {
-                "rust_bincode_varint" => Encoding::RustBincodeVarInt,
-                "rust_bincode_fixedint" => Encoding::RustBincodeFixedInt,
-                "rust_borsh" => Encoding::RustBorsh,
-                "c" => Encoding::C,
-                _ => return Err(stre(&format!("Invalid encoding: {}", encoding)))
-            }
-        }
-        else {
-            Encoding::RustBincodeVarInt
-        }
-    };
+fn do_verify(formats : &TransactionFormats) -> Result<(), Error>
+{
+    let statuses = formats.read_transaction(&mut std::io::stdin())?.verify_signatures()?;
 
-    // Read fee payer
-    let fee_payer = {
-        if words.len() == 0 {
-            return Err(stre("Missing fee payer"));
-        }
+    for (pubkey, status) in &statuses {
+        println!("{} {}", pubkey, status);
+    }
 
-        if words[0] != "fee_payer" {
-            return Err(stre("Expected fee_payer before instructions"));
-        }
+    match statuses.iter().filter(|(_, status)| *status == SignatureStatus::Invalid).count() {
+        0 => Ok(()),
+        1 => Err(Error::signature("Transaction has 1 invalid signature")),
+        invalid_count => Err(Error::signature(&format!("Transaction has {} invalid signatures", invalid_count)))
+    }
+}
 
-        words.remove(0);
+// Reads the cluster option's cluster identifier or genesis hash, returning the genesis hash
+fn read_cluster_arg(args : &mut std::env::Args) -> Result<String, Error>
+{
+    genesis_hash_from_arg(&args.next().ok_or_else(|| Error::usage("The final cluster parameter is incomplete"))?)
+}
 
-        make_pubkey(&pubkey_from_words(&mut words)?)?
+// Reads the error-table option's program id and error table file
+fn read_error_table_arg(
+    args : &mut std::env::Args,
+    error_tables : &mut ErrorTables
+) -> Result<(), Error>
+{
+    let (program_id, path) = match (args.next(), args.next()) {
+        (Some(program_id), Some(path)) => (program_id, path),
+        _ => return Err(Error::usage("The final error-table parameter is incomplete"))
     };
 
-    let mut transaction = Transaction::new(fee_payer);
-
-    // Read and add instructions
-    loop {
-        skip_comments(&mut words)?;
+    error_tables.add(make_pubkey(&program_id)?.into(), read_error_table_file(&path)?);
 
-        if words.len() == 0 {
-            break;
-        }
+    Ok(())
+}
 
-        if words[0] != "program" {
-            return Err(stre("First line of instruction is expected to be program"));
+fn do_simulate(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
+) -> Result<(), Error>
+{
+    let mut report = false;
+    let mut options = SimulateOptions::default();
+    let mut error_tables = ErrorTables::default();
+    let mut genesis_hash = None;
+    let mut rpc_url = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name : &str| {
+            args.next().ok_or_else(|| Error::usage(&format!("The final {} parameter is incomplete", name)))
+        };
+        match arg.as_str() {
+            "report" => report = true,
+            "sig-verify" => options.sig_verify = true,
+            "replace-blockhash" => options.replace_recent_blockhash = true,
+            "commitment" => options.commitment = Some(make_commitment(&value("commitment")?)?),
+            "min-context-slot" => {
+                let slot = value("min-context-slot")?;
+                options.min_context_slot =
+                    Some(u64::from_str(&slot).map_err(|_| Error::usage(&format!("Invalid slot: {}", slot)))?);
+            },
+            "account" => options.accounts.push(make_pubkey(&value("account")?)?.into()),
+            "error-table" => read_error_table_arg(args, &mut error_tables)?,
+            "cluster" => genesis_hash = Some(read_cluster_arg(args)?),
+            _ => {
+                if rpc_url.is_some() {
+                    return Err(Error::usage(&format!("Invalid argument: {}", arg)));
+                }
+                rpc_url = Some(arg);
+            }
         }
+    }
 
-        words.remove(0);
+    if options.sig_verify && options.replace_recent_blockhash {
+        return Err(Error::usage("sig-verify and replace-blockhash cannot be used together"));
+    }
 
-        let program_id = make_pubkey(&pubkey_from_words(&mut words)?)?;
+    let endpoint = config.rpc_endpoint(rpc_url.as_deref())?;
 
-        let mut accounts : Vec<(Address, bool, bool)> = vec![];
+    if options.commitment.is_none() {
+        options.commitment = config.profile.commitment.clone();
+    }
 
-        read_accounts(&mut words, &mut accounts)?;
+    let transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-        let mut data_values = Vec::<DataValue>::new();
+    check_clusters(&endpoint, &[&genesis_hash, &endpoint.genesis_hash])?;
 
-        read_data_values(&mut words, &mut data_values)?;
+    let value = simulate_transaction(&endpoint, &transaction, &options)?;
 
-        let mut data = Vec::<u8>::new();
+    let err = value["err"].clone();
 
-        for dv in data_values.into_iter() {
-            write_data_value(dv, &encoding, &mut data)?;
-        }
+    let logs = value["logs"].clone();
 
-        transaction.add_instruction(Instruction { program_address : program_id.into(), addresses : accounts, data });
+    // The report is printed whether or not the simulation failed, since its logs are most useful on failure
+    if report {
+        println!("{}", simulation_report(value, &options.accounts, &transaction, &error_tables)?);
     }
 
-    transaction.encode(&mut std::io::stdout())
-}
-
-fn do_decode() -> Result<(), Error>
-{
-    write!(std::io::stdout(), "{}", format!("{}", Transaction::decode(&mut std::io::stdin())?))
-        .map_err(|err| Box::new(err).into())
+    match err {
+        serde_json::Value::Null if report => Ok(()),
+        serde_json::Value::Null => formats.write_transaction(&transaction, &mut std::io::stdout()),
+        err => Err(transaction_rejected(&transaction_error_description(&err, Some(&logs), &transaction, &error_tables)))
+    }
 }
 
-fn post_json_honor_backoff(
-    url : &str,
-    json : &str
-) -> Result<ureq::Response, ureq::Error>
+// Simulates a transaction with the states of its writable accounts returned, and reports how the transaction changes
+// their balances from their current states
+fn do_preview(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
+) -> Result<(), Error>
 {
-    loop {
-        match ureq::post(&url).set("Content-Type", "application/json").send_string(&json) {
-            Ok(response) => return Ok(response),
-            Err(ureq::Error::Status(status, response)) => {
-                if status == 429 {
-                    // Sleep according to the Retry-After header, or a default of 3 seconds if that header is not
-                    // present
-                    std::thread::sleep(std::time::Duration::from_secs(
-                        response.header("Retry-After").and_then(|value| value.parse::<u64>().ok()).unwrap_or(3)
-                    ));
-                }
-                else {
-                    return Err(ureq::Error::Status(status, response));
-                }
+    let mut commitment = None;
+    let mut error_tables = ErrorTables::default();
+    let mut genesis_hash = None;
+    let mut rpc_url = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "commitment" => {
+                let value = args.next().ok_or_else(|| Error::usage("The final commitment parameter is incomplete"))?;
+                commitment = Some(make_commitment(&value)?);
             },
-            Err(error) => return Err(error)
+            "error-table" => read_error_table_arg(args, &mut error_tables)?,
+            "cluster" => genesis_hash = Some(read_cluster_arg(args)?),
+            _ => {
+                if rpc_url.is_some() {
+                    return Err(Error::usage(&format!("Invalid argument: {}", arg)));
+                }
+                rpc_url = Some(arg);
+            }
         }
     }
-}
 
-fn fetch_recent_blockhash_using_method(
-    rpc_url : &str,
-    method : &str
-) -> Result<String, Error>
-{
-    let resp = post_json_honor_backoff(
-        rpc_url,
-        &format!(
-            "{}",
-            serde_json::json!({
-                "jsonrpc" : "2.0",
-                "id" : 1,
-                "method" : method
-            })
-        )
-    )?;
+    let endpoint = config.rpc_endpoint(rpc_url.as_deref())?;
 
-    match jv(serde_json::from_reader(resp.into_reader()).map_err(|e| format!("{}", e))?, "result.value.blockhash")? {
-        serde_json::Value::String(s) => Ok(s),
-        _ => Err(stre(&format!("Invalid response to {}", method)))
-    }
-}
+    let commitment = commitment.or_else(|| config.profile.commitment.clone());
 
-fn fetch_recent_blockhash(rpc_url : String) -> Result<String, Error>
-{
-    fetch_recent_blockhash_using_method(&rpc_url, "getLatestBlockhash")
-        .or_else(|_| fetch_recent_blockhash_using_method(&rpc_url, "getRecentBlockhash"))
-}
+    let transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-fn jv(
-    mut v : serde_json::Value,
-    path : &str
-) -> Result<serde_json::Value, Error>
-{
-    for s in path.split(".") {
-        v = match v {
-            serde_json::Value::Object(m) => {
-                m.get(s).ok_or(format!("Invalid response json, missing field {}", s))?.clone()
-            },
-            _ => return Err(stre("Invalid response json, expected object"))
-        };
-    }
+    check_clusters(&endpoint, &[&genesis_hash, &endpoint.genesis_hash])?;
 
-    Ok(v)
-}
+    let preview = preview_transaction(&endpoint, &transaction, commitment, &error_tables)?;
 
-fn get_rpc_url(args : &mut std::env::Args) -> Result<String, Error>
-{
-    let args : Vec<String> = args.collect();
+    println!("{}", preview);
 
-    Ok(match args.len() {
-        0 => DEFAULT_MAINNET_RPC_URL.to_string(),
-        1 => match args[0].as_str() {
-            "l" | "localhost" => DEFAULT_LOCALHOST_RPC_URL.to_string(),
-            "d" | "devnet" => DEFAULT_DEVNET_RPC_URL.to_string(),
-            "t" | "testnet" => DEFAULT_TESTNET_RPC_URL.to_string(),
-            "m" | "mainnet" => DEFAULT_MAINNET_RPC_URL.to_string(),
-            _ => args[0].clone()
-        },
-        _ => return Err(stre(&format!("Invalid argument: {}", args[1])))
-    })
+    check_execution_report(&preview)
 }
 
-fn do_hash(args : &mut std::env::Args) -> Result<(), Error>
+fn do_submit(
+    args : &mut std::env::Args,
+    formats : &TransactionFormats,
+    config : &Config
+) -> Result<(), Error>
 {
-    let mut transaction = Transaction::decode(&mut std::io::stdin())?;
-
-    transaction.set_recent_blockhash(Sha256Digest::from_str(&fetch_recent_blockhash(get_rpc_url(args)?)?)?);
+    let mut report = false;
+    let mut wait = true;
+    let mut commitment = None;
+    let mut expiry = None;
+    let mut record = false;
+    let mut options = SubmitOptions::default();
+    let mut error_tables = ErrorTables::default();
+    let mut genesis_hash = None;
+    let mut endpoints = vec![];
+
+    while let Some(arg) = args.next() {
+        let mut value = |name : &str| {
+            args.next().ok_or_else(|| Error::usage(&format!("The final {} parameter is incomplete", name)))
+        };
+        match arg.as_str() {
+            "report" => report = true,
+            "no-wait" => wait = false,
+            "skip-preflight" => options.send.skip_preflight = true,
+            "preflight-commitment" => {
+                options.send.preflight_commitment = Some(make_commitment(&value("preflight-commitment")?)?)
+            },
+            "max-retries" => {
+                let max_retries = value("max-retries")?;
+                options.send.max_retries = Some(
+                    u64::from_str(&max_retries)
+                        .map_err(|_| Error::usage(&format!("Invalid max-retries: {}", max_retries)))?
+                );
+            },
+            "commitment" => commitment = Some(make_commitment(&value("commitment")?)?),
+            "timeout" => {
+                let seconds = value("timeout")?;
+                options.timeout = u64::from_str(&seconds)
+                    .ok()
+                    .filter(|seconds| *seconds > 0)
+                    .ok_or_else(|| Error::usage(&format!("Invalid timeout: {}", seconds)))?;
+            },
+            "rebroadcast" => {
+                let seconds = value("rebroadcast")?;
+                options.rebroadcast =
+                    u64::from_str(&seconds).map_err(|_| Error::usage(&format!("Invalid rebroadcast: {}", seconds)))?;
+            },
+            "expiry" => expiry = Some(read_blockhash_expiry_file(&value("expiry")?)?),
+            "record" => record = true,
+            "error-table" => read_error_table_arg(args, &mut error_tables)?,
+            "cluster" => genesis_hash = Some(read_cluster_arg(args)?),
+            _ => endpoints.push(config.rpc_endpoint(Some(&arg))?)
+        }
+    }
 
-    transaction.encode(&mut std::io::stdout())
-}
+    if report && !wait {
+        return Err(Error::usage("report and no-wait cannot be used together"));
+    }
 
-fn do_show_unsigned() -> Result<(), Error>
-{
-    Ok(Transaction::decode(&mut std::io::stdin())?.needed_signatures().for_each(|p| println!("{}", p)))
-}
+    if record && expiry.is_some() {
+        return Err(Error::usage("record and expiry cannot be used together"));
+    }
 
-fn do_signature() -> Result<(), Error>
-{
-    let tx = Transaction::decode(&mut std::io::stdin())?;
+    if endpoints.is_empty() {
+        endpoints.push(config.rpc_endpoint(None)?);
+    }
 
-    if tx.signed_read_write_addresses.len() > 0 {
-        if let Some(signature) = tx.signed_read_write_addresses[0].signature {
-            println!("{}", bs58::encode(signature.to_bytes()).into_string());
-            return Ok(());
-        }
+    if let Some(commitment) = commitment.or_else(|| config.profile.commitment.clone()) {
+        options.commitment = commitment;
     }
 
-    Err(stre("Transaction is not signed and thus has no signature"))
-}
+    let transaction = formats.read_transaction(&mut std::io::stdin())?;
 
-fn do_sign(args : &mut std::env::Args) -> Result<(), Error>
-{
-    let mut keypairs = vec![];
+    // Sanity check transaction to make sure that it has all needed signatures
+    check_signed(&transaction)?;
 
-    for a in args {
-        keypairs.push(make_keypair(&a)?);
+    if record {
+        let blockhash = transaction
+            .recent_blockhash
+            .as_ref()
+            .map(|hash| format!("{}", hash))
+            .ok_or_else(|| Error::usage("The transaction has no recent blockhash"))?;
+        expiry = Some(read_blockhash_record(&blockhash).ok_or_else(|| {
+            Error::usage(&format!("There is no record of blockhash {}; apply it with solxact hash record", blockhash))
+        })?);
     }
 
-    let mut transaction = Transaction::decode(&mut std::io::stdin())?;
-
-    let mut message = vec![];
+    let (genesis_hash, last_valid_block_height) = check_recent_blockhash(&transaction, expiry, genesis_hash)?;
 
-    transaction.message(&mut message)?;
+    options.last_valid_block_height = last_valid_block_height;
 
-    for keypair in keypairs {
-        transaction.sign(&Pubkey(keypair.public.to_bytes()), keypair.sign(&message))?;
+    // Every RPC node must belong to the expected cluster, and to the one that the recent blockhash came from
+    for endpoint in &endpoints {
+        check_clusters(endpoint, &[&genesis_hash, &endpoint.genesis_hash])?;
     }
 
-    transaction.encode(&mut std::io::stdout())
-}
-
-fn do_simulate(args : &mut std::env::Args) -> Result<(), Error>
-{
-    let rpc_url = get_rpc_url(args)?;
+    // The timeout covers everything from sending the transaction until it is reported
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(options.timeout);
 
-    let transaction = {
-        let decoded_transaction = Transaction::decode(&mut std::io::stdin())?;
-        let mut encoded_transaction = vec![];
-        decoded_transaction.encode(&mut encoded_transaction)?;
-        encoded_transaction
+    let (signature, index) = match send_submission(&endpoints, &transaction, &options.send)? {
+        Sent::Signature(signature, index) => (signature, index),
+        Sent::PreflightFailed(submission) => return finish_submission(submission, report, &transaction, &error_tables)
     };
 
-    let json_request = format!(
-        "{}",
-        serde_json::json!({
-        "jsonrpc" : "2.0",
-        "id" : 1,
-        "method" : "simulateTransaction",
-        "params" : [
-            base64::encode(&transaction),
-            {
-                "encoding" : "base64"
-            }
-        ]
-        })
-    );
+    if !report {
+        println!("Transaction signature: {}", signature);
+    }
 
-    let resp = post_json_honor_backoff(&rpc_url, &json_request).map_err(|e| format!("{}", e))?;
+    if !wait {
+        return Ok(());
+    }
 
-    let result_json = serde_json::from_reader(resp.into_reader()).map_err(|e| format!("{}", e))?;
+    let submission = wait_for_commitment(&endpoints, index, &transaction, &signature, &options, deadline)?;
 
-    let result_json_string = format!("{}", result_json);
+    let submission =
+        if report { fetch_execution_report(&endpoints, index, &signature, &options, deadline)? } else { submission };
 
-    match jv(result_json, "result.value.err") {
-        Ok(serde_json::Value::Null) => {
-            std::io::stdout()
-                .write(&transaction)
-                .map_err(|e| format!("Failed to write transaction to stdout: {}", e))?;
-            Ok(())
-        },
-        Ok(v) => Err(stre(&format!("{}", v))),
-        Err(_) => Err(stre(&result_json_string))
-    }
+    finish_submission(submission, report, &transaction, &error_tables)
 }
 
-fn do_submit(args : &mut std::env::Args) -> Result<(), Error>
+// Completes the report printed by submit report from the signature, slot, err, units_consumed, and logs of a
+// submission, printing it if it was requested, and fails if the transaction did
+fn finish_submission(
+    submission : serde_json::Value,
+    report : bool,
+    transaction : &Transaction,
+    error_tables : &ErrorTables
+) -> Result<(), Error>
 {
-    let rpc_url = get_rpc_url(args)?;
-
-    let transaction = Transaction::decode(&mut std::io::stdin())?;
-
-    // Sanity check transaction to make sure that it has all needed signatures
-    let mut needed_signatures = transaction.needed_signatures();
-
-    let needed_signature = needed_signatures.next();
+    let submission = complete_submission_report(submission, transaction, error_tables);
 
-    if let Some(needed_signature) = needed_signature {
-        let mut msg = "Transaction cannot be submitted because it is not signed by: ".to_string();
+    if report {
+        println!("{}", submission);
+    }
 
-        write!(msg, "{}", needed_signature)?;
+    check_execution_report(&submission)
+}
 
-        for pubkey in needed_signatures {
-            write!(msg, ", {}", pubkey)?;
+fn do_keygen(args : &mut std::env::Args) -> Result<(), Error>
+{
+    let mut force = false;
+    let mut mnemonic_word_count = None;
+    let mut prefix = None;
+    let mut suffix = None;
+    let mut ignore_case = false;
+    let mut thread_count = None;
+    let mut path = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name : &str| {
+            args.next().ok_or_else(|| Error::usage(&format!("The final {} parameter is incomplete", name)))
+        };
+        match arg.as_str() {
+            "force" => force = true,
+            "mnemonic" => {
+                let word_count = value("mnemonic")?;
+                mnemonic_word_count = Some(
+                    usize::from_str(&word_count)
+                        .map_err(|_| Error::usage(&format!("Invalid mnemonic word count: {}", word_count)))?
+                );
+            },
+            "prefix" => prefix = Some(value("prefix")?),
+            "suffix" => suffix = Some(value("suffix")?),
+            "ignore-case" => ignore_case = true,
+            "threads" => {
+                let threads = value("threads")?;
+                thread_count = Some(
+                    usize::from_str(&threads)
+                        .ok()
+                        .filter(|t| *t > 0)
+                        .ok_or_else(|| Error::usage(&format!("Invalid thread count: {}", threads)))?
+                );
+            },
+            _ => {
+                if path.is_some() {
+                    return Err(Error::usage(&format!("Invalid argument: {}", arg)));
+                }
+                path = Some(arg);
+            }
         }
+    }
 
-        return Err(stre(&msg));
+    let path = path.ok_or_else(|| Error::usage("Missing keypair file path"))?;
+
+    // Fail early rather than after a potentially long grind
+    if !force && std::path::Path::new(&path).exists() {
+        return Err(Error::io(&format!("{} already exists; use force to overwrite it", path)));
+    }
+
+    let (keypair, mnemonic) = if prefix.is_some() || suffix.is_some() {
+        let thread_count =
+            thread_count.unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+        grind_keypair(
+            mnemonic_word_count,
+            prefix.as_deref().unwrap_or(""),
+            suffix.as_deref().unwrap_or(""),
+            ignore_case,
+            thread_count,
+            |attempts, elapsed| {
+                let seconds = elapsed.as_secs_f64();
+                eprintln!(
+                    "Searched {} keypairs in {:.0} seconds ({:.0} keypairs/second)",
+                    attempts,
+                    seconds,
+                    (attempts as f64) / seconds
+                );
+            }
+        )?
     }
+    else {
+        generate_keypair(mnemonic_word_count, &mut rand::rngs::OsRng)?
+    };
 
-    let mut encoded_transaction = vec![];
+    write_keypair_file(&path, &keypair, force)?;
 
-    transaction.encode(&mut encoded_transaction)?;
+    println!("{}", Pubkey(keypair.public.to_bytes()));
 
-    let json_request = format!(
-        "{}",
-        serde_json::json!({
-            "jsonrpc" : "2.0",
-            "id" : 1,
-            "method" : "sendTransaction",
-            "params" : [
-                base64::encode(&encoded_transaction),
-                {
-                    "encoding" : "base64"
-                }
-            ]
-        })
-    );
-
-    let resp = post_json_honor_backoff(&rpc_url, &json_request).map_err(|e| format!("{}", e))?;
-
-    let result_json = serde_json::from_reader(resp.into_reader()).map_err(|e| format!("{}", e))?;
-
-    let result_json_string = format!("{}", result_json);
-
-    match jv(result_json, "result") {
-        Ok(serde_json::Value::String(s)) => {
-            println!("Transaction signature: {}", s);
-            let json_request = format!(
-                "{}",
-                serde_json::json!({
-                    "jsonrpc" : "2.0",
-                    "id" : 1,
-                    "method" : "getTransaction",
-                    "params" : [
-                        s,
-                        {
-                            "commitment" : "finalized"
-                        }
-                    ]
-                })
-            );
-            loop {
-                let resp = post_json_honor_backoff(&rpc_url, &json_request).map_err(|e| format!("{}", e))?;
-
-                let json_result = serde_json::from_reader(resp.into_reader()).map_err(|e| format!("{}", e))?;
-                match jv(json_result, "result") {
-                    Ok(serde_json::Value::Null) => {
-                        std::thread::sleep(std::time::Duration::from_secs(1));
-                    },
-                    Ok(_) => {
-                        return Ok(());
-                    },
-                    Err(err) => return Err(err)
-                }
-            }
-        },
-        Ok(v) => Err(stre(&format!("{}", v))),
-        Err(_) => Err(stre(&result_json_string))
+    if let Some(mnemonic) = mnemonic {
+        println!("{}", mnemonic);
     }
+
+    Ok(())
 }
 
 fn print_pubkey_bytes(b : &[u8; 32])
@@ -1775,7 +995,7 @@ fn do_pda(args : &mut std::env::Args) -> Result<(), Error>
     let (pda, bump_seed) = if no_bump_seed {
         try_find_pda(&program_id, &seed, None)
             .and_then(|pda| Some((pda, None)))
-            .ok_or(stre("Cannot find PDA, consider allowing bump seed"))?
+            .ok_or(Error::encode("Cannot find PDA, consider allowing bump seed"))?
     }
     else {
         find_pda(&program_id, &seed).and_then(|(pda, bump_seed)| Some((pda, Some(bump_seed)))).unwrap()
@@ -1832,57 +1052,174 @@ fn do_pubkey(args : &mut std::env::Args) -> Result<(), Error>
     Ok(())
 }
 
-fn do_main() -> Result<(), Error>
+// json_errors is set if errors are to be printed as JSON
+fn do_main(json_errors : &mut bool) -> Result<(), Error>
 {
     let mut args = std::env::args();
 
-    match args.nth(1) {
+    let mut formats = TransactionFormats::default();
+
+    let mut profile = None;
+
+    let mut headers = vec![];
+
+    let mut arg = args.nth(1);
+
+    // Options which apply to all commands precede the command
+    loop {
+        match arg.as_deref() {
+            Some("input") => {
+                formats.input = match args.next() {
+                    Some(format) if format == "auto" => None,
+                    Some(format) => Some(TransactionFormat::from_str(&format).map_err(|e| Error::usage(&e))?),
+                    None => return Err(Error::usage("Missing input format"))
+                }
+            },
+            Some("output") => {
+                formats.output = match args.next() {
+                    Some(format) => TransactionFormat::from_str(&format).map_err(|e| Error::usage(&e))?,
+                    None => return Err(Error::usage("Missing output format"))
+                }
+            },
+            Some("profile") => profile = Some(args.next().ok_or_else(|| Error::usage("Missing profile"))?),
+            Some("header") => match (args.next(), args.next()) {
+                (Some(name), Some(value)) => headers.push(make_header(&name, &read_secret_arg(&value)?)?),
+                _ => return Err(Error::usage("Missing header name or value"))
+            },
+            Some("bearer-token") => match args.next() {
+                Some(token) => headers.push(bearer_token_header(&read_secret_arg(&token)?)?),
+                None => return Err(Error::usage("Missing bearer token"))
+            },
+            Some("basic-auth") => match args.next() {
+                Some(credentials) => headers.push(basic_auth_header(&read_secret_arg(&credentials)?)?),
+                None => return Err(Error::usage("Missing basic auth credentials"))
+            },
+            Some("errors") => {
+                *json_errors = match args.next().as_deref() {
+                    Some("text") => false,
+                    Some("json") => true,
+                    Some(format) => return Err(Error::usage(&format!("Invalid error format: {}", format))),
+                    None => return Err(Error::usage("Missing error format"))
+                }
+            },
+            _ => break
+        }
+        arg = args.next();
+    }
+
+    // Which of several Authorization headers an RPC node would use is undefined
+    if headers.iter().filter(|(name, _)| name.eq_ignore_ascii_case("Authorization")).count() > 1 {
+        return Err(Error::usage(
+            "Only one of an Authorization header, bearer-token, and basic-auth may be given on the command line"
+        ));
+    }
+
+    match arg {
         Some(arg) => match arg.as_str() {
             "--help" | "help" => {
                 let msg = match args.nth(0) {
                     Some(arg) => match arg.as_str() {
                         "encode" => &usage::ENCODE_USAGE_MESSAGE,
                         "decode" => &usage::DECODE_USAGE_MESSAGE,
+                        "format" => &usage::FORMAT_USAGE_MESSAGE,
+                        "errors" => &usage::ERRORS_USAGE_MESSAGE,
+                        "config" | "profile" => &usage::CONFIG_USAGE_MESSAGE,
+                        "headers" | "header" | "bearer-token" | "basic-auth" => &usage::HEADERS_USAGE_MESSAGE,
+                        "encode-json" => &usage::ENCODE_JSON_USAGE_MESSAGE,
                         "hash" => &usage::HASH_USAGE_MESSAGE,
+                        "nonce" => &usage::NONCE_USAGE_MESSAGE,
+                        "priority-fee" => &usage::PRIORITY_FEE_USAGE_MESSAGE,
                         "sign" => &usage::SIGN_USAGE_MESSAGE,
                         "show-unsigned" => &usage::SHOW_UNSIGNED_USAGE_MESSAGE,
                         "signature" => &usage::SIGNATURE_USAGE_MESSAGE,
+                        "verify" => &usage::VERIFY_USAGE_MESSAGE,
+                        "merge" => &usage::MERGE_USAGE_MESSAGE,
+                        "message" => &usage::MESSAGE_USAGE_MESSAGE,
+                        "attach" | "add-signature" => &usage::ATTACH_USAGE_MESSAGE,
                         "simulate" => &usage::SIMULATE_USAGE_MESSAGE,
+                        "preview" => &usage::PREVIEW_USAGE_MESSAGE,
                         "submit" => &usage::SUBMIT_USAGE_MESSAGE,
                         "pda" => &usage::PDA_USAGE_MESSAGE,
                         "pubkey" => &usage::PUBKEY_USAGE_MESSAGE,
+                        "keygen" => &usage::KEYGEN_USAGE_MESSAGE,
                         _ => &usage::USAGE_MESSAGE
                     },
                     None => &usage::USAGE_MESSAGE
                 };
                 usage_exit(msg, None)
             },
-            "encode" => do_encode(&mut args),
-            "decode" => do_decode(),
-            "hash" => do_hash(&mut args),
-            "sign" => do_sign(&mut args),
-            "show-unsigned" => do_show_unsigned(),
-            "signature" => do_signature(),
-            "simulate" => do_simulate(&mut args),
-            "submit" => do_submit(&mut args),
+            "encode" => do_encode(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
+            "decode" => do_decode(&mut args, &formats),
+            "encode-json" => do_encode_json(&formats),
+            "hash" => do_hash(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
+            "nonce" => do_nonce(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
+            "priority-fee" => do_priority_fee(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
+            "sign" => do_sign(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
+            "show-unsigned" => do_show_unsigned(&formats),
+            "signature" => do_signature(&formats),
+            "verify" => do_verify(&formats),
+            "merge" => do_merge(&mut args, &formats),
+            "message" => do_message(&mut args, &formats),
+            "attach" | "add-signature" => do_attach(&mut args, &formats),
+            "simulate" => do_simulate(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
+            "preview" => do_preview(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
+            "submit" => do_submit(&mut args, &formats, &Config::load(profile.as_deref(), &headers)?),
             "pda" => do_pda(&mut args),
             "pubkey" => do_pubkey(&mut args),
-            _ => Err(stre(&format!("Unknown command: {}", arg)))
+            "keygen" => do_keygen(&mut args),
+            _ => Err(Error::usage(&format!("Unknown command: {}", arg)))
         },
         None => usage_exit(usage::USAGE_MESSAGE, None)
     }
 }
 
+// Scans the options which precede the command for the errors option without acting on any of them, so that errors in
+// options that come before it are also reported in the requested format
+fn json_errors_requested() -> bool
+{
+    let args : Vec<String> = std::env::args().skip(1).collect();
+
+    let mut json_errors = false;
+
+    let mut i = 0;
+
+    while i < args.len() {
+        let values = match args[i].as_str() {
+            "errors" => {
+                match args.get(i + 1).map(|format| format.as_str()) {
+                    Some("text") => json_errors = false,
+                    Some("json") => json_errors = true,
+                    _ => ()
+                }
+                1
+            },
+            "input" | "output" | "profile" | "bearer-token" | "basic-auth" => 1,
+            "header" => 2,
+            _ => break
+        };
+        i += 1 + values;
+    }
+
+    json_errors
+}
+
 fn main()
 {
-    match do_main() {
+    let mut json_errors = json_errors_requested();
+
+    match do_main(&mut json_errors) {
         Ok(_) => (),
         Err(e) => {
-            eprintln!("");
-            eprintln!("ERROR: {}", e);
-            eprintln!("");
-            eprintln!("Try 'solxact help' for help");
-            std::process::exit(-1);
+            if json_errors {
+                eprintln!("{}", e.to_json());
+            }
+            else {
+                eprintln!("");
+                eprintln!("ERROR: {}", e);
+                eprintln!("");
+                eprintln!("Try 'solxact help' for help");
+            }
+            std::process::exit(e.exit_code());
         }
     }
 }
@@ -0,0 +1,50 @@
+use crate::transaction::Pubkey;
+use sha2::{Digest, Sha256};
+
+fn bytes_are_curve_point(bytes : &[u8; 32]) -> bool
+{
+    curve25519_dalek::edwards::CompressedEdwardsY::from_slice(bytes.as_ref()).decompress().is_some()
+}
+
+pub fn try_find_pda(
+    pubkey : &Pubkey,
+    seed : &[u8],
+    bump_seed : Option<u8>
+) -> Option<Pubkey>
+{
+    let mut hasher = Sha256::new();
+
+    hasher.update(&seed);
+    if let Some(bump_seed) = bump_seed {
+        hasher.update(&[bump_seed]);
+    }
+    hasher.update(&pubkey.0);
+    hasher.update(b"ProgramDerivedAddress");
+
+    let hash = <[u8; 32]>::try_from(hasher.finalize().as_slice()).unwrap();
+
+    if bytes_are_curve_point(&hash) {
+        None
+    }
+    else {
+        Some(Pubkey(hash))
+    }
+}
+
+pub fn find_pda(
+    program_id : &Pubkey,
+    seed : &[u8]
+) -> Option<(Pubkey, u8)>
+{
+    // Use the same algorithm as Solana's seed finding algorithm: start the bump seed at 255 and work backwards
+    let mut bump_seed = (std::u8::MAX) as i16;
+
+    while bump_seed >= 0 {
+        if let Some(pubkey) = try_find_pda(&program_id, seed, Some(bump_seed as u8)) {
+            return Some((pubkey, bump_seed as u8));
+        }
+        bump_seed -= 1;
+    }
+
+    None
+}
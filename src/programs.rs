@@ -0,0 +1,972 @@
+use crate::data_value::{make_words, Encoding};
+use crate::schema::{decode_data, read_data_schemas, SchemaElement};
+use crate::transaction::{Address, Instruction, Transaction};
+use crate::{
+    Error, ADDRESS_LOOKUP_TABLE_PROGRAM_ID, COMPUTE_BUDGET_PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID
+};
+use serde_json::{Map as json_Map, Value as json_Value};
+
+// Built-in decoders for the instructions of well-known programs, which are applied by solxact decode
+
+const STAKE_PROGRAM_ID : &str = "Stake11111111111111111111111111111111111111";
+const VOTE_PROGRAM_ID : &str = "Vote111111111111111111111111111111111111111";
+const MEMO_V1_PROGRAM_ID : &str = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo";
+const MEMO_PROGRAM_ID : &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+const ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID : &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+struct BuiltinInstruction
+{
+    index : u32,
+
+    name : &'static str,
+
+    // Data schema of the arguments that follow the instruction index, or None if the arguments are not decoded
+    arguments : Option<&'static str>,
+
+    // Roles of the instruction's accounts, in order
+    accounts : &'static [&'static str],
+
+    // Role of any accounts beyond those given by accounts
+    remaining_accounts : Option<&'static str>
+}
+
+enum Layout
+{
+    // The data is an instruction index of the given number of bytes followed by the instruction's arguments
+    Indexed
+    {
+        index_size : usize,
+        // The index of the instruction that is given by empty data, if any
+        empty_data_index : Option<u32>,
+        instruction_sets : &'static [&'static [BuiltinInstruction]]
+    },
+
+    // The data is a UTF-8 string that is the only argument of the program's only instruction
+    Utf8(BuiltinInstruction)
+}
+
+struct BuiltinProgram
+{
+    program_id : &'static str,
+
+    name : &'static str,
+
+    encoding : Encoding,
+
+    layout : Layout,
+
+    // Names of the program's custom errors, indexed by error code
+    errors : &'static [&'static str]
+}
+
+macro_rules! instruction {
+    ($index:expr, $name:expr, $arguments:expr, [$($account:expr),*]) => {
+        BuiltinInstruction {
+            index : $index,
+            name : $name,
+            arguments : $arguments,
+            accounts : &[$($account),*],
+            remaining_accounts : None
+        }
+    };
+    ($index:expr, $name:expr, $arguments:expr, [$($account:expr),*], $remaining:expr) => {
+        BuiltinInstruction {
+            index : $index,
+            name : $name,
+            arguments : $arguments,
+            accounts : &[$($account),*],
+            remaining_accounts : Some($remaining)
+        }
+    };
+}
+
+const SYSTEM_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(0, "CreateAccount", Some("lamports:u64 space:u64 owner:pubkey"), ["funding_account", "new_account"]),
+    instruction!(1, "Assign", Some("owner:pubkey"), ["assigned_account"]),
+    instruction!(2, "Transfer", Some("lamports:u64"), ["funding_account", "recipient_account"]),
+    instruction!(3, "CreateAccountWithSeed", Some("base:pubkey seed:string lamports:u64 space:u64 owner:pubkey"), [
+        "funding_account",
+        "created_account",
+        "base_account"
+    ]),
+    instruction!(4, "AdvanceNonceAccount", Some(""), ["nonce_account", "recent_blockhashes_sysvar", "nonce_authority"]),
+    instruction!(5, "WithdrawNonceAccount", Some("lamports:u64"), [
+        "nonce_account",
+        "recipient_account",
+        "recent_blockhashes_sysvar",
+        "rent_sysvar",
+        "nonce_authority"
+    ]),
+    instruction!(6, "InitializeNonceAccount", Some("nonce_authority:pubkey"), [
+        "nonce_account",
+        "recent_blockhashes_sysvar",
+        "rent_sysvar"
+    ]),
+    instruction!(7, "AuthorizeNonceAccount", Some("new_nonce_authority:pubkey"), ["nonce_account", "nonce_authority"]),
+    instruction!(8, "Allocate", Some("space:u64"), ["new_account"]),
+    instruction!(9, "AllocateWithSeed", Some("base:pubkey seed:string space:u64 owner:pubkey"), [
+        "allocated_account",
+        "base_account"
+    ]),
+    instruction!(10, "AssignWithSeed", Some("base:pubkey seed:string owner:pubkey"), [
+        "assigned_account",
+        "base_account"
+    ]),
+    instruction!(11, "TransferWithSeed", Some("lamports:u64 from_seed:string from_owner:pubkey"), [
+        "funding_account",
+        "base_account",
+        "recipient_account"
+    ]),
+    instruction!(12, "UpgradeNonceAccount", Some(""), ["nonce_account"])
+];
+
+const STAKE_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(
+        0,
+        "Initialize",
+        Some(
+            "authorized:struct [ staker:pubkey withdrawer:pubkey ] lockup:struct [ unix_timestamp:i64 epoch:u64 \
+             custodian:pubkey ]"
+        ),
+        ["stake_account", "rent_sysvar"]
+    ),
+    instruction!(1, "Authorize", Some("new_authority:pubkey stake_authorize:enum"), [
+        "stake_account",
+        "clock_sysvar",
+        "authority",
+        "lockup_custodian"
+    ]),
+    instruction!(2, "DelegateStake", Some(""), [
+        "stake_account",
+        "vote_account",
+        "clock_sysvar",
+        "stake_history_sysvar",
+        "stake_config",
+        "stake_authority"
+    ]),
+    instruction!(3, "Split", Some("lamports:u64"), ["stake_account", "split_stake_account", "stake_authority"]),
+    instruction!(4, "Withdraw", Some("lamports:u64"), [
+        "stake_account",
+        "recipient_account",
+        "clock_sysvar",
+        "stake_history_sysvar",
+        "withdraw_authority",
+        "lockup_custodian"
+    ]),
+    instruction!(5, "Deactivate", Some(""), ["stake_account", "clock_sysvar", "stake_authority"]),
+    // The lockup arguments are bincode Options, which are not encoded as enums
+    instruction!(6, "SetLockup", None, ["stake_account", "lockup_or_withdraw_authority"]),
+    instruction!(7, "Merge", Some(""), [
+        "destination_stake_account",
+        "source_stake_account",
+        "clock_sysvar",
+        "stake_history_sysvar",
+        "stake_authority"
+    ]),
+    instruction!(
+        8,
+        "AuthorizeWithSeed",
+        Some("new_authority:pubkey stake_authorize:enum authority_seed:string authority_owner:pubkey"),
+        ["stake_account", "authority_base", "clock_sysvar", "lockup_custodian"]
+    ),
+    instruction!(9, "InitializeChecked", Some(""), ["stake_account", "rent_sysvar", "staker", "withdrawer"]),
+    instruction!(10, "AuthorizeChecked", Some("stake_authorize:enum"), [
+        "stake_account",
+        "clock_sysvar",
+        "authority",
+        "new_authority",
+        "lockup_custodian"
+    ]),
+    instruction!(
+        11,
+        "AuthorizeCheckedWithSeed",
+        Some("stake_authorize:enum authority_seed:string authority_owner:pubkey"),
+        ["stake_account", "authority_base", "clock_sysvar", "new_authority", "lockup_custodian"]
+    ),
+    instruction!(12, "SetLockupChecked", None, [
+        "stake_account",
+        "lockup_or_withdraw_authority",
+        "new_lockup_custodian"
+    ]),
+    instruction!(13, "GetMinimumDelegation", Some(""), []),
+    instruction!(14, "DeactivateDelinquent", Some(""), [
+        "stake_account",
+        "delinquent_vote_account",
+        "reference_vote_account"
+    ]),
+    instruction!(15, "Redelegate", Some(""), [
+        "stake_account",
+        "uninitialized_stake_account",
+        "vote_account",
+        "stake_config",
+        "stake_authority"
+    ]),
+    instruction!(16, "MoveStake", Some("lamports:u64"), [
+        "source_stake_account",
+        "destination_stake_account",
+        "stake_authority"
+    ]),
+    instruction!(17, "MoveLamports", Some("lamports:u64"), [
+        "source_stake_account",
+        "destination_stake_account",
+        "stake_authority"
+    ])
+];
+
+// Vote state updates have layouts that cannot be described by data schemas, and so their arguments are not decoded
+const VOTE_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(
+        0,
+        "InitializeAccount",
+        Some("node_pubkey:pubkey authorized_voter:pubkey authorized_withdrawer:pubkey commission:u8"),
+        ["vote_account", "rent_sysvar", "clock_sysvar", "node"]
+    ),
+    instruction!(1, "Authorize", Some("new_authority:pubkey vote_authorize:enum"), [
+        "vote_account",
+        "clock_sysvar",
+        "authority"
+    ]),
+    instruction!(2, "Vote", None, ["vote_account", "slot_hashes_sysvar", "clock_sysvar", "vote_authority"]),
+    instruction!(3, "Withdraw", Some("lamports:u64"), ["vote_account", "recipient_account", "withdraw_authority"]),
+    instruction!(4, "UpdateValidatorIdentity", Some(""), ["vote_account", "new_identity", "withdraw_authority"]),
+    instruction!(5, "UpdateCommission", Some("commission:u8"), ["vote_account", "withdraw_authority"]),
+    instruction!(6, "VoteSwitch", None, ["vote_account", "slot_hashes_sysvar", "clock_sysvar", "vote_authority"]),
+    instruction!(7, "AuthorizeChecked", Some("vote_authorize:enum"), [
+        "vote_account",
+        "clock_sysvar",
+        "authority",
+        "new_authority"
+    ]),
+    instruction!(8, "UpdateVoteState", None, ["vote_account", "vote_authority"]),
+    instruction!(9, "UpdateVoteStateSwitch", None, ["vote_account", "vote_authority"]),
+    instruction!(
+        10,
+        "AuthorizeWithSeed",
+        Some(
+            "authorization_type:enum current_authority_derived_key_owner:pubkey \
+             current_authority_derived_key_seed:string new_authority:pubkey"
+        ),
+        ["vote_account", "clock_sysvar", "authority_base"]
+    ),
+    instruction!(
+        11,
+        "AuthorizeCheckedWithSeed",
+        Some(
+            "authorization_type:enum current_authority_derived_key_owner:pubkey \
+             current_authority_derived_key_seed:string"
+        ),
+        ["vote_account", "clock_sysvar", "authority_base", "new_authority"]
+    ),
+    instruction!(12, "CompactUpdateVoteState", None, ["vote_account", "vote_authority"]),
+    instruction!(13, "CompactUpdateVoteStateSwitch", None, ["vote_account", "vote_authority"]),
+    instruction!(14, "TowerSync", None, ["vote_account", "vote_authority"]),
+    instruction!(15, "TowerSyncSwitch", None, ["vote_account", "vote_authority"])
+];
+
+const COMPUTE_BUDGET_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(0, "RequestUnitsDeprecated", Some("units:u32 additional_fee:u32"), []),
+    instruction!(1, "RequestHeapFrame", Some("bytes:u32"), []),
+    instruction!(2, "SetComputeUnitLimit", Some("units:u32"), []),
+    instruction!(3, "SetComputeUnitPrice", Some("micro_lamports:u64"), []),
+    instruction!(4, "SetLoadedAccountsDataSizeLimit", Some("bytes:u32"), [])
+];
+
+// Token program instructions pack COption<Pubkey> the same way that borsh encodes Option<Pubkey>
+const TOKEN_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(0, "InitializeMint", Some("decimals:u8 mint_authority:pubkey freeze_authority:option pubkey"), [
+        "mint",
+        "rent_sysvar"
+    ]),
+    instruction!(1, "InitializeAccount", Some(""), ["account", "mint", "owner", "rent_sysvar"]),
+    instruction!(2, "InitializeMultisig", Some("m:u8"), ["multisig", "rent_sysvar"], "signer"),
+    instruction!(3, "Transfer", Some("amount:u64"), ["source", "destination", "authority"], "signer"),
+    instruction!(4, "Approve", Some("amount:u64"), ["source", "delegate", "owner"], "signer"),
+    instruction!(5, "Revoke", Some(""), ["source", "owner"], "signer"),
+    instruction!(
+        6,
+        "SetAuthority",
+        Some("authority_type:enum new_authority:option pubkey"),
+        ["account_or_mint", "current_authority"],
+        "signer"
+    ),
+    instruction!(7, "MintTo", Some("amount:u64"), ["mint", "destination", "mint_authority"], "signer"),
+    instruction!(8, "Burn", Some("amount:u64"), ["account", "mint", "owner"], "signer"),
+    instruction!(9, "CloseAccount", Some(""), ["account", "destination", "owner"], "signer"),
+    instruction!(10, "FreezeAccount", Some(""), ["account", "mint", "freeze_authority"], "signer"),
+    instruction!(11, "ThawAccount", Some(""), ["account", "mint", "freeze_authority"], "signer"),
+    instruction!(
+        12,
+        "TransferChecked",
+        Some("amount:u64 decimals:u8"),
+        ["source", "mint", "destination", "authority"],
+        "signer"
+    ),
+    instruction!(
+        13,
+        "ApproveChecked",
+        Some("amount:u64 decimals:u8"),
+        ["source", "mint", "delegate", "owner"],
+        "signer"
+    ),
+    instruction!(
+        14,
+        "MintToChecked",
+        Some("amount:u64 decimals:u8"),
+        ["mint", "destination", "mint_authority"],
+        "signer"
+    ),
+    instruction!(15, "BurnChecked", Some("amount:u64 decimals:u8"), ["account", "mint", "owner"], "signer"),
+    instruction!(16, "InitializeAccount2", Some("owner:pubkey"), ["account", "mint", "rent_sysvar"]),
+    instruction!(17, "SyncNative", Some(""), ["account"]),
+    instruction!(18, "InitializeAccount3", Some("owner:pubkey"), ["account", "mint"]),
+    instruction!(19, "InitializeMultisig2", Some("m:u8"), ["multisig"], "signer"),
+    instruction!(20, "InitializeMint2", Some("decimals:u8 mint_authority:pubkey freeze_authority:option pubkey"), [
+        "mint"
+    ]),
+    // Token-2022 follows the instruction index with a list of extension types
+    instruction!(21, "GetAccountDataSize", None, ["mint"]),
+    instruction!(22, "InitializeImmutableOwner", Some(""), ["account"]),
+    instruction!(23, "AmountToUiAmount", Some("amount:u64"), ["mint"]),
+    // The UI amount is the remainder of the data, which has no length prefix
+    instruction!(24, "UiAmountToAmount", None, ["mint"])
+];
+
+// Token-2022 extension instructions each carry their own nested instruction index, and their arguments are not
+// decoded
+const TOKEN_2022_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(25, "InitializeMintCloseAuthority", Some("close_authority:option pubkey"), ["mint"]),
+    instruction!(26, "TransferFeeExtension", None, []),
+    instruction!(27, "ConfidentialTransferExtension", None, []),
+    instruction!(28, "DefaultAccountStateExtension", None, []),
+    instruction!(29, "Reallocate", None, ["account", "payer", "system_program", "owner"], "signer"),
+    instruction!(30, "MemoTransferExtension", None, []),
+    instruction!(31, "CreateNativeMint", Some(""), ["payer", "native_mint", "system_program"]),
+    instruction!(32, "InitializeNonTransferableMint", Some(""), ["mint"]),
+    instruction!(33, "InterestBearingMintExtension", None, []),
+    instruction!(34, "CpiGuardExtension", None, []),
+    instruction!(35, "InitializePermanentDelegate", Some("delegate:pubkey"), ["mint"]),
+    instruction!(36, "TransferHookExtension", None, []),
+    instruction!(37, "ConfidentialTransferFeeExtension", None, []),
+    instruction!(38, "WithdrawExcessLamports", Some(""), ["source", "destination", "authority"], "signer"),
+    instruction!(39, "MetadataPointerExtension", None, []),
+    instruction!(40, "GroupPointerExtension", None, []),
+    instruction!(41, "GroupMemberPointerExtension", None, []),
+    instruction!(42, "ConfidentialMintBurnExtension", None, []),
+    instruction!(43, "ScaledUiAmountExtension", None, []),
+    instruction!(44, "PausableExtension", None, [])
+];
+
+const ASSOCIATED_TOKEN_ACCOUNT_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(0, "Create", Some(""), [
+        "funding_account",
+        "associated_token_account",
+        "wallet",
+        "mint",
+        "system_program",
+        "token_program"
+    ]),
+    instruction!(1, "CreateIdempotent", Some(""), [
+        "funding_account",
+        "associated_token_account",
+        "wallet",
+        "mint",
+        "system_program",
+        "token_program"
+    ]),
+    instruction!(2, "RecoverNested", Some(""), [
+        "nested_associated_token_account",
+        "nested_mint",
+        "destination_associated_token_account",
+        "owner_associated_token_account",
+        "owner_mint",
+        "wallet",
+        "token_program"
+    ])
+];
+
+const ADDRESS_LOOKUP_TABLE_INSTRUCTIONS : &[BuiltinInstruction] = &[
+    instruction!(0, "CreateLookupTable", Some("recent_slot:u64 bump_seed:u8"), [
+        "lookup_table",
+        "authority",
+        "payer",
+        "system_program"
+    ]),
+    instruction!(1, "FreezeLookupTable", Some(""), ["lookup_table", "authority"]),
+    instruction!(2, "ExtendLookupTable", Some("new_addresses:vector [ pubkey ]"), [
+        "lookup_table",
+        "authority",
+        "payer",
+        "system_program"
+    ]),
+    instruction!(3, "DeactivateLookupTable", Some(""), ["lookup_table", "authority"]),
+    instruction!(4, "CloseLookupTable", Some(""), ["lookup_table", "authority", "recipient_account"])
+];
+
+const SYSTEM_ERRORS : &[&str] = &[
+    "AccountAlreadyInUse",
+    "ResultWithNegativeLamports",
+    "InvalidProgramId",
+    "InvalidAccountDataLength",
+    "MaxSeedLengthExceeded",
+    "AddressWithSeedMismatch",
+    "NonceNoRecentBlockhashes",
+    "NonceBlockhashNotExpired",
+    "NonceUnexpectedBlockhashValue"
+];
+
+const STAKE_ERRORS : &[&str] = &[
+    "NoCreditsToRedeem",
+    "LockupInForce",
+    "AlreadyDeactivated",
+    "TooSoonToRedelegate",
+    "InsufficientStake",
+    "MergeTransientStake",
+    "MergeMismatch",
+    "CustodianMissing",
+    "CustodianSignatureMissing",
+    "InsufficientReferenceVotes",
+    "VoteAddressMismatch",
+    "MinimumDelinquentEpochsForDeactivationNotMet",
+    "InsufficientDelegation",
+    "RedelegateTransientOrInactiveStake",
+    "RedelegateToSameVoteAccount",
+    "RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted",
+    "EpochRewardsActive"
+];
+
+const VOTE_ERRORS : &[&str] = &[
+    "VoteTooOld",
+    "SlotsMismatch",
+    "SlotHashMismatch",
+    "EmptySlots",
+    "TimestampTooOld",
+    "TooSoonToReauthorize",
+    "LockoutConflict",
+    "NewVoteStateLockoutMismatch",
+    "SlotsNotOrdered",
+    "ConfirmationsNotOrdered",
+    "ZeroConfirmations",
+    "ConfirmationTooLarge",
+    "RootRollBack",
+    "ConfirmationRollBack",
+    "SlotSmallerThanRoot",
+    "TooManyVotes",
+    "VotesTooOldAllFiltered",
+    "RootOnDifferentFork",
+    "ActiveVoteAccountClose",
+    "CommissionUpdateTooLate"
+];
+
+const TOKEN_ERRORS : &[&str] = &[
+    "NotRentExempt",
+    "InsufficientFunds",
+    "InvalidMint",
+    "MintMismatch",
+    "OwnerMismatch",
+    "FixedSupply",
+    "AlreadyInUse",
+    "InvalidNumberOfProvidedSigners",
+    "InvalidNumberOfRequiredSigners",
+    "UninitializedState",
+    "NativeNotSupported",
+    "NonNativeHasBalance",
+    "InvalidInstruction",
+    "InvalidState",
+    "Overflow",
+    "AuthorityTypeNotSupported",
+    "MintCannotFreeze",
+    "AccountFrozen",
+    "MintDecimalsMismatch",
+    "NonNativeNotSupported"
+];
+
+// Token-2022 errors begin with those of the Token program
+const TOKEN_2022_ERRORS : &[&str] = &[
+    "NotRentExempt",
+    "InsufficientFunds",
+    "InvalidMint",
+    "MintMismatch",
+    "OwnerMismatch",
+    "FixedSupply",
+    "AlreadyInUse",
+    "InvalidNumberOfProvidedSigners",
+    "InvalidNumberOfRequiredSigners",
+    "UninitializedState",
+    "NativeNotSupported",
+    "NonNativeHasBalance",
+    "InvalidInstruction",
+    "InvalidState",
+    "Overflow",
+    "AuthorityTypeNotSupported",
+    "MintCannotFreeze",
+    "AccountFrozen",
+    "MintDecimalsMismatch",
+    "NonNativeNotSupported",
+    "ExtensionTypeMismatch",
+    "ExtensionBaseMismatch",
+    "ExtensionAlreadyInitialized",
+    "ConfidentialTransferAccountHasBalance",
+    "ConfidentialTransferAccountNotApproved",
+    "ConfidentialTransferDepositsAndTransfersDisabled",
+    "ConfidentialTransferElGamalPubkeyMismatch",
+    "ConfidentialTransferBalanceMismatch",
+    "MintHasSupply",
+    "NoAuthorityExists",
+    "TransferFeeExceedsMaximum",
+    "MintRequiredForTransfer",
+    "FeeMismatch",
+    "FeeParametersMismatch",
+    "ImmutableOwner",
+    "AccountHasWithheldTransferFees",
+    "NoMemo",
+    "NonTransferable",
+    "NonTransferableNeedsImmutableOwnership",
+    "MaximumPendingBalanceCreditCounterExceeded",
+    "MaximumDepositAmountExceeded",
+    "CpiGuardSettingsLocked",
+    "CpiGuardTransferBlocked",
+    "CpiGuardBurnBlocked",
+    "CpiGuardCloseAccountBlocked",
+    "CpiGuardApproveBlocked",
+    "CpiGuardSetAuthorityBlocked",
+    "CpiGuardOwnerChangeBlocked",
+    "ExtensionNotFound",
+    "NonConfidentialTransfersDisabled",
+    "ConfidentialTransferFeeAccountHasWithheldFee",
+    "InvalidExtensionCombination",
+    "InvalidLengthForAlloc"
+];
+
+const ASSOCIATED_TOKEN_ACCOUNT_ERRORS : &[&str] = &["InvalidOwner"];
+
+const BUILTIN_PROGRAMS : &[BuiltinProgram] = &[
+    BuiltinProgram {
+        program_id : SYSTEM_PROGRAM_ID,
+        name : "System",
+        encoding : Encoding::RustBincodeFixedInt,
+        layout : Layout::Indexed { index_size : 4, empty_data_index : None, instruction_sets : &[SYSTEM_INSTRUCTIONS] },
+        errors : SYSTEM_ERRORS
+    },
+    BuiltinProgram {
+        program_id : STAKE_PROGRAM_ID,
+        name : "Stake",
+        encoding : Encoding::RustBincodeFixedInt,
+        layout : Layout::Indexed { index_size : 4, empty_data_index : None, instruction_sets : &[STAKE_INSTRUCTIONS] },
+        errors : STAKE_ERRORS
+    },
+    BuiltinProgram {
+        program_id : VOTE_PROGRAM_ID,
+        name : "Vote",
+        encoding : Encoding::RustBincodeFixedInt,
+        layout : Layout::Indexed { index_size : 4, empty_data_index : None, instruction_sets : &[VOTE_INSTRUCTIONS] },
+        errors : VOTE_ERRORS
+    },
+    BuiltinProgram {
+        program_id : COMPUTE_BUDGET_PROGRAM_ID,
+        name : "ComputeBudget",
+        encoding : Encoding::RustBorsh,
+        layout : Layout::Indexed {
+            index_size : 1,
+            empty_data_index : None,
+            instruction_sets : &[COMPUTE_BUDGET_INSTRUCTIONS]
+        },
+        errors : &[]
+    },
+    BuiltinProgram {
+        program_id : MEMO_V1_PROGRAM_ID,
+        name : "Memo",
+        encoding : Encoding::RustBorsh,
+        layout : Layout::Utf8(instruction!(0, "Memo", Some("memo:string"), [], "signer")),
+        errors : &[]
+    },
+    BuiltinProgram {
+        program_id : MEMO_PROGRAM_ID,
+        name : "Memo",
+        encoding : Encoding::RustBorsh,
+        layout : Layout::Utf8(instruction!(0, "Memo", Some("memo:string"), [], "signer")),
+        errors : &[]
+    },
+    BuiltinProgram {
+        program_id : TOKEN_PROGRAM_ID,
+        name : "Token",
+        encoding : Encoding::RustBorsh,
+        layout : Layout::Indexed { index_size : 1, empty_data_index : None, instruction_sets : &[TOKEN_INSTRUCTIONS] },
+        errors : TOKEN_ERRORS
+    },
+    BuiltinProgram {
+        program_id : TOKEN_2022_PROGRAM_ID,
+        name : "Token-2022",
+        encoding : Encoding::RustBorsh,
+        layout : Layout::Indexed {
+            index_size : 1,
+            empty_data_index : None,
+            instruction_sets : &[TOKEN_INSTRUCTIONS, TOKEN_2022_INSTRUCTIONS]
+        },
+        errors : TOKEN_2022_ERRORS
+    },
+    BuiltinProgram {
+        program_id : ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+        name : "AssociatedTokenAccount",
+        encoding : Encoding::RustBorsh,
+        layout : Layout::Indexed {
+            index_size : 1,
+            empty_data_index : Some(0),
+            instruction_sets : &[ASSOCIATED_TOKEN_ACCOUNT_INSTRUCTIONS]
+        },
+        errors : ASSOCIATED_TOKEN_ACCOUNT_ERRORS
+    },
+    BuiltinProgram {
+        program_id : ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+        name : "AddressLookupTable",
+        encoding : Encoding::RustBincodeFixedInt,
+        layout : Layout::Indexed {
+            index_size : 4,
+            empty_data_index : None,
+            instruction_sets : &[ADDRESS_LOOKUP_TABLE_INSTRUCTIONS]
+        },
+        errors : &[]
+    }
+];
+
+fn find_builtin_program(program_address : &Address) -> Option<&'static BuiltinProgram>
+{
+    let program_id = format!("{}", program_address);
+
+    BUILTIN_PROGRAMS.iter().find(|program| program.program_id == program_id)
+}
+
+pub fn builtin_program_name(program_address : &Address) -> Option<&'static str>
+{
+    find_builtin_program(program_address).map(|program| program.name)
+}
+
+pub fn builtin_instruction_name(instruction : &Instruction) -> Option<&'static str>
+{
+    find_builtin_program(&instruction.program_address)
+        .and_then(|program| find_builtin_instruction(&program.layout, &instruction.data))
+        .map(|(builtin, _)| builtin.name)
+}
+
+// Returns the name of a custom error of a well-known program
+pub fn builtin_error_name(
+    program_address : &Address,
+    code : u32
+) -> Option<&'static str>
+{
+    find_builtin_program(program_address).and_then(|program| program.errors.get(code as usize).copied())
+}
+
+// Returns the instruction given by data, along with the data of its arguments
+fn find_builtin_instruction<'a>(
+    layout : &'static Layout,
+    data : &'a [u8]
+) -> Option<(&'static BuiltinInstruction, &'a [u8])>
+{
+    match layout {
+        Layout::Indexed { index_size, empty_data_index, instruction_sets } => {
+            let index = if data.is_empty() {
+                (*empty_data_index)?
+            }
+            else if data.len() < *index_size {
+                return None;
+            }
+            else {
+                data[0..*index_size].iter().rev().fold(0_u32, |index, b| (index << 8) | (*b as u32))
+            };
+            instruction_sets
+                .iter()
+                .flat_map(|instructions| instructions.iter())
+                .find(|instruction| instruction.index == index)
+                .map(|instruction| (instruction, &data[data.len().min(*index_size)..]))
+        },
+        Layout::Utf8(instruction) => Some((instruction, data))
+    }
+}
+
+fn decode_builtin_arguments(
+    program : &BuiltinProgram,
+    instruction : &BuiltinInstruction,
+    data : &[u8]
+) -> Result<Option<Vec<json_Value>>, Error>
+{
+    let arguments = match instruction.arguments {
+        Some(arguments) => arguments,
+        None => return Ok(None)
+    };
+
+    let mut words = arguments.split_whitespace().flat_map(make_words).collect::<Vec<String>>();
+
+    let schemas = read_data_schemas(&mut words)?;
+
+    match program.layout {
+        Layout::Indexed { index_size: _, empty_data_index: _, instruction_sets: _ } => {
+            decode_data(&schemas, &program.encoding, data).map(Some)
+        },
+        // The string has no length prefix, so its length is prepended in order to decode it as a borsh string
+        Layout::Utf8(_) => {
+            let mut prefixed = (data.len() as u32).to_le_bytes().to_vec();
+            prefixed.extend(data);
+            decode_data(&schemas, &Encoding::RustBorsh, &prefixed).map(Some)
+        }
+    }
+}
+
+// If the instruction is of a well-known program, adds the program name, instruction name, decoded arguments, and
+// account roles to the instruction's JSON representation.  Arguments are omitted if the data does not match the
+// instruction's layout.
+pub fn decode_builtin_instruction(
+    instruction : &Instruction,
+    map : &mut json_Map<String, json_Value>
+)
+{
+    let program = match find_builtin_program(&instruction.program_address) {
+        Some(program) => program,
+        None => return
+    };
+
+    map.insert("program_name".to_string(), json_Value::String(program.name.to_string()));
+
+    let (builtin, data) = match find_builtin_instruction(&program.layout, &instruction.data) {
+        Some(found) => found,
+        None => return
+    };
+
+    map.insert("instruction_name".to_string(), json_Value::String(builtin.name.to_string()));
+
+    if let Ok(Some(arguments)) = decode_builtin_arguments(program, builtin, data) {
+        map.insert("arguments".to_string(), json_Value::Array(arguments));
+    }
+
+    if let Some(json_Value::Array(addresses)) = map.get_mut("addresses") {
+        for (i, address) in addresses.iter_mut().enumerate() {
+            if let json_Value::Object(address) = address {
+                if let Some(role) = builtin.accounts.get(i).copied().or(builtin.remaining_accounts) {
+                    address.insert("role".to_string(), json_Value::String(role.to_string()));
+                }
+            }
+        }
+    }
+}
+
+// Makes the JSON representation of a transaction with the data of each of its instructions decoded.  schemas gives the
+// data schemas to decode the instructions of programs with, as (program, encoding, schemas), and takes precedence over
+// the built-in decoders of well-known programs.  A program may be given several schemas, one for each of its
+// instruction layouts, and the first that the data matches is used.  Data that matches none of them is given as hex,
+// along with why it did not match.
+pub fn decode_transaction_json(
+    transaction : &Transaction,
+    schemas : &[(Address, Encoding, Vec<SchemaElement>)]
+) -> json_Value
+{
+    let mut json = transaction.to_json();
+
+    if let Some(json_Value::Array(instructions)) = json.get_mut("instructions") {
+        for (instruction, instruction_json) in transaction.instructions.iter().zip(instructions.iter_mut()) {
+            let map = match instruction_json {
+                json_Value::Object(map) => map,
+                _ => continue
+            };
+            let mut program_schemas =
+                schemas.iter().filter(|(program, _, _)| *program == instruction.program_address).peekable();
+            if program_schemas.peek().is_none() {
+                decode_builtin_instruction(instruction, map);
+                continue;
+            }
+            let mut last_error = None;
+            for (_, encoding, schemas) in program_schemas {
+                match decode_data(schemas, encoding, &instruction.data) {
+                    Ok(decoded) => {
+                        map.insert("decoded_data".to_string(), json_Value::Array(decoded));
+                        last_error = None;
+                        break;
+                    },
+                    Err(err) => last_error = Some(err)
+                }
+            }
+            if let Some(err) = last_error {
+                map.insert("data_hex".to_string(), json_Value::String(hex::encode(&instruction.data)));
+                map.insert("decode_error".to_string(), json_Value::String(err.message().to_string()));
+            }
+        }
+    }
+
+    json
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::tests::{system_transfer, FEE_PAYER};
+    use crate::transaction::Pubkey;
+    use std::str::FromStr;
+
+    const PROGRAM : &str = "4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM";
+
+    fn schemas(s : &str) -> Vec<SchemaElement>
+    {
+        read_data_schemas(&mut s.split_whitespace().flat_map(make_words).collect()).unwrap()
+    }
+
+    fn address(s : &str) -> Address
+    {
+        Address(Pubkey::from_str(s).unwrap().0)
+    }
+
+    // A System program transfer followed by three instructions of PROGRAM
+    fn transaction() -> Transaction
+    {
+        let mut transaction = system_transfer();
+
+        for data in [vec![0, 5], vec![1, 7, 0, 0, 0, 0, 0, 0, 0], vec![9]] {
+            transaction.add_instruction(Instruction { program_address : address(PROGRAM), addresses : vec![], data });
+        }
+
+        transaction
+    }
+
+    #[test]
+    fn decodes_each_instruction_with_the_first_of_its_program_schemas_that_fits()
+    {
+        let program_schemas = vec![
+            (address(PROGRAM), Encoding::RustBorsh, schemas("enum 0 amount:u8")),
+            (address(PROGRAM), Encoding::RustBorsh, schemas("enum 1 lamports:u64")),
+        ];
+
+        let json = decode_transaction_json(&transaction(), &program_schemas);
+
+        let instructions = json["instructions"].as_array().unwrap();
+
+        assert_eq!(
+            instructions[1]["decoded_data"],
+            serde_json::json!([
+                { "type" : "enum", "value" : { "index" : 0 } },
+                { "name" : "amount", "type" : "u8", "value" : 5 }
+            ])
+        );
+
+        assert_eq!(
+            instructions[2]["decoded_data"],
+            serde_json::json!([
+                { "type" : "enum", "value" : { "index" : 1 } },
+                { "name" : "lamports", "type" : "u64", "value" : 7 }
+            ])
+        );
+
+        // Data that no schema fits is given as hex, with the error of the last schema tried
+        assert!(instructions[3].get("decoded_data").is_none());
+        assert_eq!(instructions[3]["data_hex"], "09");
+        assert_eq!(instructions[3]["decode_error"], "Expected enum index 1 at offset 0 but found 9");
+    }
+
+    #[test]
+    fn decodes_instructions_of_well_known_programs_without_schemas()
+    {
+        let json = decode_transaction_json(&transaction(), &[]);
+
+        let transfer = &json["instructions"][0];
+
+        assert_eq!(transfer["program_name"], "System");
+        assert_eq!(transfer["instruction_name"], "Transfer");
+        assert_eq!(
+            transfer["arguments"],
+            serde_json::json!([ { "name" : "lamports", "type" : "u64", "value" : 1000000 } ])
+        );
+        assert_eq!(transfer["addresses"][0]["role"], "funding_account");
+        assert_eq!(transfer["addresses"][1]["role"], "recipient_account");
+
+        // Instructions of other programs are left as they are
+        assert!(json["instructions"][1].get("program_name").is_none());
+        assert!(json["instructions"][1].get("decoded_data").is_none());
+    }
+
+    // Decodes a transaction holding the single instruction of program, with the given number of accounts and data
+    fn decode_instruction(
+        program : &str,
+        account_count : usize,
+        data : Vec<u8>
+    ) -> json_Value
+    {
+        let mut transaction = Transaction::new(Pubkey::from_str(FEE_PAYER).unwrap());
+
+        transaction.add_instruction(Instruction {
+            program_address : address(program),
+            addresses : (0..account_count).map(|i| (Address([i as u8 + 1; 32]).into(), false, true)).collect(),
+            data
+        });
+
+        decode_transaction_json(&transaction, &[])["instructions"][0].clone()
+    }
+
+    #[test]
+    fn decodes_token_instructions_with_account_roles()
+    {
+        let mut data = vec![3];
+        data.extend(1500_u64.to_le_bytes());
+
+        let transfer = decode_instruction(TOKEN_PROGRAM_ID, 5, data.clone());
+
+        assert_eq!(transfer["program_name"], "Token");
+        assert_eq!(transfer["instruction_name"], "Transfer");
+        assert_eq!(transfer["arguments"], serde_json::json!([ { "name" : "amount", "type" : "u64", "value" : 1500 } ]));
+
+        // Accounts beyond those that the instruction names are the signers of a multisig authority
+        let roles = transfer["addresses"].as_array().unwrap().iter().map(|a| a["role"].clone()).collect::<Vec<_>>();
+        assert_eq!(roles, vec!["source", "destination", "authority", "signer", "signer"]);
+
+        // Token-2022 has the instructions of the Token program as well as its own
+        assert_eq!(decode_instruction(TOKEN_2022_PROGRAM_ID, 3, data)["instruction_name"], "Transfer");
+
+        // Data that does not match the instruction's layout is named but has no arguments
+        let truncated = decode_instruction(TOKEN_PROGRAM_ID, 3, vec![3, 1]);
+        assert_eq!(truncated["instruction_name"], "Transfer");
+        assert!(truncated.get("arguments").is_none());
+
+        let unknown = decode_instruction(TOKEN_PROGRAM_ID, 0, vec![250]);
+        assert_eq!(unknown["program_name"], "Token");
+        assert!(unknown.get("instruction_name").is_none());
+
+        assert_eq!(builtin_error_name(&address(TOKEN_PROGRAM_ID), 1), Some("InsufficientFunds"));
+        assert_eq!(builtin_error_name(&address(TOKEN_PROGRAM_ID), 1000), None);
+    }
+
+    #[test]
+    fn decodes_memos_compute_budget_and_empty_data_instructions()
+    {
+        let memo = decode_instruction(MEMO_PROGRAM_ID, 1, b"hello".to_vec());
+        assert_eq!(memo["instruction_name"], "Memo");
+        assert_eq!(memo["arguments"], serde_json::json!([ { "name" : "memo", "type" : "string", "value" : "hello" } ]));
+        assert_eq!(memo["addresses"][0]["role"], "signer");
+
+        let mut data = vec![3];
+        data.extend(25000_u64.to_le_bytes());
+        let price = decode_instruction(COMPUTE_BUDGET_PROGRAM_ID, 0, data);
+        assert_eq!(price["program_name"], "ComputeBudget");
+        assert_eq!(price["instruction_name"], "SetComputeUnitPrice");
+        assert_eq!(price["arguments"][0]["value"], 25000);
+
+        // The Associated Token Account program's Create instruction has no data at all
+        let create = decode_instruction(ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, 2, vec![]);
+        assert_eq!(create["instruction_name"], "Create");
+        assert_eq!(create["addresses"][1]["role"], "associated_token_account");
+    }
+
+    #[test]
+    fn decodes_system_transfers()
+    {
+        let mut data = vec![2, 0, 0, 0];
+        data.extend(1000000_u64.to_le_bytes());
+
+        let transfer = decode_instruction(SYSTEM_PROGRAM_ID, 2, data);
+
+        assert_eq!(transfer["program_name"], "System");
+        assert_eq!(transfer["instruction_name"], "Transfer");
+        assert_eq!(
+            transfer["arguments"],
+            serde_json::json!([ { "name" : "lamports", "type" : "u64", "value" : 1000000 } ])
+        );
+        assert_eq!(transfer["addresses"][0]["role"], "funding_account");
+        assert_eq!(transfer["addresses"][1]["role"], "recipient_account");
+    }
+}
@@ -0,0 +1,1261 @@
+use crate::config::expand_home;
+use crate::transaction::{Address, Pubkey, Sha256Digest, Transaction};
+use crate::{Error, ADDRESS_LOOKUP_TABLE_PROGRAM_ID};
+use std::str::FromStr;
+
+pub const DEFAULT_MAINNET_RPC_URL : &str = "https://api.mainnet-beta.solana.com";
+pub const DEFAULT_TESTNET_RPC_URL : &str = "https://api.testnet.solana.com";
+pub const DEFAULT_DEVNET_RPC_URL : &str = "https://api.devnet.solana.com";
+pub const DEFAULT_LOCALHOST_RPC_URL : &str = "http://localhost:8899";
+
+// The genesis hashes that identify the public clusters
+pub const MAINNET_GENESIS_HASH : &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+pub const TESTNET_GENESIS_HASH : &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+pub const DEVNET_GENESIS_HASH : &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+// Nonce accounts are: u32 version, u32 state (1 = initialized), authority pubkey, stored nonce, fee calculator u64
+pub const NONCE_ACCOUNT_SIZE : usize = 80;
+pub const NONCE_ACCOUNT_AUTHORITY_OFFSET : usize = 8;
+pub const NONCE_ACCOUNT_NONCE_OFFSET : usize = 40;
+
+// Requests to RPC nodes fail rather than wait forever on a node that does not respond
+pub const RPC_CONNECT_TIMEOUT_SECONDS : u64 = 10;
+pub const RPC_READ_TIMEOUT_SECONDS : u64 = 30;
+
+// solxact hash record keeps a record of the blockhash that it applies in this directory, in the same form as an expiry
+// file, so that submit record knows the cluster and last valid block height of a transaction's recent blockhash
+// without an expiry file.  Records are removed once they are older than any blockhash could still be valid.
+pub const BLOCKHASH_RECORD_DIR : &str = "~/.cache/solxact/blockhashes";
+pub const BLOCKHASH_RECORD_MAX_AGE_SECONDS : u64 = 24 * 60 * 60;
+
+// Address lookup table accounts begin with a fixed size metadata section, which is followed by the table's addresses.
+// The metadata begins with a u32 discriminator, which is 1 for an initialized table, followed by the u64 slot that the
+// table was deactivated in, which is u64::MAX for a table that has not been deactivated.
+pub const ADDRESS_LOOKUP_TABLE_META_SIZE : usize = 56;
+pub const ADDRESS_LOOKUP_TABLE_DEACTIVATION_SLOT_OFFSET : usize = 4;
+
+// An RPC node to send requests to, along with the HTTP headers to send with each request to it, such as those that
+// carry API keys, and the genesis hash of the cluster that it is expected to belong to, if that is known
+#[derive(Clone, Default)]
+pub struct RpcEndpoint
+{
+    pub url : String,
+
+    pub headers : Vec<(String, String)>,
+
+    pub genesis_hash : Option<String>
+}
+
+impl RpcEndpoint
+{
+    // An RPC node that is sent no additional headers and whose cluster is not known in advance
+    pub fn new(url : &str) -> Self
+    {
+        RpcEndpoint { url : url.to_string(), headers : vec![], genesis_hash : None }
+    }
+}
+
+pub fn post_json_honor_backoff(
+    endpoint : &RpcEndpoint,
+    json : &str
+) -> Result<ureq::Response, ureq::Error>
+{
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(std::time::Duration::from_secs(RPC_CONNECT_TIMEOUT_SECONDS))
+        .timeout_read(std::time::Duration::from_secs(RPC_READ_TIMEOUT_SECONDS))
+        .build();
+
+    loop {
+        let request = endpoint
+            .headers
+            .iter()
+            .fold(agent.post(&endpoint.url).set("Content-Type", "application/json"), |request, (name, value)| {
+                request.set(name, value)
+            });
+        match request.send_string(json) {
+            Ok(response) => return Ok(response),
+            Err(ureq::Error::Status(status, response)) => {
+                if status == 429 {
+                    // Sleep according to the Retry-After header, or a default of 3 seconds if that header is not
+                    // present
+                    std::thread::sleep(std::time::Duration::from_secs(
+                        response.header("Retry-After").and_then(|value| value.parse::<u64>().ok()).unwrap_or(3)
+                    ));
+                }
+                else {
+                    return Err(ureq::Error::Status(status, response));
+                }
+            },
+            Err(error) => return Err(error)
+        }
+    }
+}
+
+// A recent blockhash, along with the last block height at which transactions that use it may be executed.  The last
+// valid block height is unknown when it comes from an RPC node that predates getLatestBlockhash.
+pub struct RecentBlockhash
+{
+    pub blockhash : String,
+
+    pub last_valid_block_height : Option<u64>,
+
+    // The genesis hash of the cluster that the blockhash came from, if it is known
+    pub genesis_hash : Option<String>
+}
+
+fn fetch_recent_blockhash_using_method(
+    endpoint : &RpcEndpoint,
+    method : &str
+) -> Result<RecentBlockhash, Error>
+{
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : method
+            })
+        )
+    )?;
+
+    let value =
+        jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result.value")?;
+
+    match value.get("blockhash") {
+        Some(serde_json::Value::String(s)) => Ok(RecentBlockhash {
+            blockhash : s.clone(),
+            last_valid_block_height : value.get("lastValidBlockHeight").and_then(|height| height.as_u64()),
+            genesis_hash : None
+        }),
+        _ => Err(Error::rpc(&format!("Invalid response to {}", method)))
+    }
+}
+
+pub fn fetch_latest_blockhash(endpoint : &RpcEndpoint) -> Result<RecentBlockhash, Error>
+{
+    fetch_recent_blockhash_using_method(endpoint, "getLatestBlockhash")
+        .or_else(|_| fetch_recent_blockhash_using_method(endpoint, "getRecentBlockhash"))
+}
+
+pub fn fetch_recent_blockhash(rpc_url : String) -> Result<String, Error>
+{
+    fetch_latest_blockhash(&RpcEndpoint::new(&rpc_url)).map(|recent_blockhash| recent_blockhash.blockhash)
+}
+
+pub fn fetch_genesis_hash(endpoint : &RpcEndpoint) -> Result<String, Error>
+{
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "getGenesisHash"
+            })
+        )
+    )?;
+
+    match jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result")? {
+        serde_json::Value::String(s) => Ok(s),
+        _ => Err(Error::rpc("Invalid response to getGenesisHash"))
+    }
+}
+
+// Turns a cluster identifier into the genesis hash of that cluster; anything else must already be a genesis hash
+pub fn genesis_hash_from_arg(arg : &str) -> Result<String, Error>
+{
+    match arg {
+        "m" | "mainnet" | "mainnet-beta" => Ok(MAINNET_GENESIS_HASH.to_string()),
+        "t" | "testnet" => Ok(TESTNET_GENESIS_HASH.to_string()),
+        "d" | "devnet" => Ok(DEVNET_GENESIS_HASH.to_string()),
+        _ => match Sha256Digest::from_str(arg) {
+            Ok(_) => Ok(arg.to_string()),
+            Err(_) => Err(Error::usage(&format!("Invalid cluster: {}", arg)))
+        }
+    }
+}
+
+// Describes the cluster that a genesis hash identifies, by name if it is a public cluster
+fn cluster_description(genesis_hash : &str) -> String
+{
+    match genesis_hash {
+        MAINNET_GENESIS_HASH => format!("mainnet (genesis hash {})", genesis_hash),
+        TESTNET_GENESIS_HASH => format!("testnet (genesis hash {})", genesis_hash),
+        DEVNET_GENESIS_HASH => format!("devnet (genesis hash {})", genesis_hash),
+        _ => format!("the cluster with genesis hash {}", genesis_hash)
+    }
+}
+
+// Checks that an RPC node belongs to the cluster with the given genesis hash
+pub fn check_genesis_hash(
+    endpoint : &RpcEndpoint,
+    genesis_hash : &str
+) -> Result<(), Error>
+{
+    let actual = fetch_genesis_hash(endpoint)?;
+
+    if actual == genesis_hash {
+        Ok(())
+    }
+    else {
+        Err(Error::usage(&format!(
+            "RPC node {} belongs to {}, not {}",
+            endpoint.url,
+            cluster_description(&actual),
+            cluster_description(genesis_hash)
+        )))
+    }
+}
+
+// Checks that an RPC node belongs to each of the clusters that it is expected to, given by genesis hash.  The RPC
+// node's genesis hash is fetched once for each different genesis hash.
+pub fn check_clusters(
+    endpoint : &RpcEndpoint,
+    genesis_hashes : &[&Option<String>]
+) -> Result<(), Error>
+{
+    let mut checked : Vec<&String> = vec![];
+
+    for genesis_hash in genesis_hashes.iter().filter_map(|genesis_hash| genesis_hash.as_ref()) {
+        if !checked.contains(&genesis_hash) {
+            check_genesis_hash(endpoint, genesis_hash)?;
+            checked.push(genesis_hash);
+        }
+    }
+
+    Ok(())
+}
+
+// Applies the latest blockhash of an RPC node's cluster to a transaction, returning it along with the genesis hash of
+// that cluster.  If the genesis hash is already known, it is not fetched again.
+pub fn apply_latest_blockhash(
+    endpoint : &RpcEndpoint,
+    transaction : &mut Transaction,
+    genesis_hash : Option<String>
+) -> Result<RecentBlockhash, Error>
+{
+    let mut recent_blockhash = fetch_latest_blockhash(endpoint)?;
+
+    transaction.set_recent_blockhash(Sha256Digest::from_str(&recent_blockhash.blockhash).map_err(|e| Error::rpc(&e))?);
+
+    recent_blockhash.genesis_hash = match genesis_hash.or_else(|| endpoint.genesis_hash.clone()) {
+        Some(genesis_hash) => Some(genesis_hash),
+        None => Some(fetch_genesis_hash(endpoint)?)
+    };
+
+    Ok(recent_blockhash)
+}
+
+// Fetches the current block height at the given commitment level
+pub fn fetch_block_height(
+    endpoint : &RpcEndpoint,
+    commitment : &str
+) -> Result<u64, Error>
+{
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "getBlockHeight",
+                "params" : [
+                    {
+                        "commitment" : commitment
+                    }
+                ]
+            })
+        )
+    )?;
+
+    jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result")?
+        .as_u64()
+        .ok_or_else(|| Error::rpc("Invalid response to getBlockHeight"))
+}
+
+// Fetches the prioritization fees, in micro-lamports per compute unit, paid by transactions in recent slots that
+// wrote to any of the given addresses
+pub fn fetch_recent_prioritization_fees(
+    endpoint : &RpcEndpoint,
+    addresses : &[Address]
+) -> Result<Vec<u64>, Error>
+{
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "getRecentPrioritizationFees",
+                "params" : [
+                    addresses.iter().map(|a| format!("{}", a)).collect::<Vec<String>>()
+                ]
+            })
+        )
+    )?;
+
+    match jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result")? {
+        serde_json::Value::Array(a) => a
+            .into_iter()
+            .map(|v| {
+                jv(v, "prioritizationFee")?
+                    .as_u64()
+                    .ok_or_else(|| Error::rpc("Invalid response to getRecentPrioritizationFees"))
+            })
+            .collect(),
+        _ => Err(Error::rpc("Invalid response to getRecentPrioritizationFees"))
+    }
+}
+
+// Options of the simulateTransaction RPC method
+#[derive(Default)]
+pub struct SimulateOptions
+{
+    // If true, the transaction's signatures are verified
+    pub sig_verify : bool,
+
+    // If true, the transaction is simulated using the cluster's most recent blockhash in place of its own, which
+    // allows transactions without a recent blockhash to be simulated.  Cannot be used with sig_verify.
+    pub replace_recent_blockhash : bool,
+
+    pub commitment : Option<String>,
+
+    pub min_context_slot : Option<u64>,
+
+    // Accounts whose state after the simulation is returned
+    pub accounts : Vec<Address>
+}
+
+// Simulates a transaction, returning the value of the simulation result, which gives the error that the transaction
+// failed with (or null if it succeeded), its logs, the compute units that it consumed, its return data, and the
+// states of the accounts requested by the options
+pub fn simulate_transaction(
+    endpoint : &RpcEndpoint,
+    transaction : &Transaction,
+    options : &SimulateOptions
+) -> Result<serde_json::Value, Error>
+{
+    let mut encoded_transaction = vec![];
+
+    transaction.encode(&mut encoded_transaction)?;
+
+    let mut config = serde_json::json!({
+        "encoding" : "base64",
+        "sigVerify" : options.sig_verify,
+        "replaceRecentBlockhash" : options.replace_recent_blockhash
+    });
+
+    if let Some(commitment) = &options.commitment {
+        config["commitment"] = serde_json::Value::String(commitment.clone());
+    }
+
+    if let Some(min_context_slot) = options.min_context_slot {
+        config["minContextSlot"] = serde_json::Value::from(min_context_slot);
+    }
+
+    if !options.accounts.is_empty() {
+        config["accounts"] = serde_json::json!({
+            "encoding" : "base64",
+            "addresses" : options.accounts.iter().map(|a| format!("{}", a)).collect::<Vec<String>>()
+        });
+    }
+
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "simulateTransaction",
+                "params" : [
+                    base64::encode(&encoded_transaction),
+                    config
+                ]
+            })
+        )
+    )?;
+
+    let result_json : serde_json::Value =
+        serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?;
+
+    let result_json_string = format!("{}", result_json);
+
+    jv(result_json, "result.value").map_err(|_| Error::rpc(&result_json_string))
+}
+
+// Options passed to sendTransaction
+#[derive(Clone, Default)]
+pub struct SendOptions
+{
+    // If true, the RPC node does not simulate the transaction before sending it
+    pub skip_preflight : bool,
+
+    // The commitment level of the bank that preflight simulation is performed against
+    pub preflight_commitment : Option<String>,
+
+    // How many times the RPC node retries sending the transaction to the leader; if None, it retries until the
+    // transaction is finalized or its blockhash expires
+    pub max_retries : Option<u64>
+}
+
+// Sends a transaction, returning the RPC node's response, which gives either the transaction's signature as its
+// result, or the error that the transaction was rejected with
+pub fn send_transaction(
+    endpoint : &RpcEndpoint,
+    transaction : &Transaction,
+    options : &SendOptions
+) -> Result<serde_json::Value, Error>
+{
+    let mut encoded_transaction = vec![];
+
+    transaction.encode(&mut encoded_transaction)?;
+
+    send_encoded_transaction(endpoint, &encoded_transaction, options)
+}
+
+// Sends a transaction to several RPC nodes in parallel, returning the index of the RPC node that first responded with
+// the transaction's signature, along with its response.  If none did, the first response that is a rejection of the
+// transaction is returned, or failing that, the first response.
+pub fn send_transaction_to_any(
+    endpoints : &[RpcEndpoint],
+    transaction : &Transaction,
+    options : &SendOptions
+) -> (usize, Result<serde_json::Value, Error>)
+{
+    let mut encoded_transaction = vec![];
+
+    if let Err(e) = transaction.encode(&mut encoded_transaction) {
+        return (0, Err(e));
+    }
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    for (index, endpoint) in endpoints.iter().enumerate() {
+        let (sender, endpoint, encoded_transaction, options) =
+            (sender.clone(), endpoint.clone(), encoded_transaction.clone(), options.clone());
+        std::thread::spawn(move || {
+            let _ = sender.send((index, send_encoded_transaction(&endpoint, &encoded_transaction, &options)));
+        });
+    }
+
+    drop(sender);
+
+    let mut first = None;
+
+    let mut first_rejection = None;
+
+    // Threads that are still sending when a signature is returned are left to finish on their own
+    for (index, response) in receiver {
+        match &response {
+            Ok(json) if json.get("result").map(|result| result.is_string()).unwrap_or(false) => {
+                return (index, response)
+            },
+            Ok(json) if first_rejection.is_none() && json.get("error").is_some() => {
+                first_rejection = Some((index, response))
+            },
+            _ => {
+                if first.is_none() {
+                    first = Some((index, response))
+                }
+            },
+        }
+    }
+
+    first_rejection.or(first).unwrap_or_else(|| (0, Err(Error::usage("No RPC nodes were given"))))
+}
+
+// Makes a request of the RPC node at index first, and failing that, of each of the other RPC nodes in turn, until one
+// answers.  If none do, the error of the last is returned.
+pub fn from_any_endpoint<T>(
+    endpoints : &[RpcEndpoint],
+    first : usize,
+    request : impl Fn(&RpcEndpoint) -> Result<T, Error>
+) -> Result<T, Error>
+{
+    let mut result = request(&endpoints[first]);
+
+    for (index, endpoint) in endpoints.iter().enumerate() {
+        if result.is_ok() {
+            break;
+        }
+        if index != first {
+            result = request(endpoint);
+        }
+    }
+
+    result
+}
+
+fn send_encoded_transaction(
+    endpoint : &RpcEndpoint,
+    encoded_transaction : &[u8],
+    options : &SendOptions
+) -> Result<serde_json::Value, Error>
+{
+    let mut config = serde_json::json!({
+        "encoding" : "base64"
+    });
+
+    if options.skip_preflight {
+        config["skipPreflight"] = serde_json::Value::Bool(true);
+    }
+
+    if let Some(preflight_commitment) = &options.preflight_commitment {
+        config["preflightCommitment"] = serde_json::Value::String(preflight_commitment.clone());
+    }
+
+    if let Some(max_retries) = options.max_retries {
+        config["maxRetries"] = serde_json::Value::from(max_retries);
+    }
+
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "sendTransaction",
+                "params" : [
+                    base64::encode(encoded_transaction),
+                    config
+                ]
+            })
+        )
+    )?;
+
+    serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))
+}
+
+// Fetches the status of a transaction, which gives the slot it was executed in, the error that it failed with (or null
+// if it succeeded), and the commitment level it has reached; or null if the RPC node does not know of the transaction
+pub fn fetch_signature_status(
+    endpoint : &RpcEndpoint,
+    signature : &str
+) -> Result<serde_json::Value, Error>
+{
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "getSignatureStatuses",
+                "params" : [
+                    [signature]
+                ]
+            })
+        )
+    )?;
+
+    match jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result.value")? {
+        serde_json::Value::Array(statuses) if statuses.len() == 1 => Ok(statuses[0].clone()),
+        _ => Err(Error::rpc("Invalid response to getSignatureStatuses"))
+    }
+}
+
+// Fetches a transaction that has reached the given commitment level, which must be confirmed or finalized; or null
+// if it has not
+pub fn fetch_transaction(
+    endpoint : &RpcEndpoint,
+    signature : &str,
+    commitment : &str
+) -> Result<serde_json::Value, Error>
+{
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "getTransaction",
+                "params" : [
+                    signature,
+                    {
+                        "commitment" : commitment,
+                        "maxSupportedTransactionVersion" : 0
+                    }
+                ]
+            })
+        )
+    )?;
+
+    jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result")
+}
+
+// Returns true if a transaction status, as returned by fetch_signature_status, has reached the given commitment
+// level.  Statuses without a confirmation status are from nodes that only report confirmations, which are null once
+// the transaction is finalized.
+pub fn commitment_reached(
+    status : &serde_json::Value,
+    commitment : &str
+) -> bool
+{
+    let rank = |commitment : &str| match commitment {
+        "processed" => 0,
+        "confirmed" => 1,
+        _ => 2
+    };
+
+    if status.is_null() {
+        return false;
+    }
+
+    let reached = match status.get("confirmationStatus").and_then(|s| s.as_str()) {
+        Some(confirmation_status) => rank(confirmation_status),
+        None if status.get("confirmations").map(|c| c.is_null()).unwrap_or(false) => 2,
+        None => 0
+    };
+
+    reached >= rank(commitment)
+}
+
+// Fetches the data of an account, which must exist
+pub fn fetch_account_data(
+    endpoint : &RpcEndpoint,
+    address : &Address
+) -> Result<Vec<u8>, Error>
+{
+    fetch_account(endpoint, address).map(|(_, data)| data)
+}
+
+// Fetches the owner and data of an account, which must exist
+pub fn fetch_account(
+    endpoint : &RpcEndpoint,
+    address : &Address
+) -> Result<(String, Vec<u8>), Error>
+{
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "getAccountInfo",
+                "params" : [
+                    format!("{}", address),
+                    {
+                        "encoding" : "base64"
+                    }
+                ]
+            })
+        )
+    )?;
+
+    match jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result.value")? {
+        serde_json::Value::Null => Err(Error::rpc(&format!("Account {} does not exist", address))),
+        value => match (jv(value.clone(), "owner")?, jv(value, "data")?) {
+            (serde_json::Value::String(owner), serde_json::Value::Array(a)) => match a.first() {
+                Some(serde_json::Value::String(s)) => Ok((
+                    owner,
+                    base64::decode(s).map_err(|e| Error::rpc(&format!("Invalid account data: {}", e)))?
+                )),
+                _ => Err(Error::rpc("Invalid response to getAccountInfo"))
+            },
+            _ => Err(Error::rpc("Invalid response to getAccountInfo"))
+        }
+    }
+}
+
+// Fetches the states of accounts, as returned by getMultipleAccounts with base64 encoding; the state of an account
+// that does not exist is null
+pub fn fetch_account_states(
+    endpoint : &RpcEndpoint,
+    addresses : &[Address],
+    commitment : Option<&str>
+) -> Result<Vec<serde_json::Value>, Error>
+{
+    let mut config = serde_json::json!({
+        "encoding" : "base64"
+    });
+
+    if let Some(commitment) = commitment {
+        config["commitment"] = serde_json::Value::String(commitment.to_string());
+    }
+
+    let resp = post_json_honor_backoff(
+        endpoint,
+        &format!(
+            "{}",
+            serde_json::json!({
+                "jsonrpc" : "2.0",
+                "id" : 1,
+                "method" : "getMultipleAccounts",
+                "params" : [
+                    addresses.iter().map(|a| format!("{}", a)).collect::<Vec<String>>(),
+                    config
+                ]
+            })
+        )
+    )?;
+
+    match jv(serde_json::from_reader(resp.into_reader()).map_err(|e| Error::rpc(&e.to_string()))?, "result.value")? {
+        serde_json::Value::Array(states) if states.len() == addresses.len() => Ok(states),
+        _ => Err(Error::rpc("Invalid response to getMultipleAccounts"))
+    }
+}
+
+pub fn fetch_lookup_table(
+    endpoint : &RpcEndpoint,
+    table : &Address
+) -> Result<Vec<Address>, Error>
+{
+    let (owner, data) = fetch_account(endpoint, table)?;
+
+    if (owner != ADDRESS_LOOKUP_TABLE_PROGRAM_ID) ||
+        (data.len() < ADDRESS_LOOKUP_TABLE_META_SIZE) ||
+        (((data.len() - ADDRESS_LOOKUP_TABLE_META_SIZE) % 32) != 0) ||
+        (data[0..4] != [1, 0, 0, 0])
+    {
+        return Err(Error::rpc(&format!("Account {} is not an address lookup table", table)));
+    }
+
+    let deactivation_slot = u64::from_le_bytes(
+        data[ADDRESS_LOOKUP_TABLE_DEACTIVATION_SLOT_OFFSET..(ADDRESS_LOOKUP_TABLE_DEACTIVATION_SLOT_OFFSET + 8)]
+            .try_into()
+            .unwrap()
+    );
+
+    if deactivation_slot != u64::MAX {
+        return Err(Error::rpc(&format!(
+            "Address lookup table {} was deactivated in slot {}",
+            table, deactivation_slot
+        )));
+    }
+
+    Ok(data[ADDRESS_LOOKUP_TABLE_META_SIZE..].chunks(32).map(|c| Address(c.try_into().unwrap())).collect())
+}
+
+// Returns every address that a transaction writes to: its static writable addresses, followed by the writable
+// addresses that it loads from address lookup tables, which are resolved by fetching those tables
+pub fn fetch_writable_addresses(
+    endpoint : &RpcEndpoint,
+    transaction : &Transaction
+) -> Result<Vec<Address>, Error>
+{
+    let tables = transaction
+        .address_table_lookups
+        .iter()
+        .map(|lookup| Ok((lookup.table.clone(), fetch_lookup_table(endpoint, &lookup.table)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut addresses = transaction.static_writable_addresses();
+
+    addresses.extend(transaction.loaded_writable_addresses(&tables).map_err(|e| Error::rpc(&e.to_string()))?);
+
+    Ok(addresses)
+}
+
+// Reads the contents of an address lookup table from a file containing a JSON array of Base58-encoded addresses
+pub fn read_lookup_table_file(path : &str) -> Result<Vec<Address>, Error>
+{
+    let contents = std::fs::read_to_string(path).map_err(|e| Error::io(&format!("Failed to read {}: {}", path, e)))?;
+
+    match serde_json::from_str(&contents)
+        .map_err(|e| Error::parse(&format!("Invalid lookup table file {}: {}", path, e)))?
+    {
+        serde_json::Value::Array(a) => a
+            .iter()
+            .map(|v| match v {
+                serde_json::Value::String(s) => Address::from_str(s).map_err(|e| Error::parse(&e)),
+                _ => Err(Error::parse(&format!("Invalid address in lookup table file {}: {}", path, v)))
+            })
+            .collect(),
+        _ => Err(Error::parse(&format!("Invalid lookup table file contents in {}", path)))
+    }
+}
+
+// Writes a recent blockhash, its last valid block height, and the genesis hash of its cluster to an expiry file, which
+// holds a JSON object with fields blockhash, last_valid_block_height, and genesis_hash
+pub fn write_blockhash_expiry_file(
+    path : &str,
+    recent_blockhash : &RecentBlockhash
+) -> Result<(), Error>
+{
+    std::fs::write(
+        path,
+        format!(
+            "{}\n",
+            serde_json::json!({
+                "blockhash" : recent_blockhash.blockhash,
+                "last_valid_block_height" : recent_blockhash.last_valid_block_height,
+                "genesis_hash" : recent_blockhash.genesis_hash
+            })
+        )
+    )
+    .map_err(|e| Error::io(&format!("Failed to write {}: {}", path, e)))
+}
+
+pub fn read_blockhash_expiry_file(path : &str) -> Result<RecentBlockhash, Error>
+{
+    let json : serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(path).map_err(|e| Error::io(&format!("Failed to read {}: {}", path, e)))?
+    )
+    .map_err(|e| Error::parse(&format!("Invalid expiry file {}: {}", path, e)))?;
+
+    // The genesis hash is null or absent when it is not known
+    let genesis_hash = match json.get("genesis_hash") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(genesis_hash)) => Some(genesis_hash.clone()),
+        Some(_) => return Err(Error::parse(&format!("Invalid expiry file contents in {}", path)))
+    };
+
+    match (json.get("blockhash"), json.get("last_valid_block_height")) {
+        (Some(serde_json::Value::String(blockhash)), Some(height)) if height.is_null() || height.is_u64() => {
+            Ok(RecentBlockhash {
+                blockhash : blockhash.clone(),
+                last_valid_block_height : height.as_u64(),
+                genesis_hash
+            })
+        },
+        _ => Err(Error::parse(&format!("Invalid expiry file contents in {}", path)))
+    }
+}
+
+// The directory holding blockhash records, which is within XDG_CACHE_HOME if that is set
+fn blockhash_record_dir() -> String
+{
+    match std::env::var("XDG_CACHE_HOME") {
+        Ok(cache_home) if !cache_home.is_empty() => format!("{}/solxact/blockhashes", cache_home.trim_end_matches('/')),
+        _ => expand_home(BLOCKHASH_RECORD_DIR)
+    }
+}
+
+// Records a recent blockhash in the default blockhash record directory
+pub fn write_blockhash_record(recent_blockhash : &RecentBlockhash) -> Result<(), Error>
+{
+    write_blockhash_record_in(&blockhash_record_dir(), recent_blockhash)
+}
+
+// Records a recent blockhash, along with its last valid block height and the genesis hash of its cluster, in dir for
+// submit to find by blockhash.  Records in dir that have grown too old to be of use are removed.
+pub fn write_blockhash_record_in(
+    dir : &str,
+    recent_blockhash : &RecentBlockhash
+) -> Result<(), Error>
+{
+    std::fs::create_dir_all(dir).map_err(|e| Error::io(&format!("Failed to create {}: {}", dir, e)))?;
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        let max_age = std::time::Duration::from_secs(BLOCKHASH_RECORD_MAX_AGE_SECONDS);
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .map(|age| age > max_age)
+                .unwrap_or(false);
+            if expired {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    write_blockhash_expiry_file(&format!("{}/{}.json", dir, recent_blockhash.blockhash), recent_blockhash)
+}
+
+// Returns the record of a blockhash in the default blockhash record directory, if there is one
+pub fn read_blockhash_record(blockhash : &str) -> Option<RecentBlockhash>
+{
+    read_blockhash_record_in(&blockhash_record_dir(), blockhash)
+}
+
+// Returns the record of a blockhash written by write_blockhash_record_in to dir, if there is one
+pub fn read_blockhash_record_in(
+    dir : &str,
+    blockhash : &str
+) -> Option<RecentBlockhash>
+{
+    let path = format!("{}/{}.json", dir, blockhash);
+
+    if std::path::Path::new(&path).exists() {
+        read_blockhash_expiry_file(&path).ok().filter(|recent_blockhash| recent_blockhash.blockhash == blockhash)
+    }
+    else {
+        None
+    }
+}
+
+pub fn jv(
+    mut v : serde_json::Value,
+    path : &str
+) -> Result<serde_json::Value, Error>
+{
+    for s in path.split(".") {
+        v = match v {
+            serde_json::Value::Object(m) => {
+                m.get(s).ok_or_else(|| Error::rpc(&format!("Invalid response json, missing field {}", s)))?.clone()
+            },
+            _ => return Err(Error::rpc("Invalid response json, expected object"))
+        };
+    }
+
+    Ok(v)
+}
+
+// Turns a cluster identifier into the URL of that cluster's RPC node; anything else is assumed to already be a URL
+pub fn rpc_url_from_arg(arg : &str) -> String
+{
+    match arg {
+        "l" | "localhost" => DEFAULT_LOCALHOST_RPC_URL.to_string(),
+        "d" | "devnet" => DEFAULT_DEVNET_RPC_URL.to_string(),
+        "t" | "testnet" => DEFAULT_TESTNET_RPC_URL.to_string(),
+        "m" | "mainnet" => DEFAULT_MAINNET_RPC_URL.to_string(),
+        _ => arg.to_string()
+    }
+}
+
+// Returns the stored nonce of a nonce account, checking that the account is initialized and has the expected
+// authority
+pub fn fetch_nonce(
+    endpoint : &RpcEndpoint,
+    nonce_account : &Pubkey,
+    nonce_authority : &Pubkey
+) -> Result<Sha256Digest, Error>
+{
+    let data = fetch_account_data(endpoint, &nonce_account.clone().into())?;
+
+    if (data.len() != NONCE_ACCOUNT_SIZE) || (data[4..8] != [1, 0, 0, 0]) {
+        return Err(Error::rpc(&format!("Account {} is not an initialized nonce account", nonce_account)));
+    }
+
+    let invalid = || Error::rpc(&format!("Invalid nonce account data for {}", nonce_account));
+
+    let authority = Pubkey(
+        data[NONCE_ACCOUNT_AUTHORITY_OFFSET..(NONCE_ACCOUNT_AUTHORITY_OFFSET + 32)]
+            .try_into()
+            .map_err(|_| invalid())?
+    );
+
+    if authority != *nonce_authority {
+        return Err(Error::rpc(&format!(
+            "Nonce account {} has authority {}, not {}",
+            nonce_account, authority, nonce_authority
+        )));
+    }
+
+    Ok(Sha256Digest(
+        data[NONCE_ACCOUNT_NONCE_OFFSET..(NONCE_ACCOUNT_NONCE_OFFSET + 32)].try_into().map_err(|_| invalid())?
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests
+{
+    use super::*;
+    use crate::data_value::{DataValue, Encoding};
+    use crate::{TransactionBuilder, SYSTEM_PROGRAM_ID};
+    use serde_json::Value as json_Value;
+    use std::io::{BufRead, Read, Write};
+
+    pub(crate) const FEE_PAYER : &str = "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk";
+
+    pub(crate) const DESTINATION : &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    // An unsigned System program transfer of 1000000 lamports from FEE_PAYER to DESTINATION, which the tests of
+    // several modules send to mock RPC nodes
+    pub(crate) fn system_transfer() -> Transaction
+    {
+        let fee_payer = Pubkey::from_str(FEE_PAYER).unwrap();
+
+        TransactionBuilder::new(fee_payer.clone())
+            .encoding(Encoding::RustBincodeFixedInt)
+            .program(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap())
+            .account(fee_payer, true, true)
+            .account(Pubkey::from_str(DESTINATION).unwrap(), false, true)
+            .data(DataValue::U32List(vec![2]))
+            .data(DataValue::U64List(vec![1000000]))
+            .recent_blockhash(Sha256Digest([1_u8; 32]))
+            .build()
+            .unwrap()
+    }
+
+    // Starts an RPC node on localhost which answers each request by calling respond with the request's method, params,
+    // and HTTP headers (with lower case names).  respond returns the result or error member of the response.  The RPC
+    // node runs until the test process exits.
+    pub(crate) fn mock_rpc_node<F>(respond : F) -> RpcEndpoint
+    where
+        F : Fn(&str, &json_Value, &[(String, String)]) -> json_Value + Send + 'static
+    {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+
+                let mut headers = vec![];
+
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim_end().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+                    }
+                }
+
+                let content_length = headers
+                    .iter()
+                    .find(|(name, _)| name == "content-length")
+                    .and_then(|(_, value)| value.parse().ok())
+                    .unwrap_or(0);
+
+                let mut body = vec![0_u8; content_length];
+
+                if reader.read_exact(&mut body).is_err() {
+                    continue;
+                }
+
+                let request : json_Value = serde_json::from_slice(&body).unwrap_or(json_Value::Null);
+
+                let mut response = respond(request["method"].as_str().unwrap_or(""), &request["params"], &headers);
+
+                response["jsonrpc"] = "2.0".into();
+                response["id"] = request["id"].clone();
+
+                let response = response.to_string();
+
+                let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\n")
+                    .and_then(|_| write!(stream, "Content-Length: {}\r\n\r\n{}", response.len(), response));
+            }
+        });
+
+        RpcEndpoint::new(&url)
+    }
+
+    #[test]
+    fn expiry_files_round_trip()
+    {
+        let path = std::env::temp_dir().join(format!("solxact-rpc-test-{}-expiry.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let recent_blockhash = RecentBlockhash {
+            blockhash : "abc".to_string(),
+            last_valid_block_height : Some(100),
+            genesis_hash : Some(DEVNET_GENESIS_HASH.to_string())
+        };
+
+        write_blockhash_expiry_file(path, &recent_blockhash).unwrap();
+
+        let read = read_blockhash_expiry_file(path).unwrap();
+        assert_eq!(read.blockhash, "abc");
+        assert_eq!(read.last_valid_block_height, Some(100));
+        assert_eq!(read.genesis_hash.as_deref(), Some(DEVNET_GENESIS_HASH));
+
+        // Expiry files without a genesis hash are still accepted
+        std::fs::write(path, r#"{ "blockhash" : "abc", "last_valid_block_height" : null }"#).unwrap();
+        let read = read_blockhash_expiry_file(path).unwrap();
+        assert_eq!(read.last_valid_block_height, None);
+        assert_eq!(read.genesis_hash, None);
+
+        std::fs::write(path, r#"{ "blockhash" : "abc" }"#).unwrap();
+        assert!(read_blockhash_expiry_file(path).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    // Returns the data of an address lookup table account holding addresses, deactivated in deactivation_slot
+    fn lookup_table_data(
+        deactivation_slot : u64,
+        addresses : &[Address]
+    ) -> Vec<u8>
+    {
+        let mut data = vec![0_u8; ADDRESS_LOOKUP_TABLE_META_SIZE];
+        data[0..4].copy_from_slice(&1_u32.to_le_bytes());
+        data[ADDRESS_LOOKUP_TABLE_DEACTIVATION_SLOT_OFFSET..(ADDRESS_LOOKUP_TABLE_DEACTIVATION_SLOT_OFFSET + 8)]
+            .copy_from_slice(&deactivation_slot.to_le_bytes());
+        addresses.iter().for_each(|address| data.extend(address.0));
+        data
+    }
+
+    #[test]
+    fn only_accounts_of_the_address_lookup_table_program_are_lookup_tables()
+    {
+        let addresses = [Address([1_u8; 32]), Address([2_u8; 32])];
+
+        let mut uninitialized = lookup_table_data(u64::MAX, &addresses);
+        uninitialized[0] = 0;
+
+        let accounts = [
+            (Address([10_u8; 32]), ADDRESS_LOOKUP_TABLE_PROGRAM_ID, lookup_table_data(u64::MAX, &addresses)),
+            (Address([11_u8; 32]), crate::SYSTEM_PROGRAM_ID, lookup_table_data(u64::MAX, &addresses)),
+            (Address([12_u8; 32]), ADDRESS_LOOKUP_TABLE_PROGRAM_ID, uninitialized),
+            (Address([13_u8; 32]), ADDRESS_LOOKUP_TABLE_PROGRAM_ID, lookup_table_data(500, &addresses))
+        ];
+
+        let endpoint = {
+            let accounts = accounts
+                .iter()
+                .map(|(address, owner, data)| (format!("{}", address), owner.to_string(), data.clone()))
+                .collect::<Vec<_>>();
+            mock_rpc_node(move |method, params, _| {
+                assert_eq!(method, "getAccountInfo");
+                let (_, owner, data) = accounts.iter().find(|(address, _, _)| params[0] == **address).unwrap();
+                serde_json::json!({
+                    "result" : {
+                        "context" : { "slot" : 1 },
+                        "value" : { "owner" : owner, "data" : [base64::encode(data), "base64"] }
+                    }
+                })
+            })
+        };
+
+        assert!(fetch_lookup_table(&endpoint, &accounts[0].0).unwrap() == addresses);
+
+        for (address, _, _) in &accounts[1..3] {
+            let err = fetch_lookup_table(&endpoint, address).err().unwrap();
+            assert_eq!(err.category(), "rpc");
+            assert_eq!(err.message(), format!("Account {} is not an address lookup table", address));
+        }
+
+        let err = fetch_lookup_table(&endpoint, &accounts[3].0).err().unwrap();
+        assert_eq!(err.category(), "rpc");
+        assert_eq!(err.message(), format!("Address lookup table {} was deactivated in slot 500", accounts[3].0));
+    }
+
+    #[test]
+    fn clusters_are_given_by_name_or_genesis_hash()
+    {
+        assert_eq!(genesis_hash_from_arg("m").unwrap(), MAINNET_GENESIS_HASH);
+        assert_eq!(genesis_hash_from_arg("mainnet-beta").unwrap(), MAINNET_GENESIS_HASH);
+        assert_eq!(genesis_hash_from_arg("testnet").unwrap(), TESTNET_GENESIS_HASH);
+        assert_eq!(genesis_hash_from_arg("d").unwrap(), DEVNET_GENESIS_HASH);
+        assert_eq!(genesis_hash_from_arg(DEVNET_GENESIS_HASH).unwrap(), DEVNET_GENESIS_HASH);
+
+        let err = genesis_hash_from_arg("localnet").err().unwrap();
+        assert_eq!(err.category(), "usage");
+        assert_eq!(err.message(), "Invalid cluster: localnet");
+    }
+
+    #[test]
+    fn rpc_nodes_are_checked_against_the_genesis_hash_of_the_cluster()
+    {
+        let endpoint = mock_rpc_node(|method, _, _| {
+            assert_eq!(method, "getGenesisHash");
+            serde_json::json!({ "result" : DEVNET_GENESIS_HASH })
+        });
+
+        check_genesis_hash(&endpoint, DEVNET_GENESIS_HASH).unwrap();
+
+        let err = check_genesis_hash(&endpoint, MAINNET_GENESIS_HASH).err().unwrap();
+        assert_eq!(err.category(), "usage");
+        assert_eq!(
+            err.message(),
+            format!(
+                "RPC node {} belongs to devnet (genesis hash {}), not mainnet (genesis hash {})",
+                endpoint.url, DEVNET_GENESIS_HASH, MAINNET_GENESIS_HASH
+            )
+        );
+    }
+
+    #[test]
+    fn requests_go_to_the_first_rpc_node_then_each_other_in_turn()
+    {
+        let endpoints = ["http://a", "http://b", "http://c"].map(RpcEndpoint::new);
+
+        let tried = std::cell::RefCell::new(vec![]);
+
+        let result : Result<(), Error> = from_any_endpoint(&endpoints, 1, |endpoint| {
+            tried.borrow_mut().push(endpoint.url.clone());
+            Err(Error::rpc(&endpoint.url))
+        });
+
+        assert_eq!(*tried.borrow(), vec!["http://b", "http://a", "http://c"]);
+        assert_eq!(result.err().unwrap().message(), "http://c");
+
+        tried.borrow_mut().clear();
+
+        let result = from_any_endpoint(&endpoints, 0, |endpoint| {
+            tried.borrow_mut().push(endpoint.url.clone());
+            if endpoint.url == "http://b" {
+                Ok(1)
+            }
+            else {
+                Err(Error::rpc(&endpoint.url))
+            }
+        });
+
+        assert_eq!(*tried.borrow(), vec!["http://a", "http://b"]);
+        assert_eq!(result.unwrap(), 1);
+    }
+
+    #[test]
+    fn each_cluster_is_checked_once_against_the_rpc_node()
+    {
+        let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let endpoint = {
+            let requests = requests.clone();
+            mock_rpc_node(move |method, _, _| {
+                assert_eq!(method, "getGenesisHash");
+                requests.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                serde_json::json!({ "result" : DEVNET_GENESIS_HASH })
+            })
+        };
+
+        let devnet = Some(DEVNET_GENESIS_HASH.to_string());
+
+        check_clusters(&endpoint, &[&devnet, &None, &devnet]).unwrap();
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let err = check_clusters(&endpoint, &[&devnet, &Some(MAINNET_GENESIS_HASH.to_string())]).err().unwrap();
+        assert_eq!(err.category(), "usage");
+        assert_eq!(
+            err.message(),
+            format!(
+                "RPC node {} belongs to devnet (genesis hash {}), not mainnet (genesis hash {})",
+                endpoint.url, DEVNET_GENESIS_HASH, MAINNET_GENESIS_HASH
+            )
+        );
+    }
+
+    #[test]
+    fn applying_the_latest_blockhash_fetches_the_genesis_hash_only_when_it_is_unknown()
+    {
+        let blockhash = format!("{}", Sha256Digest([2_u8; 32]));
+
+        let mut endpoint = {
+            let blockhash = blockhash.clone();
+            mock_rpc_node(move |method, _, _| match method {
+                "getLatestBlockhash" => serde_json::json!({
+                    "result" : {
+                        "context" : { "slot" : 1 },
+                        "value" : { "blockhash" : blockhash, "lastValidBlockHeight" : 50 }
+                    }
+                }),
+                "getGenesisHash" => serde_json::json!({ "result" : TESTNET_GENESIS_HASH }),
+                _ => panic!("Unexpected method {}", method)
+            })
+        };
+
+        let mut transaction = Transaction::new(Pubkey([1_u8; 32]));
+
+        let recent_blockhash = apply_latest_blockhash(&endpoint, &mut transaction, None).unwrap();
+        assert!(transaction.recent_blockhash == Some(Sha256Digest([2_u8; 32])));
+        assert_eq!(recent_blockhash.blockhash, blockhash);
+        assert_eq!(recent_blockhash.last_valid_block_height, Some(50));
+        assert_eq!(recent_blockhash.genesis_hash.as_deref(), Some(TESTNET_GENESIS_HASH));
+
+        // A genesis hash that is already known is not fetched again
+        let recent_blockhash =
+            apply_latest_blockhash(&endpoint, &mut transaction, Some(DEVNET_GENESIS_HASH.to_string())).unwrap();
+        assert_eq!(recent_blockhash.genesis_hash.as_deref(), Some(DEVNET_GENESIS_HASH));
+
+        endpoint.genesis_hash = Some(MAINNET_GENESIS_HASH.to_string());
+        let recent_blockhash = apply_latest_blockhash(&endpoint, &mut transaction, None).unwrap();
+        assert_eq!(recent_blockhash.genesis_hash.as_deref(), Some(MAINNET_GENESIS_HASH));
+    }
+}
@@ -0,0 +1,670 @@
+use crate::data_value::{skip_comments, Encoding};
+use crate::transaction::Pubkey;
+use crate::Error;
+use bincode::Options;
+use serde_json::{Map as json_Map, Number as json_Number, Value as json_Value};
+use std::str::FromStr;
+
+// A data schema describes the layout of data written by a data value, using the same words as data values but with
+// types in place of values
+#[derive(Clone)]
+pub enum DataSchema
+{
+    Bool,
+
+    U8,
+
+    U16,
+
+    U32,
+
+    U64,
+
+    I8,
+
+    I16,
+
+    I32,
+
+    I64,
+
+    F32,
+
+    F64,
+
+    String,
+
+    CString(u16),
+
+    Pubkey,
+
+    Sha256,
+
+    Vector(Box<SchemaElement>),
+
+    Struct(Vec<SchemaElement>),
+
+    Enum
+    {
+        // If present, the enum index that the data must have
+        index : Option<usize>,
+        params : Option<Vec<SchemaElement>>
+    },
+
+    Option(Box<SchemaElement>)
+}
+
+// A schema together with the optional name that is given to its decoded value
+#[derive(Clone)]
+pub struct SchemaElement
+{
+    pub name : Option<String>,
+
+    pub schema : DataSchema
+}
+
+// Tracks the position within data being decoded; offset is needed for c alignment, which is relative to the start
+// of the data
+struct Reader<'a>
+{
+    data : &'a [u8],
+
+    offset : usize
+}
+
+fn is_data_schema_terminator(s : &str) -> bool
+{
+    matches!(s, "program" | "encoding")
+}
+
+fn read_schema_vector(
+    prefix : &str,
+    words : &mut Vec<String>
+) -> Result<Vec<SchemaElement>, Error>
+{
+    if words.is_empty() || (words[0] != "[") {
+        return Err(Error::parse(&format!("Expected [ after {}", prefix)));
+    }
+
+    words.remove(0);
+
+    let mut v = vec![];
+
+    loop {
+        skip_comments(words)?;
+
+        if words.is_empty() {
+            return Err(Error::parse(&format!("The final {} schema is incomplete", prefix)));
+        }
+
+        if words[0] == "]" {
+            words.remove(0);
+            break;
+        }
+
+        v.push(read_data_schema(words)?);
+    }
+
+    if v.is_empty() {
+        Err(Error::parse(&format!("Empty {} schema", prefix)))
+    }
+    else {
+        Ok(v)
+    }
+}
+
+// Reads a single schema, which may be prefixed by a name and a colon, i.e. lamports:u64
+pub fn read_data_schema(words : &mut Vec<String>) -> Result<SchemaElement, Error>
+{
+    let word = words.remove(0);
+
+    let (name, type_name) = match word.split_once(':') {
+        Some((name, type_name)) => (Some(name.to_string()), type_name.to_string()),
+        None => (None, word)
+    };
+
+    let schema = match type_name.as_str() {
+        "bool" => DataSchema::Bool,
+        "u8" => DataSchema::U8,
+        "u16" => DataSchema::U16,
+        "u32" => DataSchema::U32,
+        "u64" => DataSchema::U64,
+        "i8" => DataSchema::I8,
+        "i16" => DataSchema::I16,
+        "i32" => DataSchema::I32,
+        "i64" => DataSchema::I64,
+        "f32" => DataSchema::F32,
+        "f64" => DataSchema::F64,
+        "string" => DataSchema::String,
+        "c_string" => {
+            if words.is_empty() {
+                return Err(Error::parse("The final c_string schema is incomplete"));
+            }
+            let word = words.remove(0);
+            DataSchema::CString(
+                u16::from_str(&word)
+                    .map_err(|_| Error::parse(&format!("Invalid max_length in c_string schema: {}", word)))?
+            )
+        },
+        "pubkey" => DataSchema::Pubkey,
+        "sha256" => DataSchema::Sha256,
+        "vector" => {
+            // A vector of more than one schema is a vector of structs
+            let mut v = read_schema_vector("vector", words)?;
+            if v.len() == 1 {
+                DataSchema::Vector(Box::new(v.remove(0)))
+            }
+            else {
+                DataSchema::Vector(Box::new(SchemaElement { name : None, schema : DataSchema::Struct(v) }))
+            }
+        },
+        "struct" => DataSchema::Struct(read_schema_vector("struct", words)?),
+        "enum" => {
+            let index = match words.first().map(|word| usize::from_str(word)) {
+                Some(Ok(index)) => {
+                    words.remove(0);
+                    Some(index)
+                },
+                _ => None
+            };
+            let params = if words.first().map(|word| word == "[").unwrap_or(false) {
+                Some(read_schema_vector("enum", words)?)
+            }
+            else {
+                None
+            };
+            DataSchema::Enum { index, params }
+        },
+        "option" | "some" => {
+            skip_comments(words)?;
+            if words.is_empty() {
+                return Err(Error::parse(&format!("The final {} schema is incomplete", type_name)));
+            }
+            DataSchema::Option(Box::new(read_data_schema(words)?))
+        },
+        _ => return Err(Error::parse(&format!("Invalid data schema: {}", type_name)))
+    };
+
+    Ok(SchemaElement { name, schema })
+}
+
+// Reads schemas until the end of words or until a word that begins a new program or encoding
+pub fn read_data_schemas(words : &mut Vec<String>) -> Result<Vec<SchemaElement>, Error>
+{
+    let mut v = vec![];
+
+    loop {
+        skip_comments(words)?;
+
+        if words.is_empty() || is_data_schema_terminator(&words[0]) {
+            break;
+        }
+
+        v.push(read_data_schema(words)?);
+    }
+
+    Ok(v)
+}
+
+impl DataSchema
+{
+    fn type_name(&self) -> &'static str
+    {
+        match self {
+            DataSchema::Bool => "bool",
+            DataSchema::U8 => "u8",
+            DataSchema::U16 => "u16",
+            DataSchema::U32 => "u32",
+            DataSchema::U64 => "u64",
+            DataSchema::I8 => "i8",
+            DataSchema::I16 => "i16",
+            DataSchema::I32 => "i32",
+            DataSchema::I64 => "i64",
+            DataSchema::F32 => "f32",
+            DataSchema::F64 => "f64",
+            DataSchema::String => "string",
+            DataSchema::CString(_) => "c_string",
+            DataSchema::Pubkey => "pubkey",
+            DataSchema::Sha256 => "sha256",
+            DataSchema::Vector(_) => "vector",
+            DataSchema::Struct(_) => "struct",
+            DataSchema::Enum { index: _, params: _ } => "enum",
+            DataSchema::Option(_) => "option"
+        }
+    }
+
+    // Mirrors c_alignment of data values
+    fn c_alignment(&self) -> usize
+    {
+        match self {
+            DataSchema::U16 | DataSchema::I16 => 2,
+            DataSchema::U32 | DataSchema::I32 | DataSchema::F32 => 4,
+            DataSchema::U64 | DataSchema::I64 | DataSchema::F64 => 8,
+            DataSchema::Struct(v) => c_max_alignment(v),
+            DataSchema::Enum { index: _, params: Some(params) } => c_max_alignment(params),
+            DataSchema::Option(element) => element.schema.c_alignment(),
+            _ => 1
+        }
+    }
+}
+
+fn c_max_alignment(v : &[SchemaElement]) -> usize
+{
+    v.iter().map(|element| element.schema.c_alignment()).max().unwrap_or(1).max(1)
+}
+
+impl<'a> Reader<'a>
+{
+    fn read_bytes(
+        &mut self,
+        len : usize
+    ) -> Result<&'a [u8], Error>
+    {
+        if len > self.data.len() {
+            return Err(Error::decode(&format!(
+                "Data ends {} bytes short at offset {}",
+                len - self.data.len(),
+                self.offset
+            )));
+        }
+
+        let (bytes, rest) = self.data.split_at(len);
+
+        self.data = rest;
+
+        self.offset += len;
+
+        Ok(bytes)
+    }
+
+    fn c_align(
+        &mut self,
+        alignment : usize
+    ) -> Result<(), Error>
+    {
+        let padding = (alignment - (self.offset % alignment)) % alignment;
+
+        self.read_bytes(padding).map(|_| ())
+    }
+
+    // Reads a fixed size value of the given size, using bincode fixint decoding which is identical to the little
+    // endian encoding used by borsh and c for primitive values
+    fn read_primitive<T : serde::de::DeserializeOwned>(
+        &mut self,
+        size : usize,
+        encoding : &Encoding
+    ) -> Result<T, Error>
+    {
+        match encoding {
+            Encoding::RustBincodeVarInt => {
+                let before = self.data.len();
+                let v = bincode::DefaultOptions::new()
+                    .with_varint_encoding()
+                    .deserialize_from(&mut self.data)
+                    .map_err(|err| Error::decode(&format!("Invalid data at offset {}: {}", self.offset, err)))?;
+                self.offset += before - self.data.len();
+                Ok(v)
+            },
+            _ => {
+                if let Encoding::C = encoding {
+                    self.c_align(size)?;
+                }
+                let bytes = self.read_bytes(size)?;
+                bincode::DefaultOptions::new()
+                    .with_fixint_encoding()
+                    .deserialize(bytes)
+                    .map_err(|err| Error::decode(&format!("Invalid data at offset {}: {}", self.offset - size, err)))
+            }
+        }
+    }
+
+    // Reads the length prefix of a string or vector
+    fn read_length(
+        &mut self,
+        prefix : &str,
+        encoding : &Encoding
+    ) -> Result<usize, Error>
+    {
+        let len = match encoding {
+            Encoding::RustBincodeVarInt | Encoding::RustBincodeFixedInt => self.read_primitive::<u64>(8, encoding)?,
+            Encoding::RustBorsh => self.read_primitive::<u32>(4, encoding)? as u64,
+            Encoding::C => return Err(Error::decode(&format!("{} schema cannot be used with c encoding", prefix)))
+        };
+
+        // Every element occupies at least one byte, so a length beyond the remaining data can only be invalid
+        if len > (self.data.len() as u64) {
+            return Err(Error::decode(&format!("Invalid {} length {} at offset {}", prefix, len, self.offset)));
+        }
+
+        Ok(len as usize)
+    }
+
+    fn read_enum_index(
+        &mut self,
+        encoding : &Encoding
+    ) -> Result<usize, Error>
+    {
+        match encoding {
+            Encoding::RustBincodeVarInt | Encoding::RustBincodeFixedInt => {
+                Ok(self.read_primitive::<u32>(4, encoding)? as usize)
+            },
+            Encoding::RustBorsh | Encoding::C => Ok(self.read_bytes(1)?[0] as usize)
+        }
+    }
+
+    fn read_struct(
+        &mut self,
+        v : &[SchemaElement],
+        encoding : &Encoding
+    ) -> Result<Vec<json_Value>, Error>
+    {
+        let alignment = c_max_alignment(v);
+
+        if let Encoding::C = encoding {
+            self.c_align(alignment)?;
+        }
+
+        let values = v.iter().map(|element| self.read_element(element, encoding)).collect::<Result<Vec<_>, _>>()?;
+
+        if let Encoding::C = encoding {
+            self.c_align(alignment)?;
+        }
+
+        Ok(values)
+    }
+
+    fn read_value(
+        &mut self,
+        schema : &DataSchema,
+        encoding : &Encoding
+    ) -> Result<json_Value, Error>
+    {
+        Ok(match schema {
+            DataSchema::Bool => json_Value::Bool(match self.read_bytes(1)?[0] {
+                0 => false,
+                1 => true,
+                b => return Err(Error::decode(&format!("Invalid bool value {} at offset {}", b, self.offset - 1)))
+            }),
+            DataSchema::U8 => json_Value::Number(json_Number::from(self.read_bytes(1)?[0])),
+            DataSchema::U16 => json_Value::Number(json_Number::from(self.read_primitive::<u16>(2, encoding)?)),
+            DataSchema::U32 => json_Value::Number(json_Number::from(self.read_primitive::<u32>(4, encoding)?)),
+            DataSchema::U64 => json_Value::Number(json_Number::from(self.read_primitive::<u64>(8, encoding)?)),
+            DataSchema::I8 => json_Value::Number(json_Number::from(self.read_bytes(1)?[0] as i8)),
+            DataSchema::I16 => json_Value::Number(json_Number::from(self.read_primitive::<i16>(2, encoding)?)),
+            DataSchema::I32 => json_Value::Number(json_Number::from(self.read_primitive::<i32>(4, encoding)?)),
+            DataSchema::I64 => json_Value::Number(json_Number::from(self.read_primitive::<i64>(8, encoding)?)),
+            // Non-finite floats have no JSON representation
+            DataSchema::F32 => json_Number::from_f64(self.read_primitive::<f32>(4, encoding)? as f64)
+                .map(json_Value::Number)
+                .unwrap_or(json_Value::Null),
+            DataSchema::F64 => json_Number::from_f64(self.read_primitive::<f64>(8, encoding)?)
+                .map(json_Value::Number)
+                .unwrap_or(json_Value::Null),
+            DataSchema::String => {
+                let len = self.read_length("string", encoding)?;
+                let offset = self.offset;
+                json_Value::String(
+                    String::from_utf8(self.read_bytes(len)?.to_vec())
+                        .map_err(|_| Error::decode(&format!("Invalid UTF-8 string at offset {}", offset)))?
+                )
+            },
+            DataSchema::CString(max_length) => {
+                let offset = self.offset;
+                let bytes = self.read_bytes(*max_length as usize)?;
+                let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                json_Value::String(
+                    String::from_utf8(bytes[0..len].to_vec())
+                        .map_err(|_| Error::decode(&format!("Invalid UTF-8 c_string at offset {}", offset)))?
+                )
+            },
+            DataSchema::Pubkey => json_Value::String(format!(
+                "{}",
+                Pubkey(self.read_bytes(32)?.try_into().map_err(|_| Error::decode("Invalid pubkey"))?)
+            )),
+            DataSchema::Sha256 => json_Value::String(hex::encode(self.read_bytes(32)?)),
+            DataSchema::Vector(element) => {
+                let len = self.read_length("vector", encoding)?;
+                json_Value::Array(
+                    (0..len).map(|_| self.read_element(element, encoding)).collect::<Result<Vec<_>, _>>()?
+                )
+            },
+            DataSchema::Struct(v) => json_Value::Array(self.read_struct(v, encoding)?),
+            DataSchema::Enum { index, params } => {
+                let offset = self.offset;
+                let actual = self.read_enum_index(encoding)?;
+                if let Some(index) = index {
+                    if *index != actual {
+                        return Err(Error::decode(&format!(
+                            "Expected enum index {} at offset {} but found {}",
+                            index, offset, actual
+                        )));
+                    }
+                }
+                let mut map = json_Map::<String, json_Value>::new();
+                map.insert("index".to_string(), json_Value::Number(json_Number::from(actual)));
+                if let Some(params) = params {
+                    map.insert("params".to_string(), json_Value::Array(self.read_struct(params, encoding)?));
+                }
+                json_Value::Object(map)
+            },
+            DataSchema::Option(element) => {
+                let offset = self.offset;
+                match self.read_enum_index(encoding)? {
+                    0 => json_Value::Null,
+                    1 => {
+                        // c encoding writes the value of a some as an enum params struct
+                        if let Encoding::C = encoding {
+                            self.read_struct(std::slice::from_ref(element), encoding)?.remove(0)
+                        }
+                        else {
+                            self.read_element(element, encoding)?
+                        }
+                    },
+                    index => return Err(Error::decode(&format!("Invalid option index {} at offset {}", index, offset)))
+                }
+            }
+        })
+    }
+
+    // Decodes a single element into a JSON object giving its name (if any), type, and value
+    fn read_element(
+        &mut self,
+        element : &SchemaElement,
+        encoding : &Encoding
+    ) -> Result<json_Value, Error>
+    {
+        let mut map = json_Map::<String, json_Value>::new();
+
+        if let Some(name) = &element.name {
+            map.insert("name".to_string(), json_Value::String(name.clone()));
+        }
+
+        map.insert("type".to_string(), json_Value::String(element.schema.type_name().to_string()));
+
+        map.insert("value".to_string(), self.read_value(&element.schema, encoding)?);
+
+        Ok(json_Value::Object(map))
+    }
+}
+
+// Decodes data according to schemas, returning one JSON object per schema.  All of the data must be consumed.
+pub fn decode_data(
+    schemas : &[SchemaElement],
+    encoding : &Encoding,
+    data : &[u8]
+) -> Result<Vec<json_Value>, Error>
+{
+    let mut reader = Reader { data, offset : 0 };
+
+    let values = schemas.iter().map(|element| reader.read_element(element, encoding)).collect::<Result<Vec<_>, _>>()?;
+
+    if !reader.data.is_empty() {
+        return Err(Error::decode(&format!("{} bytes of data remain after decoding", reader.data.len())));
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::data_value::{make_encoding, make_words, read_data_values, write_data_value};
+
+    fn words(s : &str) -> Vec<String>
+    {
+        s.split_whitespace().flat_map(make_words).collect()
+    }
+
+    fn schemas(s : &str) -> Vec<SchemaElement>
+    {
+        read_data_schemas(&mut words(s)).unwrap()
+    }
+
+    fn encode(
+        values : &str,
+        encoding : &Encoding
+    ) -> Vec<u8>
+    {
+        let mut data_values = vec![];
+
+        read_data_values(&mut words(values), &mut data_values).unwrap();
+
+        let mut data = vec![];
+
+        for data_value in data_values {
+            write_data_value(data_value, encoding, &mut data).unwrap();
+        }
+
+        data
+    }
+
+    #[test]
+    fn decodes_what_data_values_encode_in_every_rust_encoding()
+    {
+        let values = concat!(
+            "u8 7 i16 -3 bool true string hello pubkey HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk ",
+            "vector [ u32 1 2 ] struct [ u64 9 string x ] enum 2 [ u16 5 ] some u8 4"
+        );
+
+        let schema = concat!(
+            "tag:u8 i16 bool name:string pubkey vector [ u32 ] struct [ u64 string ] enum 2 [ u16 ] ",
+            "option u8"
+        );
+
+        for encoding in ["rust_bincode_varint", "rust_bincode_fixedint", "rust_borsh"] {
+            let encoding = make_encoding(encoding).unwrap();
+
+            let decoded = decode_data(&schemas(schema), &encoding, &encode(values, &encoding)).unwrap();
+
+            assert_eq!(
+                json_Value::Array(decoded),
+                serde_json::json!([
+                    { "name" : "tag", "type" : "u8", "value" : 7 },
+                    { "type" : "i16", "value" : -3 },
+                    { "type" : "bool", "value" : true },
+                    { "name" : "name", "type" : "string", "value" : "hello" },
+                    { "type" : "pubkey", "value" : "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk" },
+                    {
+                        "type" : "vector",
+                        "value" : [ { "type" : "u32", "value" : 1 }, { "type" : "u32", "value" : 2 } ]
+                    },
+                    {
+                        "type" : "struct",
+                        "value" : [ { "type" : "u64", "value" : 9 }, { "type" : "string", "value" : "x" } ]
+                    },
+                    { "type" : "enum", "value" : { "index" : 2, "params" : [ { "type" : "u16", "value" : 5 } ] } },
+                    { "type" : "option", "value" : { "type" : "u8", "value" : 4 } }
+                ])
+            );
+        }
+    }
+
+    #[test]
+    fn decodes_c_encoded_data_with_its_alignment()
+    {
+        let data = encode("u8 7 u32 5 c_string 8 hi", &Encoding::C);
+
+        // The u32 is aligned to 4 bytes
+        assert_eq!(data.len(), 16);
+
+        let decoded = decode_data(&schemas("u8 u32 c_string 8"), &Encoding::C, &data).unwrap();
+
+        assert_eq!(
+            json_Value::Array(decoded),
+            serde_json::json!([
+                { "type" : "u8", "value" : 7 },
+                { "type" : "u32", "value" : 5 },
+                { "type" : "c_string", "value" : "hi" }
+            ])
+        );
+    }
+
+    #[test]
+    fn decodes_a_vector_of_several_schemas_as_a_vector_of_structs()
+    {
+        let encoding = Encoding::RustBorsh;
+
+        let decoded = decode_data(
+            &schemas("points:vector [ x:u8 y:u8 ]"),
+            &encoding,
+            &encode("vector [ struct [ u8 1 u8 2 ] ]", &encoding)
+        )
+        .unwrap();
+
+        assert_eq!(
+            decoded[0],
+            serde_json::json!({
+                "name" : "points",
+                "type" : "vector",
+                "value" : [ {
+                    "type" : "struct",
+                    "value" : [
+                        { "name" : "x", "type" : "u8", "value" : 1 },
+                        { "name" : "y", "type" : "u8", "value" : 2 }
+                    ]
+                } ]
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_an_absent_option_as_null()
+    {
+        let decoded = decode_data(&schemas("option u64"), &Encoding::RustBorsh, &[0]).unwrap();
+
+        assert_eq!(decoded[0], serde_json::json!({ "type" : "option", "value" : null }));
+    }
+
+    #[test]
+    fn rejects_data_that_does_not_match_the_schema()
+    {
+        let encoding = Encoding::RustBorsh;
+
+        let err = decode_data(&schemas("enum 1"), &encoding, &encode("enum 2", &encoding)).err().unwrap();
+        assert_eq!(err.to_string(), "Expected enum index 1 at offset 0 but found 2");
+
+        let err = decode_data(&schemas("u8"), &encoding, &[1, 2]).err().unwrap();
+        assert_eq!(err.to_string(), "1 bytes of data remain after decoding");
+
+        let err = decode_data(&schemas("bool"), &encoding, &[2]).err().unwrap();
+        assert_eq!(err.to_string(), "Invalid bool value 2 at offset 0");
+
+        assert!(decode_data(&schemas("u64"), &encoding, &[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn reads_schemas_up_to_the_next_program_or_encoding()
+    {
+        let mut words = words("u8 // the tag // vector [ pubkey ] program 11111111111111111111111111111111 u32");
+
+        let read = read_data_schemas(&mut words).unwrap();
+
+        assert_eq!(read.iter().map(|element| element.schema.type_name()).collect::<Vec<_>>(), vec!["u8", "vector"]);
+        assert_eq!(words, vec!["program", "11111111111111111111111111111111", "u32"]);
+
+        assert!(read_data_schemas(&mut super::tests::words("u7")).is_err());
+        assert!(read_data_schemas(&mut super::tests::words("struct [ ]")).is_err());
+        assert!(read_data_schemas(&mut super::tests::words("vector [ u8")).is_err());
+    }
+}
@@ -0,0 +1,650 @@
+use crate::rpc::{
+    commitment_reached, fetch_block_height, fetch_signature_status, fetch_transaction, from_any_endpoint, jv,
+    send_transaction_to_any, RecentBlockhash, RpcEndpoint, SendOptions
+};
+use crate::transaction::Transaction;
+use crate::Error;
+use serde_json::Value as json_Value;
+use std::fmt::Write;
+
+// How long to wait for a submitted transaction to reach the requested commitment level, by default
+pub const DEFAULT_SUBMIT_TIMEOUT_SECONDS : u64 = 120;
+
+// How often a submitted transaction that has not yet been executed is re-sent, by default
+pub const DEFAULT_REBROADCAST_INTERVAL_SECONDS : u64 = 2;
+
+// Options for waiting for a submitted transaction to be executed
+pub struct SubmitOptions
+{
+    // The commitment level that the transaction must reach
+    pub commitment : String,
+
+    // How many seconds to wait, from when the transaction is sent until it has reached the commitment level
+    pub timeout : u64,
+
+    // How often, in seconds, the transaction is re-sent until it is executed, in case it was dropped before reaching
+    // the leader; 0 never re-sends it
+    pub rebroadcast : u64,
+
+    // The last block height at which the transaction's recent blockhash is valid, if it is known.  Once the block
+    // height passes it without the transaction having been executed, the transaction has expired.
+    pub last_valid_block_height : Option<u64>,
+
+    pub send : SendOptions
+}
+
+impl Default for SubmitOptions
+{
+    fn default() -> Self
+    {
+        SubmitOptions {
+            commitment : "finalized".to_string(),
+            timeout : DEFAULT_SUBMIT_TIMEOUT_SECONDS,
+            rebroadcast : DEFAULT_REBROADCAST_INTERVAL_SECONDS,
+            last_valid_block_height : None,
+            send : SendOptions::default()
+        }
+    }
+}
+
+// The outcome of sending a transaction
+pub enum Sent
+{
+    // The transaction's signature, and the index of the RPC node that returned it
+    Signature(String, usize),
+
+    // The transaction failed preflight simulation.  This is the report of its simulated execution, giving its
+    // signature and slot as null, and its err, units_consumed, and logs.
+    PreflightFailed(json_Value)
+}
+
+// Checks that a transaction has every signature that it needs in order to be submitted
+pub fn check_signed(transaction : &Transaction) -> Result<(), Error>
+{
+    let mut needed_signatures = transaction.needed_signatures();
+
+    if let Some(needed_signature) = needed_signatures.next() {
+        let mut msg = "Transaction cannot be submitted because it is not signed by: ".to_string();
+
+        write!(msg, "{}", needed_signature)?;
+
+        for pubkey in needed_signatures {
+            write!(msg, ", {}", pubkey)?;
+        }
+
+        return Err(Error::signature(&msg));
+    }
+
+    Ok(())
+}
+
+// Checks a transaction's recent blockhash against the expiry record made when it was applied, if there is one, and
+// the cluster that the record gives against genesis_hash, the cluster that the transaction is meant for, if that is
+// known.  Returns the genesis hash of the cluster that RPC nodes must belong to and the recent blockhash's last valid
+// block height, where they are known.
+pub fn check_recent_blockhash(
+    transaction : &Transaction,
+    expiry : Option<RecentBlockhash>,
+    genesis_hash : Option<String>
+) -> Result<(Option<String>, Option<u64>), Error>
+{
+    let recent_blockhash = transaction.recent_blockhash.as_ref().map(|hash| format!("{}", hash));
+
+    match expiry {
+        Some(expiry) => {
+            if let (Some(genesis_hash), Some(expiry_genesis_hash)) = (&genesis_hash, &expiry.genesis_hash) {
+                if genesis_hash != expiry_genesis_hash {
+                    return Err(Error::usage(&format!(
+                        "The transaction's recent blockhash is from the cluster with genesis hash {}, not {}",
+                        expiry_genesis_hash, genesis_hash
+                    )));
+                }
+            }
+            // The expiry record must be the one made when the transaction's recent blockhash was applied
+            if recent_blockhash.as_deref() != Some(expiry.blockhash.as_str()) {
+                return Err(Error::usage(&format!(
+                    "The expiry file is for blockhash {}, not the transaction's recent blockhash",
+                    expiry.blockhash
+                )));
+            }
+            Ok((genesis_hash.or(expiry.genesis_hash), expiry.last_valid_block_height))
+        },
+        None => Ok((genesis_hash, None))
+    }
+}
+
+// sendTransaction responds with an error of code -32002 when the transaction fails preflight simulation, and -32003
+// when its signatures fail verification; these are rejections of the transaction rather than failures of the RPC node
+fn send_transaction_error(result_json_string : &str) -> Error
+{
+    let code = serde_json::from_str(result_json_string)
+        .ok()
+        .and_then(|v| jv(v, "error.code").ok())
+        .and_then(|code| code.as_i64());
+
+    match code {
+        Some(-32002) | Some(-32003) => Error::rejected(result_json_string),
+        _ => Error::rpc(result_json_string)
+    }
+}
+
+// Sends a transaction to every RPC node, returning the signature that the first of them to accept it returned
+pub fn send_submission(
+    endpoints : &[RpcEndpoint],
+    transaction : &Transaction,
+    options : &SendOptions
+) -> Result<Sent, Error>
+{
+    let (index, result_json) = send_transaction_to_any(endpoints, transaction, options);
+
+    let result_json = result_json?;
+
+    let result_json_string = format!("{}", result_json);
+
+    match jv(result_json, "result") {
+        Ok(json_Value::String(signature)) => Ok(Sent::Signature(signature, index)),
+        Ok(v) => Err(Error::rpc(&format!("{}", v))),
+        Err(_) => {
+            // A transaction that fails preflight simulation is described as a simulated transaction is, from the
+            // simulation result that accompanies the error
+            let preflight = serde_json::from_str(&result_json_string)
+                .ok()
+                .and_then(|v| jv(v, "error.data").ok())
+                .filter(|data| matches!(data.get("err"), Some(err) if !err.is_null()));
+            match preflight {
+                Some(data) => Ok(Sent::PreflightFailed(serde_json::json!({
+                    "signature" : json_Value::Null,
+                    "slot" : json_Value::Null,
+                    "err" : data.get("err"),
+                    "units_consumed" : data.get("unitsConsumed"),
+                    "logs" : data.get("logs")
+                }))),
+                None => Err(send_transaction_error(&result_json_string))
+            }
+        }
+    }
+}
+
+// The error of a transaction that did not reach the commitment level before the deadline.  If the RPC nodes were
+// failing to answer at the time, the last of their errors is included.
+fn timed_out(
+    signature : &str,
+    options : &SubmitOptions,
+    last_error : Option<Error>
+) -> Error
+{
+    Error::rpc(&format!(
+        "Transaction {} did not reach {} commitment within {} seconds{}",
+        signature,
+        options.commitment,
+        options.timeout,
+        last_error.map(|e| format!("; the RPC nodes last failed with: {}", e)).unwrap_or_default()
+    ))
+}
+
+// Waits until a sent transaction reaches the commitment level, returning the report of its execution, which gives its
+// signature, slot, and err.  Until the transaction is executed, it is re-sent periodically, in case it was dropped
+// before reaching the leader, and checked for expiry of its recent blockhash.
+//
+// The transaction is tracked through the RPC node at index, with the other RPC nodes used whenever that one fails to
+// answer.  If none answer, they are all tried again until the deadline, since a failure to answer is not a failure of
+// the transaction.
+pub fn wait_for_commitment(
+    endpoints : &[RpcEndpoint],
+    index : usize,
+    transaction : &Transaction,
+    signature : &str,
+    options : &SubmitOptions,
+    deadline : std::time::Instant
+) -> Result<json_Value, Error>
+{
+    let rebroadcast_options =
+        SendOptions { skip_preflight : true, preflight_commitment : None, ..options.send.clone() };
+
+    let mut last_sent = std::time::Instant::now();
+
+    let fetch_status = || from_any_endpoint(endpoints, index, |endpoint| fetch_signature_status(endpoint, signature));
+
+    let mut last_error;
+
+    let status = loop {
+        match fetch_status() {
+            Ok(status) if commitment_reached(&status, &options.commitment) => break status,
+            Ok(status) => {
+                last_error = None;
+                if status.is_null() {
+                    if let Some(last_valid_block_height) = options.last_valid_block_height {
+                        let block_height =
+                            from_any_endpoint(endpoints, index, |endpoint| fetch_block_height(endpoint, "confirmed"));
+                        // The transaction may have been executed just before the block height was fetched
+                        if let Ok(block_height) = block_height {
+                            if (block_height > last_valid_block_height) &&
+                                fetch_status().map(|status| status.is_null()).unwrap_or(false)
+                            {
+                                return Err(Error::rejected(&format!(
+                                    "Transaction {} expired: block height {} is past its last valid block height {}",
+                                    signature, block_height, last_valid_block_height
+                                )));
+                            }
+                        }
+                    }
+                    if (options.rebroadcast > 0) &&
+                        (last_sent.elapsed() >= std::time::Duration::from_secs(options.rebroadcast))
+                    {
+                        // A failure to re-send is not a failure of the transaction, which may yet be executed
+                        let _ = send_transaction_to_any(endpoints, transaction, &rebroadcast_options);
+                        last_sent = std::time::Instant::now();
+                    }
+                }
+            },
+            Err(e) => last_error = Some(e)
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err(timed_out(signature, options, last_error));
+        }
+        std::thread::sleep(std::time::Duration::from_secs(1));
+    };
+
+    Ok(serde_json::json!({
+        "signature" : signature,
+        "slot" : status.get("slot"),
+        "err" : status.get("err")
+    }))
+}
+
+// Fetches the execution of a transaction that has reached the commitment level, returning the report of it, which
+// gives its signature, slot, err, units_consumed, and logs.  Executed transactions can only be fetched once they are
+// confirmed, so with commitment processed, this waits for confirmed.
+pub fn fetch_execution_report(
+    endpoints : &[RpcEndpoint],
+    index : usize,
+    signature : &str,
+    options : &SubmitOptions,
+    deadline : std::time::Instant
+) -> Result<json_Value, Error>
+{
+    let fetch_commitment = if options.commitment == "processed" { "confirmed" } else { options.commitment.as_str() };
+
+    loop {
+        match from_any_endpoint(endpoints, index, |endpoint| fetch_transaction(endpoint, signature, fetch_commitment)) {
+            Ok(json_Value::Null) | Err(_) if std::time::Instant::now() < deadline => {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            },
+            Ok(json_Value::Null) => return Err(timed_out(signature, options, None)),
+            Err(e) => return Err(timed_out(signature, options, Some(e))),
+            Ok(result) => {
+                let meta = result.get("meta").cloned().unwrap_or(json_Value::Null);
+                return Ok(serde_json::json!({
+                    "signature" : signature,
+                    "slot" : result.get("slot"),
+                    "err" : meta.get("err"),
+                    "units_consumed" : meta.get("computeUnitsConsumed"),
+                    "logs" : meta.get("logMessages")
+                }));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::tests::{mock_rpc_node, system_transfer as transaction, FEE_PAYER};
+    use crate::rpc::{read_blockhash_record_in, write_blockhash_record_in, DEVNET_GENESIS_HASH, MAINNET_GENESIS_HASH};
+    use crate::transaction::Sha256Digest;
+
+    const SIGNATURE : &str = "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW";
+
+    fn in_seconds(seconds : u64) -> std::time::Instant
+    {
+        std::time::Instant::now() + std::time::Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn unsigned_transactions_cannot_be_submitted()
+    {
+        assert_eq!(
+            check_signed(&transaction()).err().unwrap().message(),
+            format!("Transaction cannot be submitted because it is not signed by: {}", FEE_PAYER)
+        );
+    }
+
+    #[test]
+    fn send_submission_sends_the_options_and_returns_the_signature()
+    {
+        let endpoint = mock_rpc_node(|method, params, _| {
+            assert_eq!(method, "sendTransaction");
+            assert_eq!(
+                params[1],
+                serde_json::json!({
+                    "encoding" : "base64",
+                    "skipPreflight" : true,
+                    "preflightCommitment" : "processed",
+                    "maxRetries" : 3
+                })
+            );
+            serde_json::json!({ "result" : SIGNATURE })
+        });
+
+        let options = SendOptions {
+            skip_preflight : true,
+            preflight_commitment : Some("processed".to_string()),
+            max_retries : Some(3)
+        };
+
+        match send_submission(&[endpoint], &transaction(), &options).unwrap() {
+            Sent::Signature(signature, _) => assert_eq!(signature, SIGNATURE),
+            Sent::PreflightFailed(_) => panic!("Preflight did not fail")
+        }
+    }
+
+    #[test]
+    fn send_submission_reports_preflight_failures_and_rejections()
+    {
+        let preflight = mock_rpc_node(|_, _, _| {
+            serde_json::json!({
+                "error" : {
+                    "code" : -32002,
+                    "message" : "Transaction simulation failed",
+                    "data" : { "err" : "AccountNotFound", "logs" : [], "unitsConsumed" : 0 }
+                }
+            })
+        });
+
+        match send_submission(&[preflight], &transaction(), &SendOptions::default()).unwrap() {
+            Sent::PreflightFailed(report) => {
+                assert!(report["signature"].is_null());
+                assert_eq!(report["err"], "AccountNotFound");
+                assert_eq!(report["units_consumed"], 0);
+            },
+            Sent::Signature(..) => panic!("Preflight did not fail")
+        }
+
+        let rejected = mock_rpc_node(
+            |_, _, _| serde_json::json!({ "error" : { "code" : -32003, "message" : "Signature verification failure" } })
+        );
+
+        let err = send_submission(&[rejected], &transaction(), &SendOptions::default()).err().unwrap();
+        assert_eq!(err.category(), "rejected");
+
+        let failing =
+            mock_rpc_node(|_, _, _| serde_json::json!({ "error" : { "code" : -32005, "message" : "Node is behind" } }));
+
+        let err = send_submission(&[failing], &transaction(), &SendOptions::default()).err().unwrap();
+        assert_eq!(err.category(), "rpc");
+    }
+
+    #[test]
+    fn commitment_levels_are_reached_in_order()
+    {
+        let status = |confirmation_status : &str| serde_json::json!({ "confirmationStatus" : confirmation_status });
+
+        assert!(commitment_reached(&status("confirmed"), "processed"));
+        assert!(commitment_reached(&status("confirmed"), "confirmed"));
+        assert!(!commitment_reached(&status("confirmed"), "finalized"));
+        assert!(commitment_reached(&status("finalized"), "finalized"));
+        assert!(!commitment_reached(&json_Value::Null, "processed"));
+
+        // Without a confirmation status, null confirmations means finalized
+        assert!(commitment_reached(&serde_json::json!({ "confirmations" : null }), "finalized"));
+        assert!(!commitment_reached(&serde_json::json!({ "confirmations" : 3 }), "confirmed"));
+    }
+
+    #[test]
+    fn waits_for_the_commitment_and_fetches_the_execution()
+    {
+        let endpoint = mock_rpc_node(|method, params, _| match method {
+            "getSignatureStatuses" => {
+                assert_eq!(params[0], serde_json::json!([SIGNATURE]));
+                serde_json::json!({
+                    "result" : { "value" : [ { "slot" : 77, "err" : null, "confirmationStatus" : "confirmed" } ] }
+                })
+            },
+            "getTransaction" => {
+                // Executed transactions are only fetched once confirmed
+                assert_eq!(params[1]["commitment"], "confirmed");
+                serde_json::json!({
+                    "result" : {
+                        "slot" : 77,
+                        "meta" : { "err" : null, "computeUnitsConsumed" : 150, "logMessages" : ["a"] }
+                    }
+                })
+            },
+            _ => panic!("Unexpected method {}", method)
+        });
+
+        let endpoints = [endpoint];
+
+        let options = SubmitOptions { commitment : "processed".to_string(), ..SubmitOptions::default() };
+
+        assert_eq!(
+            wait_for_commitment(&endpoints, 0, &transaction(), SIGNATURE, &options, in_seconds(10)).unwrap(),
+            serde_json::json!({ "signature" : SIGNATURE, "slot" : 77, "err" : null })
+        );
+
+        assert_eq!(
+            fetch_execution_report(&endpoints, 0, SIGNATURE, &options, in_seconds(10)).unwrap(),
+            serde_json::json!({
+                "signature" : SIGNATURE,
+                "slot" : 77,
+                "err" : null,
+                "units_consumed" : 150,
+                "logs" : ["a"]
+            })
+        );
+    }
+
+    #[test]
+    fn transactions_expire_once_the_block_height_passes_their_last_valid_block_height()
+    {
+        let endpoint = mock_rpc_node(|method, _, _| match method {
+            "getSignatureStatuses" => serde_json::json!({ "result" : { "value" : [null] } }),
+            "getBlockHeight" => serde_json::json!({ "result" : 101 }),
+            _ => panic!("Unexpected method {}", method)
+        });
+
+        let options =
+            SubmitOptions { last_valid_block_height : Some(100), rebroadcast : 0, ..SubmitOptions::default() };
+
+        let err =
+            wait_for_commitment(&[endpoint], 0, &transaction(), SIGNATURE, &options, in_seconds(10)).err().unwrap();
+
+        assert_eq!(err.category(), "rejected");
+        assert_eq!(
+            err.message(),
+            format!("Transaction {} expired: block height 101 is past its last valid block height 100", SIGNATURE)
+        );
+    }
+
+    #[test]
+    fn unexecuted_transactions_are_rebroadcast_until_executed()
+    {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+
+        let endpoint = {
+            let requests = requests.clone();
+            mock_rpc_node(move |method, params, _| {
+                let mut requests = requests.lock().unwrap();
+                requests.push((method.to_string(), params.clone()));
+                let polls = requests.iter().filter(|(method, _)| method == "getSignatureStatuses").count();
+                match method {
+                    "getSignatureStatuses" if polls < 3 => serde_json::json!({ "result" : { "value" : [null] } }),
+                    "getSignatureStatuses" => serde_json::json!({
+                        "result" : { "value" : [ { "slot" : 5, "err" : null, "confirmationStatus" : "finalized" } ] }
+                    }),
+                    "getBlockHeight" => serde_json::json!({ "result" : 10 }),
+                    "sendTransaction" => serde_json::json!({ "result" : SIGNATURE }),
+                    _ => panic!("Unexpected method {}", method)
+                }
+            })
+        };
+
+        let options =
+            SubmitOptions { last_valid_block_height : Some(100), rebroadcast : 1, ..SubmitOptions::default() };
+
+        let report = wait_for_commitment(&[endpoint], 0, &transaction(), SIGNATURE, &options, in_seconds(10)).unwrap();
+        assert_eq!(report["slot"], 5);
+
+        // Rebroadcasts skip preflight, since the transaction already passed it when first sent
+        let requests = requests.lock().unwrap();
+        let sent = requests.iter().filter(|(method, _)| method == "sendTransaction").collect::<Vec<_>>();
+        assert!(!sent.is_empty());
+        assert!(sent.iter().all(|(_, params)| params[1]["skipPreflight"] == true));
+    }
+
+    #[test]
+    fn waiting_times_out_at_the_deadline()
+    {
+        let endpoint = mock_rpc_node(|_, _, _| serde_json::json!({ "result" : { "value" : [null] } }));
+
+        let options = SubmitOptions { timeout : 0, rebroadcast : 0, ..SubmitOptions::default() };
+
+        let err = wait_for_commitment(&[endpoint], 0, &transaction(), SIGNATURE, &options, std::time::Instant::now())
+            .err()
+            .unwrap();
+
+        assert_eq!(err.category(), "rpc");
+        assert_eq!(
+            err.message(),
+            format!("Transaction {} did not reach finalized commitment within 0 seconds", SIGNATURE)
+        );
+    }
+
+    // An RPC node that cannot be connected to
+    fn unreachable_rpc_node() -> RpcEndpoint
+    {
+        RpcEndpoint::new("http://127.0.0.1:1")
+    }
+
+    #[test]
+    fn send_submission_returns_the_signature_from_any_rpc_node()
+    {
+        let preflight = || {
+            mock_rpc_node(|_, _, _| {
+                serde_json::json!({
+                    "error" : {
+                        "code" : -32002,
+                        "message" : "Transaction simulation failed",
+                        "data" : { "err" : "BlockhashNotFound", "logs" : [] }
+                    }
+                })
+            })
+        };
+
+        let accepting = mock_rpc_node(|_, _, _| serde_json::json!({ "result" : SIGNATURE }));
+
+        let endpoints = [unreachable_rpc_node(), preflight(), accepting];
+
+        match send_submission(&endpoints, &transaction(), &SendOptions::default()).unwrap() {
+            Sent::Signature(signature, index) => {
+                assert_eq!(signature, SIGNATURE);
+                assert_eq!(index, 2);
+            },
+            Sent::PreflightFailed(_) => panic!("An RPC node accepted the transaction")
+        }
+
+        // Without a signature, a rejection of the transaction is reported in preference to a failing RPC node
+        match send_submission(&[unreachable_rpc_node(), preflight()], &transaction(), &SendOptions::default()).unwrap()
+        {
+            Sent::PreflightFailed(report) => assert_eq!(report["err"], "BlockhashNotFound"),
+            Sent::Signature(..) => panic!("No RPC node accepted the transaction")
+        }
+
+        let err = send_submission(&[unreachable_rpc_node()], &transaction(), &SendOptions::default()).err().unwrap();
+        assert_eq!(err.category(), "rpc");
+    }
+
+    #[test]
+    fn waiting_falls_back_to_other_rpc_nodes_when_the_tracking_rpc_node_fails()
+    {
+        let endpoint = mock_rpc_node(|method, _, _| match method {
+            "getSignatureStatuses" => serde_json::json!({
+                "result" : { "value" : [ { "slot" : 9, "err" : null, "confirmationStatus" : "finalized" } ] }
+            }),
+            _ => panic!("Unexpected method {}", method)
+        });
+
+        let options = SubmitOptions { rebroadcast : 0, ..SubmitOptions::default() };
+
+        assert_eq!(
+            wait_for_commitment(
+                &[unreachable_rpc_node(), endpoint],
+                0,
+                &transaction(),
+                SIGNATURE,
+                &options,
+                in_seconds(10)
+            )
+            .unwrap(),
+            serde_json::json!({ "signature" : SIGNATURE, "slot" : 9, "err" : null })
+        );
+    }
+
+    fn expiry(
+        blockhash : &Sha256Digest,
+        genesis_hash : &str
+    ) -> RecentBlockhash
+    {
+        RecentBlockhash {
+            blockhash : format!("{}", blockhash),
+            last_valid_block_height : Some(100),
+            genesis_hash : Some(genesis_hash.to_string())
+        }
+    }
+
+    #[test]
+    fn recent_blockhashes_are_checked_against_their_expiry_and_cluster()
+    {
+        let transaction = transaction();
+
+        let devnet = Some(DEVNET_GENESIS_HASH.to_string());
+
+        assert_eq!(
+            check_recent_blockhash(&transaction, Some(expiry(&Sha256Digest([1_u8; 32]), DEVNET_GENESIS_HASH)), None)
+                .unwrap(),
+            (devnet.clone(), Some(100))
+        );
+
+        let err = check_recent_blockhash(
+            &transaction,
+            Some(expiry(&Sha256Digest([1_u8; 32]), DEVNET_GENESIS_HASH)),
+            Some(MAINNET_GENESIS_HASH.to_string())
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.category(), "usage");
+        assert_eq!(
+            err.message(),
+            format!(
+                "The transaction's recent blockhash is from the cluster with genesis hash {}, not {}",
+                DEVNET_GENESIS_HASH, MAINNET_GENESIS_HASH
+            )
+        );
+
+        let other = Sha256Digest([2_u8; 32]);
+        let err = check_recent_blockhash(&transaction, Some(expiry(&other, DEVNET_GENESIS_HASH)), devnet.clone())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.message(),
+            format!("The expiry file is for blockhash {}, not the transaction's recent blockhash", other)
+        );
+
+        assert_eq!(check_recent_blockhash(&transaction, None, devnet.clone()).unwrap(), (devnet.clone(), None));
+
+        // The record kept when the recent blockhash was applied may be used in place of an expiry file
+        let dir = std::env::temp_dir().join(format!("solxact-submit-test-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+
+        let blockhash = format!("{}", Sha256Digest([1_u8; 32]));
+        assert!(read_blockhash_record_in(dir, &blockhash).is_none());
+
+        write_blockhash_record_in(dir, &expiry(&Sha256Digest([1_u8; 32]), DEVNET_GENESIS_HASH)).unwrap();
+        let record = read_blockhash_record_in(dir, &blockhash);
+        assert!(record.is_some());
+        assert_eq!(check_recent_blockhash(&transaction, record, None).unwrap(), (devnet, Some(100)));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
-use crate::{stre, Error};
+use crate::Error;
 use serde_json::{Map as json_Map, Number as json_Number, Value as json_Value};
+use std::str::FromStr;
 
 // This comes from solana validator code base, which requires all transactions to fit inside an IPV4 UDP packet
 // minus some overhead
@@ -26,6 +27,20 @@ pub struct Address(pub [u8; 32]);
 #[derive(Clone, PartialEq)]
 pub struct Pubkey(pub [u8; 32]);
 
+// An address referenced by an instruction.  Static addresses are stored directly in the transaction, whereas lookup
+// addresses are loaded at execution time from the given index of an address lookup table.
+#[derive(Clone, PartialEq)]
+pub enum InstructionAddress
+{
+    Static(Address),
+
+    Lookup
+    {
+        table : Address,
+        index : u8
+    }
+}
+
 #[derive(Clone)]
 pub struct PubkeyWithSignature
 {
@@ -34,11 +49,35 @@ pub struct PubkeyWithSignature
     pub signature : Option<ed25519_dalek::Signature>
 }
 
+// The state of a signature that a transaction requires, as found by verifying it against the transaction's message
+#[derive(Clone, Copy, PartialEq)]
+pub enum SignatureStatus
+{
+    Valid,
+
+    Invalid,
+
+    Missing
+}
+
 #[derive(PartialEq, Clone)]
 pub struct Sha256Digest(pub [u8; 32]);
 
+#[derive(Clone)]
+pub struct AddressTableLookup
+{
+    pub table : Address,
+
+    pub writable_indexes : Vec<u8>,
+
+    pub readonly_indexes : Vec<u8>
+}
+
 pub struct Transaction
 {
+    // None for legacy transactions, Some(version) for versioned transactions
+    pub version : Option<u8>,
+
     pub signed_read_write_addresses : Vec<PubkeyWithSignature>,
 
     pub signed_read_only_addresses : Vec<PubkeyWithSignature>,
@@ -49,7 +88,10 @@ pub struct Transaction
 
     pub recent_blockhash : Option<Sha256Digest>,
 
-    pub instructions : Vec<Instruction>
+    pub instructions : Vec<Instruction>,
+
+    // Only versioned transactions may load addresses from address lookup tables
+    pub address_table_lookups : Vec<AddressTableLookup>
 }
 
 pub struct Instruction
@@ -57,7 +99,7 @@ pub struct Instruction
     pub program_address : Address,
 
     // (address, is_signed, is_read_write)
-    pub addresses : Vec<(Address, bool, bool)>,
+    pub addresses : Vec<(InstructionAddress, bool, bool)>,
 
     pub data : Vec<u8>
 }
@@ -66,17 +108,22 @@ const EMPTY_RECENT_BLOCKHASH : Sha256Digest = Sha256Digest([0_u8; 32]);
 
 const EMPTY_SIGNATURE_BYTES : [u8; 64] = [0_u8; 64];
 
+// The high bit of the first message byte is set for versioned messages, with the remaining bits giving the version
+const MESSAGE_VERSION_PREFIX : u8 = 0x80;
+
 impl Transaction
 {
     pub fn new(fee_payer : Pubkey) -> Self
     {
         Transaction {
+            version : None,
             signed_read_write_addresses : vec![PubkeyWithSignature { pubkey : fee_payer, signature : None }],
             signed_read_only_addresses : vec![],
             unsigned_read_write_addresses : vec![],
             unsigned_read_only_addresses : vec![],
             recent_blockhash : None,
-            instructions : vec![]
+            instructions : vec![],
+            address_table_lookups : vec![]
         }
     }
 
@@ -84,19 +131,46 @@ impl Transaction
         &mut self,
         instruction : Instruction
     )
+    {
+        self.add_instruction_addresses(&instruction);
+
+        self.instructions.push(instruction);
+    }
+
+    // Inserts an instruction at the given position within the instruction list.  All signatures of the transaction
+    // will be cleared since they are no longer valid as the message contents have changed.
+    pub fn insert_instruction(
+        &mut self,
+        index : usize,
+        instruction : Instruction
+    )
+    {
+        self.add_instruction_addresses(&instruction);
+
+        self.instructions.insert(index, instruction);
+
+        self.clear_signatures();
+    }
+
+    fn add_instruction_addresses(
+        &mut self,
+        instruction : &Instruction
+    )
     {
         self.add_address(&instruction.program_address, false);
 
-        instruction.addresses.iter().for_each(|(address, is_signed, is_read_write)| {
-            if *is_signed {
-                self.add_signature(&Pubkey(address.0), *is_read_write);
-            }
-            else {
-                self.add_address(&address, *is_read_write);
-            }
+        instruction.addresses.iter().for_each(|(address, is_signed, is_read_write)| match address {
+            InstructionAddress::Static(address) => {
+                if *is_signed {
+                    self.add_signature(&Pubkey(address.0), *is_read_write);
+                }
+                else {
+                    self.add_address(&address, *is_read_write);
+                }
+            },
+            // Addresses loaded from address lookup tables can never be signers
+            InstructionAddress::Lookup { table, index } => self.add_lookup_address(table, *index, *is_read_write)
         });
-
-        self.instructions.push(instruction);
     }
 
     pub fn decode(r : &mut dyn std::io::Read) -> Result<Self, Error>
@@ -105,7 +179,7 @@ impl Transaction
 
         // Can't provide more signatures than allowed
         if signatures_count > (MAXIMUM_ED25519_SIGNATURES_COUNT as u16) {
-            return Err(stre(&format!(
+            return Err(Error::decode(&format!(
                 "Too many signatures in transaction: expected at most {}, got {}",
                 MAXIMUM_ED25519_SIGNATURES_COUNT, signatures_count
             )));
@@ -121,16 +195,29 @@ impl Transaction
                 None
             }
             else {
-                Some(ed25519_dalek::Signature::from_bytes(&buf).map_err(|e| format!("{}", e))?)
+                Some(ed25519_dalek::Signature::from_bytes(&buf).map_err(|e| Error::decode(&e.to_string()))?)
             });
         }
 
-        Self::read(r, &mut buf[0..3])?;
+        Self::read(r, &mut buf[0..1])?;
+
+        let version = if (buf[0] & MESSAGE_VERSION_PREFIX) == MESSAGE_VERSION_PREFIX {
+            let version = buf[0] & !MESSAGE_VERSION_PREFIX;
+            if version != 0 {
+                return Err(Error::decode(&format!("Unsupported transaction version {}", version)));
+            }
+            Self::read(r, &mut buf[0..3])?;
+            Some(version)
+        }
+        else {
+            Self::read(r, &mut buf[1..3])?;
+            None
+        };
 
         let total_signed_address_count = buf[0] as u16;
 
         if total_signed_address_count > (MAXIMUM_ADDRESSES_COUNT as u16) {
-            return Err(stre(&format!(
+            return Err(Error::decode(&format!(
                 "Too many signatures supplied: expected at most {}, got {}",
                 total_signed_address_count, MAXIMUM_ADDRESSES_COUNT
             )));
@@ -140,7 +227,7 @@ impl Transaction
         // not provided.  Other implementations may instead produce a short signatures list, which can only be
         // signatures in order, with unsupplied signatures being zero.
         if signatures_count > total_signed_address_count {
-            return Err(stre(&format!(
+            return Err(Error::decode(&format!(
                 "Too many signatures supplied: expected at most {}, got {}",
                 total_signed_address_count, signatures_count
             )));
@@ -149,7 +236,7 @@ impl Transaction
         let signed_read_only_address_count = buf[1] as u16;
 
         if signed_read_only_address_count > total_signed_address_count {
-            return Err(stre(&format!(
+            return Err(Error::decode(&format!(
                 "Too many signed read only addresses: expected at most {}, got {}",
                 total_signed_address_count, signed_read_only_address_count
             )));
@@ -158,7 +245,7 @@ impl Transaction
         let signed_read_write_address_count = total_signed_address_count - signed_read_only_address_count;
 
         if signed_read_write_address_count == 0 {
-            return Err(stre("Minimum signed address count of 1 required for fee payer"));
+            return Err(Error::decode("Minimum signed address count of 1 required for fee payer"));
         }
 
         let unsigned_read_only_address_count = buf[2] as u16;
@@ -168,7 +255,7 @@ impl Transaction
         let actual_address_count = Self::decode_compact_u16(r)?;
 
         if actual_address_count < minimum_address_count {
-            return Err(stre(&format!(
+            return Err(Error::decode(&format!(
                 "Too few addresses in header; {} supplied but at least {} required",
                 actual_address_count, minimum_address_count
             )));
@@ -177,12 +264,14 @@ impl Transaction
         let unsigned_read_write_address_count = actual_address_count - minimum_address_count;
 
         let mut ret = Transaction {
+            version,
             signed_read_write_addresses : vec![],
             signed_read_only_addresses : vec![],
             unsigned_read_write_addresses : vec![],
             unsigned_read_only_addresses : vec![],
             recent_blockhash : None,
-            instructions : vec![]
+            instructions : vec![],
+            address_table_lookups : vec![]
         };
 
         let mut signatures_iter = signatures.into_iter();
@@ -207,37 +296,34 @@ impl Transaction
 
         let instruction_count = Self::decode_compact_u16(r)?;
 
+        // Instructions may reference addresses loaded from address table lookups, which are not decoded until after
+        // the instructions, so the address indices of the instructions are resolved after all are read
+        // (program address index, address indices, data)
+        let mut encoded_instructions = Vec::<(u8, Vec<u8>, Vec<u8>)>::new();
+
         for i in 0..instruction_count {
             let i = i as usize;
             Self::read(r, &mut buf[0..1])?;
 
-            let program_address = ret
-                .find_address_at_index(buf[0])
-                .ok_or(format!("Invalid program id index {} for instruction {}", buf[0], i))?;
+            let program_address_index = buf[0];
 
             let addresses_count = Self::decode_compact_u16(r)?;
 
             if addresses_count > MAXIMUM_INSTRUCTION_ADDRESS_INDEX_COUNT {
-                return Err(stre(&format!(
+                return Err(Error::decode(&format!(
                     "Too many addresses in instruction {}: expected at most {} got {}",
                     i, MAXIMUM_INSTRUCTION_ADDRESS_INDEX_COUNT, addresses_count
                 )));
             }
 
-            let mut addresses = Vec::<(Address, bool, bool)>::new();
+            let mut address_indices = vec![0_u8; addresses_count as usize];
 
-            for _ in 0..addresses_count {
-                Self::read(r, &mut buf[0..1])?;
-                addresses.push(
-                    ret.find_address_at_index(buf[0])
-                        .ok_or(format!("Invalid address index {} referenced from instruction {}", buf[0], i))?
-                );
-            }
+            Self::read(r, &mut address_indices)?;
 
             let data_count = Self::decode_compact_u16(r)?;
 
             if data_count > MAXIMUM_INSTRUCTION_DATA_COUNT {
-                return Err(stre(&format!(
+                return Err(Error::decode(&format!(
                     "Too many data bytes in instruction {}: expected at most {} got {}",
                     i, MAXIMUM_INSTRUCTION_DATA_COUNT, data_count
                 )));
@@ -247,6 +333,34 @@ impl Transaction
 
             Self::read(r, &mut data)?;
 
+            encoded_instructions.push((program_address_index, address_indices, data));
+        }
+
+        if ret.version.is_some() {
+            let lookups_count = Self::decode_compact_u16(r)?;
+
+            for _ in 0..lookups_count {
+                let table = Self::decode_address(r)?;
+                let writable_indexes = Self::decode_u8_array(r)?;
+                let readonly_indexes = Self::decode_u8_array(r)?;
+                ret.address_table_lookups.push(AddressTableLookup { table, writable_indexes, readonly_indexes });
+            }
+        }
+
+        for (i, (program_address_index, address_indices, data)) in encoded_instructions.into_iter().enumerate() {
+            // Program addresses cannot be loaded from address lookup tables
+            let program_address = ret.find_address_at_index(program_address_index).ok_or_else(|| {
+                Error::decode(&format!("Invalid program id index {} for instruction {}", program_address_index, i))
+            })?;
+
+            let mut addresses = Vec::<(InstructionAddress, bool, bool)>::new();
+
+            for index in address_indices {
+                addresses.push(ret.find_instruction_address_at_index(index).ok_or_else(|| {
+                    Error::decode(&format!("Invalid address index {} referenced from instruction {}", index, i))
+                })?);
+            }
+
             ret.instructions.push(Instruction { program_address : program_address.0, addresses, data });
         }
 
@@ -259,12 +373,21 @@ impl Transaction
         w : &mut dyn std::io::Write
     ) -> Result<(), Error>
     {
+        match self.version {
+            Some(version) => Self::write(w, &[MESSAGE_VERSION_PREFIX | version])?,
+            None => {
+                if !self.address_table_lookups.is_empty() {
+                    return Err(Error::encode("Legacy transactions cannot include address table lookups"));
+                }
+            },
+        }
+
         u8::try_from(self.signed_read_write_addresses.len() + self.signed_read_only_addresses.len())
-            .or(Err(stre("Too many signed addresses")))
+            .or(Err(Error::encode("Too many signed addresses")))
             .and_then(|u| Self::write(w, &[u]))?;
 
         u8::try_from(self.signed_read_only_addresses.len())
-            .or(Err(stre("Too many read only addresses")))
+            .or(Err(Error::encode("Too many read only addresses")))
             .and_then(|u| Self::write(w, &[u]))?;
 
         Self::write(w, &[self.unsigned_read_only_addresses.len() as u8])?;
@@ -272,7 +395,7 @@ impl Transaction
         let recent_blockhash = self.recent_blockhash.as_ref().unwrap_or(&EMPTY_RECENT_BLOCKHASH);
 
         if self.instructions.len() > (u16::MAX as usize) {
-            return Err(stre("Too many instructions"));
+            return Err(Error::encode("Too many instructions"));
         }
 
         // compact-array of account addresses
@@ -306,10 +429,12 @@ impl Transaction
             // instruction program_id index
             Self::write(
                 w,
-                std::slice::from_ref(&self.find_address_index(&instruction.program_address).ok_or(format!(
-                    "Invalid Transaction - program address {} not in address list",
-                    instruction.program_address
-                ))?)
+                std::slice::from_ref(&self.find_address_index(&instruction.program_address).ok_or_else(|| {
+                    Error::encode(&format!(
+                        "Invalid Transaction - program address {} not in address list",
+                        instruction.program_address
+                    ))
+                })?)
             )?;
 
             // instruction address indices
@@ -317,18 +442,16 @@ impl Transaction
             for a in &instruction.addresses {
                 Self::write(
                     w,
-                    std::slice::from_ref(
-                        &self
-                            .find_address_index(&a.0)
-                            .ok_or(format!("Invalid Transaction - address {} is not in address list", a.0))?
-                    )
+                    std::slice::from_ref(&self.find_instruction_address_index(&a.0).ok_or_else(|| {
+                        Error::encode(&format!("Invalid Transaction - address {} is not in address list", a.0))
+                    })?)
                 )?;
             }
 
             // instruction data
             let data_len = instruction.data.len();
             if data_len > (MAXIMUM_INSTRUCTION_DATA_COUNT as usize) {
-                return Err(stre(&format!(
+                return Err(Error::encode(&format!(
                     "Instruction data len too long: {} > {}",
                     data_len, MAXIMUM_INSTRUCTION_DATA_COUNT
                 )));
@@ -336,6 +459,20 @@ impl Transaction
             Self::encode_compact_u16(data_len as u16, w)?;
             Self::write(w, instruction.data.as_slice())?;
         }
+
+        // address table lookups
+        if self.version.is_some() {
+            if self.address_table_lookups.len() > (u16::MAX as usize) {
+                return Err(Error::encode("Too many address table lookups"));
+            }
+            Self::encode_compact_u16(self.address_table_lookups.len() as u16, w)?;
+            for lookup in &self.address_table_lookups {
+                Self::write(w, &lookup.table.0)?;
+                Self::encode_u8_array(&lookup.writable_indexes, w)?;
+                Self::encode_u8_array(&lookup.readonly_indexes, w)?;
+            }
+        }
+
         Ok(())
     }
 
@@ -370,6 +507,41 @@ impl Transaction
         v.into_iter()
     }
 
+    // Returns the addresses that the transaction writes to, other than those loaded from address lookup tables
+    pub fn static_writable_addresses(&self) -> Vec<Address>
+    {
+        self.signed_read_write_addresses
+            .iter()
+            .map(|a| a.pubkey.clone().into())
+            .chain(self.unsigned_read_write_addresses.iter().cloned())
+            .collect()
+    }
+
+    // Returns the writable addresses that the transaction loads from address lookup tables, in the order in which the
+    // runtime loads them, given the contents of each table as (table address, table contents)
+    pub fn loaded_writable_addresses(
+        &self,
+        tables : &[(Address, Vec<Address>)]
+    ) -> Result<Vec<Address>, Error>
+    {
+        let mut addresses = vec![];
+
+        for lookup in &self.address_table_lookups {
+            let contents =
+                tables.iter().find(|(table, _)| *table == lookup.table).map(|(_, contents)| contents).ok_or_else(
+                    || Error::encode(&format!("Missing contents of address lookup table {}", lookup.table))
+                )?;
+
+            for index in &lookup.writable_indexes {
+                addresses.push(contents.get(*index as usize).cloned().ok_or_else(|| {
+                    Error::encode(&format!("Address lookup table {} has no address at index {}", lookup.table, index))
+                })?);
+            }
+        }
+
+        Ok(addresses)
+    }
+
     // Adds a signature to the transaction, which adds the pubkey that is signed to the signed address list.
     pub fn add_signature(
         &mut self,
@@ -462,6 +634,112 @@ impl Transaction
         }
     }
 
+    // Adds an address to be loaded from an address lookup table, which adds the table to the address table lookups
+    // list if it is not already present.  Because only versioned transactions may use address table lookups, a legacy
+    // transaction is converted to a version 0 transaction.
+    pub fn add_lookup_address(
+        &mut self,
+        table : &Address,
+        index : u8,
+        is_read_write : bool
+    )
+    {
+        if self.version.is_none() {
+            self.version = Some(0);
+        }
+
+        let lookup = match self.address_table_lookups.iter().position(|l| l.table == *table) {
+            Some(pos) => &mut self.address_table_lookups[pos],
+            None => {
+                self.address_table_lookups.push(AddressTableLookup {
+                    table : table.clone(),
+                    writable_indexes : vec![],
+                    readonly_indexes : vec![]
+                });
+                self.address_table_lookups.last_mut().unwrap()
+            }
+        };
+
+        if is_read_write {
+            if lookup.writable_indexes.contains(&index) {
+                // Already exists as writable
+            }
+            else {
+                if let Some(pos) = lookup.readonly_indexes.iter().position(|i| *i == index) {
+                    // Promote to writable
+                    lookup.readonly_indexes.remove(pos);
+                }
+                lookup.writable_indexes.push(index);
+            }
+        }
+        else if lookup.writable_indexes.contains(&index) || lookup.readonly_indexes.contains(&index) {
+            // Already exists as writable, which supercedes read-only, or as read-only
+        }
+        else {
+            lookup.readonly_indexes.push(index);
+        }
+    }
+
+    // Converts the transaction into a version 0 transaction which loads addresses from the given address lookup
+    // tables, each supplied as (table address, table contents).  Every unsigned address which is found in a table is
+    // removed from the static address list and is instead loaded from the first table that contains it.  Signed
+    // addresses and program addresses cannot be loaded from address lookup tables and so always remain static.
+    pub fn apply_address_lookup_tables(
+        &mut self,
+        tables : &[(Address, Vec<Address>)]
+    )
+    {
+        self.version = Some(0);
+
+        // Addresses which must remain static no matter what tables they appear in
+        let static_addresses : Vec<Address> = self
+            .instructions
+            .iter()
+            .map(|i| i.program_address.clone())
+            .chain(
+                self.signed_read_write_addresses
+                    .iter()
+                    .chain(&self.signed_read_only_addresses)
+                    .map(|s| s.pubkey.clone().into())
+            )
+            .collect();
+
+        // Returns (table, index) of the address within the first table that contains it
+        let find_in_tables = |address : &Address| -> Option<(Address, u8)> {
+            if static_addresses.contains(address) {
+                return None;
+            }
+            tables.iter().find_map(|(table, addresses)| {
+                addresses
+                    .iter()
+                    .position(|a| a == address)
+                    .and_then(|index| u8::try_from(index).ok())
+                    .map(|index| (table.clone(), index))
+            })
+        };
+
+        // (table, index, is_read_write) of each address to load from an address lookup table
+        let mut lookup_addresses = vec![];
+
+        for instruction in &mut self.instructions {
+            for (address, _, is_read_write) in &mut instruction.addresses {
+                if let InstructionAddress::Static(static_address) = address {
+                    if let Some((table, index)) = find_in_tables(static_address) {
+                        lookup_addresses.push((table.clone(), index, *is_read_write));
+                        *address = InstructionAddress::Lookup { table, index };
+                    }
+                }
+            }
+        }
+
+        self.unsigned_read_write_addresses.retain(|a| find_in_tables(a).is_none());
+        self.unsigned_read_only_addresses.retain(|a| find_in_tables(a).is_none());
+
+        for (table, index, is_read_write) in lookup_addresses {
+            self.add_lookup_address(&table, index, is_read_write);
+        }
+    }
+
     // Set the recent_blockhash of the transaction.  If it is different than the current recent_blockhash of this
     // Transaction, all signatures of the transaction will be cleared since they are no longer valid as the message
     // contents have changed.
@@ -474,11 +752,16 @@ impl Transaction
 
         if recent_blockhash != self.recent_blockhash {
             self.recent_blockhash = recent_blockhash;
-            self.signed_read_write_addresses.iter_mut().for_each(|s| s.signature = None);
-            self.signed_read_only_addresses.iter_mut().for_each(|s| s.signature = None);
+            self.clear_signatures();
         }
     }
 
+    pub fn clear_signatures(&mut self)
+    {
+        self.signed_read_write_addresses.iter_mut().for_each(|s| s.signature = None);
+        self.signed_read_only_addresses.iter_mut().for_each(|s| s.signature = None);
+    }
+
     pub fn sign(
         &mut self,
         pubkey : &Pubkey,
@@ -500,6 +783,124 @@ impl Transaction
         Ok(())
     }
 
+    // Signs the transaction with each of the keypairs.  A keypair that is not a signer of the transaction adds no
+    // signature.
+    pub fn sign_with_keypairs(
+        &mut self,
+        keypairs : &[ed25519_dalek::Keypair]
+    ) -> Result<(), Error>
+    {
+        use ed25519_dalek::Signer;
+
+        let mut message = vec![];
+
+        self.message(&mut message)?;
+
+        for keypair in keypairs {
+            self.sign(&Pubkey(keypair.public.to_bytes()), keypair.sign(&message))?;
+        }
+
+        Ok(())
+    }
+
+    // Adds a signature that was made elsewhere, after checking that pubkey is a signer of the transaction and that the
+    // signature is valid for the transaction's message
+    pub fn attach_signature(
+        &mut self,
+        pubkey : &Pubkey,
+        signature : ed25519_dalek::Signature
+    ) -> Result<(), Error>
+    {
+        if !self.signed_read_write_addresses.iter().chain(&self.signed_read_only_addresses).any(|s| s.pubkey == *pubkey)
+        {
+            return Err(Error::signature(&format!("{} is not a signer of the transaction", pubkey)));
+        }
+
+        let mut message = vec![];
+
+        self.message(&mut message)?;
+
+        ed25519_dalek::PublicKey::from_bytes(&pubkey.0)
+            .and_then(|public_key| public_key.verify_strict(&message, &signature))
+            .map_err(|_| Error::signature(&format!("Signature for {} is not valid for the transaction", pubkey)))?;
+
+        self.sign(pubkey, signature)
+    }
+
+    // Verifies each signature that the transaction requires, in the order of its signers
+    pub fn verify_signatures(&self) -> Result<Vec<(Pubkey, SignatureStatus)>, Error>
+    {
+        let mut message = vec![];
+
+        self.message(&mut message)?;
+
+        Ok(self
+            .signed_read_write_addresses
+            .iter()
+            .chain(&self.signed_read_only_addresses)
+            .map(|s| {
+                let status = match &s.signature {
+                    // Use the same strict verification as the Solana runtime does
+                    Some(signature) => {
+                        if ed25519_dalek::PublicKey::from_bytes(&s.pubkey.0)
+                            .and_then(|public_key| public_key.verify_strict(&message, signature))
+                            .is_ok()
+                        {
+                            SignatureStatus::Valid
+                        }
+                        else {
+                            SignatureStatus::Invalid
+                        }
+                    },
+                    None => SignatureStatus::Missing
+                };
+                (s.pubkey.clone(), status)
+            })
+            .collect())
+    }
+
+    // Copies into this transaction the signatures of another copy of it.  The copies must have the same message, and
+    // must not have different signatures for the same signer.
+    pub fn merge_signatures(
+        &mut self,
+        other : &Transaction
+    ) -> Result<(), Error>
+    {
+        let mut message = vec![];
+
+        self.message(&mut message)?;
+
+        let mut other_message = vec![];
+
+        other.message(&mut other_message)?;
+
+        if message != other_message {
+            return Err(Error::signature("The transactions differ"));
+        }
+
+        for s in other.signed_read_write_addresses.iter().chain(&other.signed_read_only_addresses) {
+            if let Some(signature) = s.signature {
+                let existing = self
+                    .signed_read_write_addresses
+                    .iter()
+                    .chain(&self.signed_read_only_addresses)
+                    .find(|m| m.pubkey == s.pubkey)
+                    .and_then(|m| m.signature);
+                match existing {
+                    Some(existing) if existing != signature => {
+                        return Err(Error::signature(&format!(
+                            "The transactions have different signatures for {}",
+                            s.pubkey
+                        )))
+                    },
+                    _ => self.sign(&s.pubkey, signature)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn encode(
         &self,
         w : &mut dyn std::io::Write
@@ -508,7 +909,7 @@ impl Transaction
         let total_signatures = self.signed_read_write_addresses.len() + self.signed_read_only_addresses.len();
 
         if total_signatures > (u16::MAX as usize) {
-            return Err(stre("Too many addresses"));
+            return Err(Error::encode("Too many addresses"));
         }
 
         Self::encode_compact_u16(total_signatures as u16, w)?;
@@ -543,6 +944,13 @@ impl Transaction
         }
     }
 
+    fn decode_u8_array(r : &mut dyn std::io::Read) -> Result<Vec<u8>, Error>
+    {
+        let mut v = vec![0_u8; Self::decode_compact_u16(r)? as usize];
+        Self::read(r, &mut v)?;
+        Ok(v)
+    }
+
     fn decode_signature_from_header(
         signatures : impl IntoIterator<Item = Option<ed25519_dalek::Signature>>,
         r : &mut dyn std::io::Read
@@ -646,6 +1054,92 @@ impl Transaction
         None
     }
 
+    fn static_address_count(&self) -> usize
+    {
+        self.signed_read_write_addresses.len() +
+            self.signed_read_only_addresses.len() +
+            self.unsigned_read_write_addresses.len() +
+            self.unsigned_read_only_addresses.len()
+    }
+
+    // Addresses loaded from address table lookups are indexed after all static addresses, with all writable lookup
+    // addresses (in the order of the lookups) first, followed by all read-only lookup addresses
+    fn find_instruction_address_index(
+        &self,
+        address : &InstructionAddress
+    ) -> Option<u8>
+    {
+        let (table, index) = match address {
+            InstructionAddress::Static(address) => return self.find_address_index(address),
+            InstructionAddress::Lookup { table, index } => (table, index)
+        };
+
+        let mut offset = self.static_address_count();
+
+        for lookup in &self.address_table_lookups {
+            if lookup.table == *table {
+                if let Some(pos) = lookup.writable_indexes.iter().position(|i| i == index) {
+                    return u8::try_from(pos + offset).ok();
+                }
+            }
+            offset += lookup.writable_indexes.len();
+        }
+
+        for lookup in &self.address_table_lookups {
+            if lookup.table == *table {
+                if let Some(pos) = lookup.readonly_indexes.iter().position(|i| i == index) {
+                    return u8::try_from(pos + offset).ok();
+                }
+            }
+            offset += lookup.readonly_indexes.len();
+        }
+
+        None
+    }
+
+    // Returns (address, is_signed, read_write)
+    fn find_instruction_address_at_index(
+        &self,
+        index : u8
+    ) -> Option<(InstructionAddress, bool, bool)>
+    {
+        if let Some((address, is_signed, is_read_write)) = self.find_address_at_index(index) {
+            return Some((InstructionAddress::Static(address), is_signed, is_read_write));
+        }
+
+        let mut uindex = (index as usize) - self.static_address_count();
+
+        for lookup in &self.address_table_lookups {
+            if uindex < lookup.writable_indexes.len() {
+                return Some((
+                    InstructionAddress::Lookup {
+                        table : lookup.table.clone(),
+                        index : lookup.writable_indexes[uindex]
+                    },
+                    false,
+                    true
+                ));
+            }
+            uindex -= lookup.writable_indexes.len();
+        }
+
+        for lookup in &self.address_table_lookups {
+            if uindex < lookup.readonly_indexes.len() {
+                return Some((
+                    InstructionAddress::Lookup {
+                        table : lookup.table.clone(),
+                        index : lookup.readonly_indexes[uindex]
+                    },
+                    false,
+                    false
+                ));
+            }
+            uindex -= lookup.readonly_indexes.len();
+        }
+
+        None
+    }
+
     fn encode_compact_u16(
         mut u : u16,
         w : &mut dyn std::io::Write
@@ -674,6 +1168,18 @@ impl Transaction
         }
     }
 
+    fn encode_u8_array(
+        v : &[u8],
+        w : &mut dyn std::io::Write
+    ) -> Result<(), Error>
+    {
+        if v.len() > (u16::MAX as usize) {
+            return Err(Error::encode("Too many address table lookup indexes"));
+        }
+        Self::encode_compact_u16(v.len() as u16, w)?;
+        Self::write(w, v)
+    }
+
     fn encode_signature(
         signature : Option<ed25519_dalek::Signature>,
         w : &mut dyn std::io::Write
@@ -726,6 +1232,46 @@ fn convert_address(
     json_Value::Object(map)
 }
 
+fn convert_instruction_address(
+    address : &InstructionAddress,
+    is_signed : bool,
+    is_read_write : bool
+) -> json_Value
+{
+    match address {
+        InstructionAddress::Static(address) => convert_address(address, is_signed, is_read_write, None),
+        InstructionAddress::Lookup { table, index } => {
+            let mut map = json_Map::<String, json_Value>::new();
+
+            map.insert("table".to_string(), json_Value::String(format!("{}", table)));
+
+            map.insert("index".to_string(), json_Value::Number(json_Number::from(*index)));
+
+            map.insert("is_read_write".to_string(), json_Value::Bool(is_read_write));
+
+            json_Value::Object(map)
+        }
+    }
+}
+
+fn convert_u8_array(v : &[u8]) -> json_Value
+{
+    json_Value::Array(v.iter().map(|v| json_Value::Number(json_Number::from(*v))).collect())
+}
+
+fn convert_address_table_lookup(lookup : &AddressTableLookup) -> json_Value
+{
+    let mut map = json_Map::<String, json_Value>::new();
+
+    map.insert("table".to_string(), json_Value::String(format!("{}", lookup.table)));
+
+    map.insert("writable_indexes".to_string(), convert_u8_array(&lookup.writable_indexes));
+
+    map.insert("readonly_indexes".to_string(), convert_u8_array(&lookup.readonly_indexes));
+
+    json_Value::Object(map)
+}
+
 fn convert_instruction(instruction : &Instruction) -> json_Value
 {
     let mut map = json_Map::<String, json_Value>::new();
@@ -733,29 +1279,134 @@ fn convert_instruction(instruction : &Instruction) -> json_Value
     map.insert("program_id".to_string(), json_Value::String(format!("{}", instruction.program_address)));
 
     let addresses : Vec<json_Value> =
-        instruction.addresses.iter().map(|a| convert_address(&a.0, a.1, a.2, None)).collect();
+        instruction.addresses.iter().map(|a| convert_instruction_address(&a.0, a.1, a.2)).collect();
 
     if addresses.len() > 0 {
         map.insert("addresses".to_string(), json_Value::Array(addresses));
     }
 
-    map.insert(
-        "data".to_string(),
-        json_Value::Array(instruction.data.iter().map(|v| json_Value::Number(json_Number::from(*v))).collect())
-    );
+    map.insert("data".to_string(), convert_u8_array(&instruction.data));
 
     json_Value::Object(map)
 }
 
-impl std::fmt::Display for Transaction
+fn parse_field<'a>(
+    json : &'a json_Value,
+    field : &str
+) -> Result<&'a json_Value, Error>
 {
-    fn fmt(
-        &self,
-        f : &mut std::fmt::Formatter
-    ) -> std::fmt::Result
+    json.get(field).ok_or_else(|| Error::parse(&format!("Missing {} in JSON transaction", field)))
+}
+
+fn parse_bool(
+    json : &json_Value,
+    field : &str
+) -> Result<bool, Error>
+{
+    // Flags are omitted when false
+    match json.get(field) {
+        None => Ok(false),
+        Some(json_Value::Bool(b)) => Ok(*b),
+        Some(_) => Err(Error::parse(&format!("Invalid {} in JSON transaction: expected a bool", field)))
+    }
+}
+
+fn parse_array<'a>(
+    json : &'a json_Value,
+    field : &str
+) -> Result<&'a Vec<json_Value>, Error>
+{
+    match parse_field(json, field)? {
+        json_Value::Array(a) => Ok(a),
+        _ => Err(Error::parse(&format!("Invalid {} in JSON transaction: expected an array", field)))
+    }
+}
+
+fn parse_address(
+    json : &json_Value,
+    field : &str
+) -> Result<Address, Error>
+{
+    match parse_field(json, field)? {
+        json_Value::String(s) => Address::from_str(s).map_err(|e| Error::parse(&e)),
+        _ => Err(Error::parse(&format!("Invalid {} in JSON transaction: expected a string", field)))
+    }
+}
+
+fn parse_u8(
+    json : &json_Value,
+    field : &str
+) -> Result<u8, Error>
+{
+    json.as_u64()
+        .and_then(|u| u8::try_from(u).ok())
+        .ok_or_else(|| Error::parse(&format!("Invalid {} in JSON transaction: expected a number from 0 to 255", field)))
+}
+
+fn parse_u8_array(
+    json : &json_Value,
+    field : &str
+) -> Result<Vec<u8>, Error>
+{
+    parse_array(json, field)?.iter().map(|u| parse_u8(u, field)).collect()
+}
+
+fn parse_instruction_address(json : &json_Value) -> Result<(InstructionAddress, bool, bool), Error>
+{
+    let is_signed = parse_bool(json, "is_signed")?;
+
+    let address = if json.get("table").is_some() {
+        let address = InstructionAddress::Lookup {
+            table : parse_address(json, "table")?,
+            index : parse_u8(parse_field(json, "index")?, "index")?
+        };
+        // Signers are always static addresses of the transaction
+        if is_signed {
+            return Err(Error::parse(&format!("Lookup table address {} cannot be signed in JSON transaction", address)));
+        }
+        address
+    }
+    else {
+        InstructionAddress::Static(parse_address(json, "address")?)
+    };
+
+    Ok((address, is_signed, parse_bool(json, "is_read_write")?))
+}
+
+fn parse_address_table_lookup(json : &json_Value) -> Result<AddressTableLookup, Error>
+{
+    Ok(AddressTableLookup {
+        table : parse_address(json, "table")?,
+        writable_indexes : parse_u8_array(json, "writable_indexes")?,
+        readonly_indexes : parse_u8_array(json, "readonly_indexes")?
+    })
+}
+
+fn parse_instruction(json : &json_Value) -> Result<Instruction, Error>
+{
+    Ok(Instruction {
+        program_address : parse_address(json, "program_id")?,
+        addresses : match json.get("addresses") {
+            None => vec![],
+            Some(_) => {
+                parse_array(json, "addresses")?.iter().map(parse_instruction_address).collect::<Result<_, _>>()?
+            },
+        },
+        data : parse_u8_array(json, "data")?
+    })
+}
+
+impl Transaction
+{
+    // Returns the JSON representation of the transaction, which is what Display prints
+    pub fn to_json(&self) -> json_Value
     {
         let mut top_map = json_Map::<String, json_Value>::new();
 
+        if let Some(version) = self.version {
+            top_map.insert("version".to_string(), json_Value::Number(json_Number::from(version)));
+        }
+
         let mut addresses = Vec::<json_Value>::new();
 
         for s in &self.signed_read_write_addresses {
@@ -792,7 +1443,144 @@ impl std::fmt::Display for Transaction
 
         top_map.insert("instructions".to_string(), self.instructions.iter().map(|i| convert_instruction(i)).collect());
 
-        write!(f, "{}", json_Value::to_string(&json_Value::Object(top_map)))
+        if !self.address_table_lookups.is_empty() {
+            top_map.insert(
+                "address_table_lookups".to_string(),
+                self.address_table_lookups.iter().map(convert_address_table_lookup).collect()
+            );
+        }
+
+        json_Value::Object(top_map)
+    }
+
+    // Builds a transaction from the JSON representation returned by to_json.  Signatures are not included in the
+    // JSON representation, so the transaction is unsigned.  Fields which are not part of the JSON representation,
+    // such as those added when instruction data is decoded, are ignored.  Each instruction account that is not loaded
+    // from a lookup table must be present in the addresses list, and be signed and read-write just as it is there.
+    pub fn from_json(json : &json_Value) -> Result<Self, Error>
+    {
+        let version = match json.get("version") {
+            None => None,
+            Some(version) => match parse_u8(version, "version")? {
+                0 => Some(0),
+                version => return Err(Error::parse(&format!("Unsupported transaction version {}", version)))
+            }
+        };
+
+        let mut ret = Transaction {
+            version,
+            signed_read_write_addresses : vec![],
+            signed_read_only_addresses : vec![],
+            unsigned_read_write_addresses : vec![],
+            unsigned_read_only_addresses : vec![],
+            recent_blockhash : None,
+            instructions : vec![],
+            address_table_lookups : vec![]
+        };
+
+        // Each address, as (address, is_signed, is_read_write)
+        let mut seen = Vec::<(Address, bool, bool)>::new();
+
+        for (i, json_address) in parse_array(json, "addresses")?.iter().enumerate() {
+            let address = parse_address(json_address, "address")?;
+
+            if seen.iter().any(|(seen, _, _)| *seen == address) {
+                return Err(Error::parse(&format!("Duplicate address {} in JSON transaction", address)));
+            }
+
+            let is_signed = parse_bool(json_address, "is_signed")?;
+
+            let is_read_write = parse_bool(json_address, "is_read_write")?;
+
+            // The fee payer is always the first address, which must be signed and read-write
+            if i == 0 {
+                if !is_signed || !is_read_write {
+                    return Err(Error::parse(
+                        "The first address of a JSON transaction is the fee payer, and must be signed and read-write"
+                    ));
+                }
+            }
+            else if parse_bool(json_address, "fee_payer")? {
+                return Err(Error::parse(&format!(
+                    "Fee payer {} is not the first address of the JSON transaction",
+                    address
+                )));
+            }
+
+            match (is_signed, is_read_write) {
+                (true, true) => ret
+                    .signed_read_write_addresses
+                    .push(PubkeyWithSignature { pubkey : address.clone().into(), signature : None }),
+                (true, false) => ret
+                    .signed_read_only_addresses
+                    .push(PubkeyWithSignature { pubkey : address.clone().into(), signature : None }),
+                (false, true) => ret.unsigned_read_write_addresses.push(address.clone()),
+                (false, false) => ret.unsigned_read_only_addresses.push(address.clone())
+            }
+
+            seen.push((address, is_signed, is_read_write));
+        }
+
+        if seen.is_empty() {
+            return Err(Error::parse("Missing fee payer in JSON transaction"));
+        }
+
+        if let Some(recent_blockhash) = json.get("recent_blockhash") {
+            match recent_blockhash {
+                json_Value::String(s) => {
+                    ret.recent_blockhash = Some(Sha256Digest::from_str(s).map_err(|e| Error::parse(&e))?)
+                },
+                _ => return Err(Error::parse("Invalid recent_blockhash in JSON transaction: expected a string"))
+            }
+        }
+
+        if json.get("address_table_lookups").is_some() {
+            ret.address_table_lookups = parse_array(json, "address_table_lookups")?
+                .iter()
+                .map(parse_address_table_lookup)
+                .collect::<Result<_, _>>()?;
+        }
+
+        for json_instruction in parse_array(json, "instructions")? {
+            let instruction = parse_instruction(json_instruction)?;
+
+            for (address, is_signed, is_read_write) in &instruction.addresses {
+                if let InstructionAddress::Static(address) = address {
+                    match seen.iter().find(|(seen, _, _)| seen == address) {
+                        None => {
+                            return Err(Error::parse(&format!(
+                                "Instruction address {} is not in the addresses of the JSON transaction",
+                                address
+                            )))
+                        },
+                        Some((_, seen_is_signed, seen_is_read_write)) => {
+                            if (seen_is_signed != is_signed) || (seen_is_read_write != is_read_write) {
+                                return Err(Error::parse(&format!(
+                                    "Instruction address {} is not signed and read-write as it is in the addresses \
+                                     of the JSON transaction",
+                                    address
+                                )));
+                            }
+                        }
+                    }
+                }
+            }
+
+            ret.add_instruction(instruction);
+        }
+
+        Ok(ret)
+    }
+}
+
+impl std::fmt::Display for Transaction
+{
+    fn fmt(
+        &self,
+        f : &mut std::fmt::Formatter
+    ) -> std::fmt::Result
+    {
+        write!(f, "{}", json_Value::to_string(&self.to_json()))
     }
 }
 
@@ -846,6 +1634,36 @@ impl PartialEq<Pubkey> for Address
     }
 }
 
+impl std::fmt::Display for InstructionAddress
+{
+    fn fmt(
+        &self,
+        f : &mut std::fmt::Formatter
+    ) -> std::fmt::Result
+    {
+        match self {
+            InstructionAddress::Static(address) => write!(f, "{}", address),
+            InstructionAddress::Lookup { table, index } => write!(f, "{}[{}]", table, index)
+        }
+    }
+}
+
+impl std::convert::From<Address> for InstructionAddress
+{
+    fn from(a : Address) -> Self
+    {
+        InstructionAddress::Static(a)
+    }
+}
+
+impl std::convert::From<Pubkey> for InstructionAddress
+{
+    fn from(p : Pubkey) -> Self
+    {
+        InstructionAddress::Static(Address(p.0))
+    }
+}
+
 impl std::fmt::Display for Pubkey
 {
     fn fmt(
@@ -907,3 +1725,440 @@ impl std::str::FromStr for Sha256Digest
         }
     }
 }
+
+impl std::fmt::Display for SignatureStatus
+{
+    fn fmt(
+        &self,
+        f : &mut std::fmt::Formatter
+    ) -> std::fmt::Result
+    {
+        match self {
+            SignatureStatus::Valid => write!(f, "valid"),
+            SignatureStatus::Invalid => write!(f, "invalid"),
+            SignatureStatus::Missing => write!(f, "missing")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::str::FromStr;
+
+    // A v0 transaction signed by its fee payer, with a System program instruction whose second account is loaded
+    // writable from index 0, and whose third account is loaded read-only from index 1, of one address lookup table
+    const V0_TRANSACTION_HEX : &str = concat!(
+        "012a5e8a53fddc8a19b612e5934b9d33c6b2d0ff5a6e28654319cf6a1f95d55b502f8a579ef1e3a8e7328265e194f7a35e8b",
+        "6060e39241f0786dbcb6bce19a1b008001000102c3aafcfb595c2267da6d919f0e2aba5536bfa04622ba459918b4c2b6cf72",
+        "a9e10000000000000000000000000000000000000000000000000000000000000000ce59db5080fc2c6d3bcf7ca90712d3c2",
+        "e5e6c28f27f0dfbb9953bdb0894c03ab01010300020302020501850f2d6e02a47af824d09ab69dc42d70cb28cbfa249fb7ee",
+        "57b9d256c12762ef01000101"
+    );
+
+    // A legacy transaction with two signers, both of which have signed it
+    const LEGACY_TRANSACTION_HEX : &str = concat!(
+        "02277e3f87d49ec2e3099c2b1e64ea8f404d3088fb4f1b1549ad9ce66b293dff2e94f31847868bf1f5d4ccd12494800a5b42",
+        "c6efb53f9ee482661de62d5473e80d0a30a9956280e859158e0c3b6d5bc568f7395afbd0841082f2907713f43337b3f8865b",
+        "f49b84c908f879c3c804ba2271b8c84ace26fc28019675dbfc1e0dcb0002000103c3aafcfb595c2267da6d919f0e2aba5536",
+        "bfa04622ba459918b4c2b6cf72a9e1d36bc547498bc88f75fce3188a218ce7275644f20ad46ccf64dba9ff833f93cb000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "00000000000000000000000102020001020205"
+    );
+
+    const FEE_PAYER : &str = "EAoizDFAwK2o5jjAgtuwgKcPxnz8JmGyevUkEwi9gwmS";
+
+    const LOOKUP_TABLE : &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+
+    // The contents of LOOKUP_TABLE
+    const LOOKUP_TABLE_ADDRESSES : [&str; 3] = [
+        "AVheJF4ZzCZjfysZP2FHdFERY3r7dh9AdBRRcJRWKARc",
+        "SysvarRent111111111111111111111111111111111",
+        "11111111111111111111111111111111"
+    ];
+
+    const RECENT_BLOCKHASH : &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+    // The message of V0_TRANSACTION_HEX
+    const V0_MESSAGE_HEX : &str = concat!(
+        "8001000102c3aafcfb595c2267da6d919f0e2aba5536bfa04622ba459918b4c2b6cf72a9e100000000000000000000000000",
+        "00000000000000000000000000000000000000ce59db5080fc2c6d3bcf7ca90712d3c2e5e6c28f27f0dfbb9953bdb0894c03",
+        "ab01010300020302020501850f2d6e02a47af824d09ab69dc42d70cb28cbfa249fb7ee57b9d256c12762ef01000101"
+    );
+
+    fn address(s : &str) -> Address
+    {
+        Address::from_str(s).unwrap()
+    }
+
+    // A legacy transaction with a System program instruction whose second and third accounts are in LOOKUP_TABLE
+    fn legacy_transaction() -> Transaction
+    {
+        let mut transaction = Transaction::new(Pubkey::from_str(FEE_PAYER).unwrap());
+
+        transaction.add_instruction(Instruction {
+            program_address : address(LOOKUP_TABLE_ADDRESSES[2]),
+            addresses : vec![
+                (address(FEE_PAYER).into(), true, true),
+                (address(LOOKUP_TABLE_ADDRESSES[0]).into(), false, true),
+                (address(LOOKUP_TABLE_ADDRESSES[1]).into(), false, false)
+            ],
+            data : vec![2, 5]
+        });
+
+        transaction.set_recent_blockhash(Sha256Digest::from_str(RECENT_BLOCKHASH).unwrap());
+
+        transaction
+    }
+
+    fn message(transaction : &Transaction) -> Vec<u8>
+    {
+        let mut message = vec![];
+        transaction.message(&mut message).unwrap();
+        message
+    }
+
+    fn decode_hex(hex : &str) -> Transaction
+    {
+        Transaction::decode(&mut hex::decode(hex).unwrap().as_slice()).unwrap()
+    }
+
+    fn encode(transaction : &Transaction) -> Vec<u8>
+    {
+        let mut bytes = vec![];
+        transaction.encode(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn v0_transaction_decodes_lookups_and_reencodes_to_the_same_bytes()
+    {
+        let transaction = decode_hex(V0_TRANSACTION_HEX);
+
+        assert!(transaction.version == Some(0));
+        assert_eq!(transaction.address_table_lookups.len(), 1);
+        let lookup = &transaction.address_table_lookups[0];
+        assert_eq!(format!("{}", lookup.table), LOOKUP_TABLE);
+        assert_eq!(lookup.writable_indexes, vec![0]);
+        assert_eq!(lookup.readonly_indexes, vec![1]);
+
+        let table = Address::from_str(LOOKUP_TABLE).unwrap();
+        let addresses = &transaction.instructions[0].addresses;
+        assert!(addresses[1] == (InstructionAddress::Lookup { table : table.clone(), index : 0 }, false, true));
+        assert!(addresses[2] == (InstructionAddress::Lookup { table, index : 1 }, false, false));
+
+        assert_eq!(encode(&transaction), hex::decode(V0_TRANSACTION_HEX).unwrap());
+    }
+
+    #[test]
+    fn v0_transaction_signature_covers_the_versioned_message()
+    {
+        let transaction = decode_hex(V0_TRANSACTION_HEX);
+
+        let statuses = transaction.verify_signatures().unwrap();
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(format!("{}", statuses[0].0), FEE_PAYER);
+        assert!(statuses[0].1 == SignatureStatus::Valid);
+    }
+
+    #[test]
+    fn legacy_transaction_reencodes_to_the_same_bytes()
+    {
+        let transaction = decode_hex(LEGACY_TRANSACTION_HEX);
+
+        assert!(transaction.version.is_none());
+        assert!(transaction.address_table_lookups.is_empty());
+        assert_eq!(encode(&transaction), hex::decode(LEGACY_TRANSACTION_HEX).unwrap());
+    }
+
+    #[test]
+    fn v0_transaction_json_gives_lookups_and_lookup_addresses()
+    {
+        let json : json_Value = serde_json::from_str(&format!("{}", decode_hex(V0_TRANSACTION_HEX))).unwrap();
+
+        assert_eq!(json["version"], 0);
+        assert_eq!(
+            json["address_table_lookups"],
+            serde_json::json!([{ "table" : LOOKUP_TABLE, "writable_indexes" : [0], "readonly_indexes" : [1] }])
+        );
+        assert_eq!(
+            json["instructions"][0]["addresses"][1],
+            serde_json::json!({ "table" : LOOKUP_TABLE, "index" : 0, "is_read_write" : true })
+        );
+        assert_eq!(
+            json["instructions"][0]["addresses"][2],
+            serde_json::json!({ "table" : LOOKUP_TABLE, "index" : 1, "is_read_write" : false })
+        );
+    }
+
+    #[test]
+    fn lookup_tables_compile_the_transaction_into_v0()
+    {
+        let mut transaction = legacy_transaction();
+
+        transaction.apply_address_lookup_tables(&[(
+            address(LOOKUP_TABLE),
+            LOOKUP_TABLE_ADDRESSES.iter().map(|a| address(a)).collect()
+        )]);
+
+        assert!(transaction.version == Some(0));
+
+        // The program stays static even though the table holds it
+        assert_eq!(message(&transaction), hex::decode(V0_MESSAGE_HEX).unwrap());
+
+        assert!(transaction.needed_signatures().eq(std::iter::once(Pubkey::from_str(FEE_PAYER).unwrap())));
+    }
+
+    #[test]
+    fn loaded_writable_addresses_come_from_lookup_table_contents()
+    {
+        let transaction = decode_hex(V0_TRANSACTION_HEX);
+
+        let contents = LOOKUP_TABLE_ADDRESSES.iter().map(|a| address(a)).collect();
+
+        let loaded = transaction.loaded_writable_addresses(&[(address(LOOKUP_TABLE), contents)]).unwrap();
+
+        assert_eq!(loaded.iter().map(|a| format!("{}", a)).collect::<Vec<_>>(), vec![LOOKUP_TABLE_ADDRESSES[0]]);
+
+        assert!(transaction.loaded_writable_addresses(&[]).is_err());
+    }
+
+    #[test]
+    fn addresses_in_no_lookup_table_stay_static()
+    {
+        let mut transaction = legacy_transaction();
+
+        transaction.apply_address_lookup_tables(&[(address(LOOKUP_TABLE), vec![address(FEE_PAYER)])]);
+
+        assert!(transaction.version == Some(0));
+        assert!(transaction.address_table_lookups.is_empty());
+        assert!(transaction.instructions[0]
+            .addresses
+            .iter()
+            .all(|(a, _, _)| matches!(a, InstructionAddress::Static(_))));
+
+        // The messages differ only in the version prefix and the empty list of address table lookups
+        let v0_message = message(&transaction);
+        assert_eq!(v0_message[0], 0x80);
+        assert_eq!(v0_message[v0_message.len() - 1], 0);
+        assert_eq!(v0_message[1..(v0_message.len() - 1)], message(&legacy_transaction())[..]);
+    }
+
+    fn keypair(secret : u8) -> ed25519_dalek::Keypair
+    {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[secret; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        ed25519_dalek::Keypair { secret, public }
+    }
+
+    fn pubkey(keypair : &ed25519_dalek::Keypair) -> Pubkey
+    {
+        Pubkey(keypair.public.to_bytes())
+    }
+
+    // An unsigned transaction with the pubkeys of two keypairs as its signers
+    fn two_signer_transaction(
+        fee_payer : &ed25519_dalek::Keypair,
+        signer : &ed25519_dalek::Keypair
+    ) -> Transaction
+    {
+        let mut transaction = Transaction::new(pubkey(fee_payer));
+
+        transaction.add_instruction(Instruction {
+            program_address : Address::from_str(LOOKUP_TABLE_ADDRESSES[2]).unwrap(),
+            addresses : vec![(pubkey(fee_payer).into(), true, true), (pubkey(signer).into(), true, false)],
+            data : vec![2, 5]
+        });
+
+        transaction.set_recent_blockhash(Sha256Digest([3_u8; 32]));
+
+        transaction
+    }
+
+    // Signs a transaction with a keypair, as solxact sign does
+    fn sign(
+        transaction : &mut Transaction,
+        keypair : &ed25519_dalek::Keypair
+    )
+    {
+        let signature = ed25519_dalek::Signer::sign(keypair, &message(transaction));
+        transaction.sign(&pubkey(keypair), signature).unwrap();
+    }
+
+    fn statuses(transaction : &Transaction) -> Vec<SignatureStatus>
+    {
+        transaction.verify_signatures().unwrap().into_iter().map(|(_, status)| status).collect()
+    }
+
+    #[test]
+    fn verify_signatures_reports_each_signer()
+    {
+        let (fee_payer, signer) = (keypair(1), keypair(2));
+
+        let mut transaction = two_signer_transaction(&fee_payer, &signer);
+
+        let verified = transaction.verify_signatures().unwrap();
+        assert!(verified[0].0 == pubkey(&fee_payer));
+        assert!(verified[1].0 == pubkey(&signer));
+        assert!(statuses(&transaction) == vec![SignatureStatus::Missing, SignatureStatus::Missing]);
+
+        sign(&mut transaction, &fee_payer);
+        assert!(statuses(&transaction) == vec![SignatureStatus::Valid, SignatureStatus::Missing]);
+        assert!(transaction.needed_signatures().eq(std::iter::once(pubkey(&signer))));
+
+        // A signature of a different message is invalid
+        let signature = ed25519_dalek::Signer::sign(&signer, b"a different message");
+        transaction.sign(&pubkey(&signer), signature).unwrap();
+        assert!(statuses(&transaction) == vec![SignatureStatus::Valid, SignatureStatus::Invalid]);
+
+        assert_eq!(format!("{}", SignatureStatus::Invalid), "invalid");
+    }
+
+    #[test]
+    fn merge_signatures_combines_partially_signed_copies()
+    {
+        let (fee_payer, signer) = (keypair(1), keypair(2));
+
+        let mut first = two_signer_transaction(&fee_payer, &signer);
+        sign(&mut first, &fee_payer);
+
+        let mut second = two_signer_transaction(&fee_payer, &signer);
+        sign(&mut second, &signer);
+
+        first.merge_signatures(&second).unwrap();
+        assert!(statuses(&first) == vec![SignatureStatus::Valid, SignatureStatus::Valid]);
+
+        // Merging the same signatures again changes nothing
+        first.merge_signatures(&second).unwrap();
+        assert!(first.needed_signatures().next().is_none());
+
+        let mut different = two_signer_transaction(&fee_payer, &signer);
+        different.set_recent_blockhash(Sha256Digest([4_u8; 32]));
+        let error = first.merge_signatures(&different).err().unwrap();
+        assert_eq!(error.category(), "signature");
+        assert_eq!(error.message(), "The transactions differ");
+
+        let mut conflicting = two_signer_transaction(&fee_payer, &signer);
+        conflicting.sign(&pubkey(&signer), ed25519_dalek::Signer::sign(&signer, b"a different message")).unwrap();
+        assert_eq!(
+            first.merge_signatures(&conflicting).err().unwrap().message(),
+            format!("The transactions have different signatures for {}", pubkey(&signer))
+        );
+    }
+
+    #[test]
+    fn attach_signature_accepts_only_valid_signatures_of_signers()
+    {
+        let (fee_payer, signer) = (keypair(1), keypair(2));
+
+        let mut transaction = two_signer_transaction(&fee_payer, &signer);
+
+        let signature = ed25519_dalek::Signer::sign(&signer, &message(&transaction));
+
+        assert_eq!(
+            transaction.attach_signature(&pubkey(&fee_payer), signature).err().unwrap().message(),
+            format!("Signature for {} is not valid for the transaction", pubkey(&fee_payer))
+        );
+
+        let outsider = keypair(3);
+        assert_eq!(
+            transaction
+                .attach_signature(&pubkey(&outsider), ed25519_dalek::Signer::sign(&outsider, &message(&transaction)))
+                .err()
+                .unwrap()
+                .message(),
+            format!("{} is not a signer of the transaction", pubkey(&outsider))
+        );
+
+        transaction.attach_signature(&pubkey(&signer), signature).unwrap();
+        assert!(statuses(&transaction) == vec![SignatureStatus::Missing, SignatureStatus::Valid]);
+    }
+
+    #[test]
+    fn v0_transaction_json_round_trips_to_the_unsigned_transaction()
+    {
+        let mut transaction = decode_hex(V0_TRANSACTION_HEX);
+
+        let from_json = Transaction::from_json(&transaction.to_json()).unwrap();
+
+        transaction.clear_signatures();
+
+        assert_eq!(encode(&from_json), encode(&transaction));
+    }
+
+    #[test]
+    fn legacy_transaction_json_round_trips_including_decoded_instructions()
+    {
+        let mut transaction = decode_hex(LEGACY_TRANSACTION_HEX);
+
+        transaction.clear_signatures();
+
+        assert_eq!(encode(&Transaction::from_json(&transaction.to_json()).unwrap()), encode(&transaction));
+
+        // What decode adds to the JSON of instructions of well-known programs is ignored
+        let decoded = crate::programs::decode_transaction_json(&transaction, &[]);
+        assert_eq!(decoded["instructions"][0]["program_name"], "System");
+        assert_eq!(encode(&Transaction::from_json(&decoded).unwrap()), encode(&transaction));
+    }
+
+    #[test]
+    fn from_json_rejects_inconsistent_transactions()
+    {
+        let json = decode_hex(LEGACY_TRANSACTION_HEX).to_json();
+
+        let rejected = |edit : &dyn Fn(&mut json_Value)| {
+            let mut json = json.clone();
+            edit(&mut json);
+            Transaction::from_json(&json).err().unwrap().message().to_string()
+        };
+
+        assert_eq!(rejected(&|json| json["version"] = 1.into()), "Unsupported transaction version 1");
+
+        assert_eq!(
+            rejected(&|json| json["addresses"] = json_Value::Array(vec![])),
+            "Missing fee payer in JSON transaction"
+        );
+
+        assert_eq!(
+            rejected(&|json| json["addresses"][0]["is_read_write"] = false.into()),
+            "The first address of a JSON transaction is the fee payer, and must be signed and read-write"
+        );
+
+        let second = json["addresses"][1]["address"].clone();
+        assert_eq!(
+            rejected(&|json| json["addresses"][2]["address"] = second.clone()),
+            format!("Duplicate address {} in JSON transaction", second.as_str().unwrap())
+        );
+
+        let signer = second.as_str().unwrap();
+        assert_eq!(
+            rejected(&|json| json["instructions"][0]["addresses"][1]["is_read_write"] = false.into()),
+            format!(
+                "Instruction address {} is not signed and read-write as it is in the addresses of the JSON transaction",
+                signer
+            )
+        );
+
+        let unlisted = "SysvarRent111111111111111111111111111111111";
+        assert_eq!(
+            rejected(&|json| json["instructions"][0]["addresses"][1]["address"] = unlisted.into()),
+            format!("Instruction address {} is not in the addresses of the JSON transaction", unlisted)
+        );
+
+        assert_eq!(
+            rejected(&|json| {
+                json["instructions"][0]["addresses"][1] = serde_json::json!({
+                    "table" : LOOKUP_TABLE,
+                    "index" : 0,
+                    "is_signed" : true,
+                    "is_read_write" : true
+                })
+            }),
+            format!("Lookup table address {}[0] cannot be signed in JSON transaction", LOOKUP_TABLE)
+        );
+
+        assert!(Transaction::from_json(&serde_json::json!({})).is_err());
+    }
+}
@@ -7,14 +7,27 @@ For help on subcommands:
 
 solxact help encode        -- for encoding a transaction
 solxact help decode        -- for decoding a transaction
+solxact help encode-json   -- for encoding a transaction from decoded json
+solxact help format        -- for reading and writing transactions as text
+solxact help errors        -- for exit codes and reporting errors as JSON
+solxact help config        -- for config files, profiles, and environment variables
+solxact help headers       -- for HTTP headers and authentication for RPC nodes
 solxact help hash          -- for setting the recent blockhash of a transaction
+solxact help nonce         -- for using a durable nonce as the recent blockhash
+solxact help priority-fee  -- for setting the compute unit limit and priority fee
 solxact help sign          -- for signing a transaction
 solxact help show-unsigned -- for showing which signatures are still required
 solxact help signature     -- for showing a transaction's signature
+solxact help verify        -- for verifying a transaction's signatures
+solxact help merge         -- for merging signatures from copies of a transaction
+solxact help message       -- for showing the message bytes that signers sign
+solxact help attach        -- for adding signatures that were made elsewhere
 solxact help simulate      -- for simulating a transaction
+solxact help preview       -- for previewing a transaction's balance changes
 solxact help submit        -- for submitting a transaction
 solxact help pda           -- for computing program derived addresses
 solxact help pubkey        -- for displaying pubkeys
+solxact help keygen        -- for generating keypairs
 
 
 Some example use cases of solxact:
@@ -124,6 +137,7 @@ line arguments will produce an error.
 The input to solxact describes a complete Solana transaction.  This includes:
   - The transaction encoding style
   - The transaction fee payer account
+  - Optionally, address lookup tables to load accounts from
   - A sequence of instructions to include in the transaction, which are
     composed of:
     - The program id of the program to invoke
@@ -168,6 +182,25 @@ The arguments to solxact are drawn from the following set:
       <PUBKEY> argument is either a base58-encoded pubkey, or the path to a
       Solana json format key file from which the pubkey will be loaded.
 
+  lookup_table <PUBKEY> file <PATH>
+  lookup_table <PUBKEY> rpc <RPC_URL>
+
+      Supplies an address lookup table that the transaction may load accounts
+      from.  Any number of lookup_table arguments may follow fee_payer.  When
+      at least one is present, the transaction is encoded as a version 0
+      transaction, and every account that is neither a signer nor a program id
+      and which is present in a lookup table will be loaded from the first
+      lookup table that contains it, rather than being included directly in
+      the transaction.  This allows transactions which reference more accounts
+      than would otherwise fit within the transaction size limit.
+
+      <PUBKEY> is the address of the lookup table.  The contents of the table
+      are read either from <PATH>, which must be a file containing a JSON array
+      of Base58-encoded addresses in table order, or are fetched from the RPC
+      node at <RPC_URL>.  <RPC_URL> may also be one of the cluster identifiers
+      l, localhost, d, devnet, t, testnet, m, or mainnet.  A fetched account
+      must be an active table owned by the address lookup table program.
+
   Sequence of instructions: after encoding and fee_payer, the remaining
   arguments describe a sequence of instructions to include in the transaction.
   These all begin with a program argument that gives the program id of the
@@ -305,6 +338,337 @@ solxact decode will read an encoded transaction from standard input and write a
 human-readable decoded version of that transaction in json format to standard
 output.
 
+Both legacy and version 0 transactions are supported.  For version 0
+transactions, the output includes a \"version\" field and an
+\"address_table_lookups\" list giving each address lookup table used by the
+transaction along with the indexes of the writable and read-only addresses
+loaded from it.  Instruction accounts that are loaded from an address lookup
+table are shown with the \"table\" and \"index\" they are loaded from instead
+of an \"address\".
+
+Instructions of the following well-known programs are decoded automatically:
+System, Stake, Vote, Compute Budget, Memo, SPL Token, Token-2022, Associated
+Token Account, and Address Lookup Table.  For these, the instruction includes
+a \"program_name\", an \"instruction_name\", the decoded \"arguments\" of the
+instruction, and a \"role\" for each of its accounts, alongside the raw data.
+The arguments of some instructions, such as vote state updates and Token-2022
+extension instructions, are not decoded, and arguments are also omitted for
+any instruction whose data does not match its expected layout.
+
+Instruction data is otherwise shown as an array of bytes.  To have the data of
+a program's instructions decoded into typed values, supply data schemas for
+that program as arguments:
+
+  solxact decode [encoding <ENCODING>] program <PUBKEY> <DATA_SCHEMAs> ...
+
+Any number of program arguments may be given.  encoding sets the encoding,
+using the same encoding names as solxact encode, that the data of the
+programs following it were encoded with; if it is not present, then
+rust_bincode_varint is assumed.  Every instruction of the given program has
+its data decoded according to the DATA_SCHEMAs, which must consume all of
+the data, and the result is added to the instruction as \"decoded_data\".
+
+The same program may be given more than once, once for each layout of its
+instructions' data, and each instruction is decoded with the first of its
+program's schemas that its data matches.  Giving an enum <INDEX> first in
+each schema selects the schema by the instruction's index.  If the data of
+an instruction matches none of them, the instruction is shown with its data
+as hex in \"data_hex\" and the reason the last schema did not match in
+\"decode_error\".  Schemas given for a well-known program replace its
+automatic decoding.
+
+DATA_SCHEMAs are written in the same form as the data values of solxact
+encode, except that each is a type without any values:
+
+  bool, u8, u16, u32, u64, i8, i16, i32, i64, f32, f64
+
+      A single value of the given type.
+
+  string
+  c_string <MAX_LENGTH>
+  pubkey
+  sha256
+
+      A single string, C string, pubkey, or sha256 value.  pubkeys are shown
+      Base58-encoded and sha256 values hex-encoded.
+
+  vector [ <DATA_SCHEMAs> ]
+  struct [ <DATA_SCHEMAs> ]
+
+      A collection of values, or a struct of values.  A vector with more than
+      one schema is a vector of structs of those schemas.
+
+  enum
+  enum <INDEX>
+  enum [ <DATA_SCHEMAs> ]
+  enum <INDEX> [ <DATA_SCHEMAs> ]
+
+      An enum value, optionally followed by parameters.  If <INDEX> is given,
+      then the data must have that enum index.
+
+  option <DATA_SCHEMA>
+  some <DATA_SCHEMA>
+
+      A Rust-style Option value, shown as null when it is None.
+
+Any schema may be prefixed by a name and a colon, for example lamports:u64,
+and that name is included in the decoded value.  Each decoded value is a json
+object with a \"type\", a \"value\", and a \"name\" if one was given.  For
+example, the following decodes System program transfer and allocate
+instructions:
+
+$ solxact decode encoding rust_bincode_fixedint                          \\
+                 program 11111111111111111111111111111111                \\
+                 enum 2 [ lamports:u64 ]                                 \\
+                 program 11111111111111111111111111111111                \\
+                 enum 8 [ space:u64 ]
+
+";
+
+#[rustfmt::skip]
+pub const ENCODE_JSON_USAGE_MESSAGE : &str = "
+
+solxact encode-json will read a transaction in the json format written by
+solxact decode from standard input, and will encode that transaction and
+write its encoded form to standard output.
+
+This allows a transaction to be decoded, modified with a text editor or a
+tool such as 'jq', and then encoded again.  The addresses, recent_blockhash,
+instructions, and for version 0 transactions the version and
+address_table_lookups, are read from the json; any other fields, such as
+those added by solxact decode when it decodes instruction data, are ignored.
+The order of the addresses list is preserved, and accounts of instructions
+which do not appear in the addresses list are added to it.  The first
+address must be the fee payer.
+
+Signatures are not part of the json format, so the encoded transaction is
+always unsigned and must be signed again.
+
+For example, the following changes the lamports of a transfer instruction
+in a transaction which is stored in a file:
+
+$ solxact decode < transaction.bin                                       \\
+  | jq '.instructions[0].data = [2,0,0,0,0,225,245,5,0,0,0,0]'           \\
+  | solxact encode-json > modified.bin
+
+";
+
+#[rustfmt::skip]
+pub const FORMAT_USAGE_MESSAGE : &str = "
+
+Every solxact command that reads a transaction, and every solxact command
+that writes a transaction, can do so in any of the following formats:
+
+  raw    -- the binary encoding of the transaction
+  base64 -- the binary encoding, base64-encoded
+  base58 -- the binary encoding, base58-encoded
+  hex    -- the binary encoding, hex-encoded
+
+The formats are selected by options which are given before the command:
+
+  solxact [input <FORMAT>] [output <FORMAT>] <COMMAND> <ARGUMENTS>
+
+input selects the format that transactions are read in.  By default, or if
+<FORMAT> is auto, the format of each transaction that is read is detected
+automatically.  This allows transactions copied from explorers, wallets, or
+RPC logs to be supplied as they are.  Whitespace surrounding a transaction in
+a text format is ignored.
+
+output selects the format that transactions are written in; the default is
+raw.  Transactions written in a text format are followed by a newline.
+
+For example, the following signs a base64-encoded transaction that was copied
+into a file, and prints the signed transaction base64-encoded:
+
+$ cat transaction.txt | solxact output base64 sign ./my_key.json
+
+";
+
+#[rustfmt::skip]
+pub const CONFIG_USAGE_MESSAGE : &str = "
+
+solxact commands that use an RPC node, and solxact sign, take their defaults
+from a profile.  Profiles are defined in a config file, which is read from the
+path given by the SOLXACT_CONFIG environment variable, or else from
+~/.config/solxact/config.json if that exists.  The profile to use is selected
+by an option which is given before the command:
+
+  solxact [profile <NAME>] <COMMAND> <ARGUMENTS>
+
+or else by the SOLXACT_PROFILE environment variable, or else by the config
+file's default_profile.  If no profile is selected, none is used.
+
+The config file is a JSON object, for example:
+
+  {
+    \"default_profile\" : \"devnet\",
+    \"profiles\" : {
+      \"devnet\" : {
+        \"rpc_url\" : \"devnet\",
+        \"commitment\" : \"confirmed\",
+        \"keypair\" : \"~/.config/solana/id.json\",
+        \"cluster\" : \"devnet\"
+      },
+      \"provider\" : {
+        \"rpc_url\" : \"https://rpc.example.com\",
+        \"headers\" : { \"x-api-key\" : \"env:PROVIDER_API_KEY\" },
+        \"cluster\" : \"mainnet\"
+      },
+      \"other-provider\" : {
+        \"rpc_url\" : \"https://rpc.example.net\",
+        \"bearer_token\" : \"file:~/.secrets/other-provider-token\"
+      },
+      \"cli\" : {
+        \"solana_cli_config\" : true
+      }
+    }
+  }
+
+Each profile may give:
+
+  rpc_url           -- the RPC URL to use when a command is given none; a
+                       cluster identifier may be used, as for the commands
+  headers           -- HTTP headers to send with each request to rpc_url,
+                       such as those that carry API keys
+  bearer_token      -- a token to send with each request to rpc_url as an
+                       Authorization: Bearer header
+  basic_auth        -- <USER>:<PASSWORD> credentials to send with each
+                       request to rpc_url as an Authorization: Basic header;
+                       only one of bearer_token, basic_auth, and an
+                       Authorization header may be given
+  commitment        -- the commitment level that simulate and preview use,
+                       and that submit waits for, when they are given none
+  keypair           -- the keypair that sign uses when it is given none
+  cluster           -- the cluster that rpc_url is expected to belong to, by
+                       name or genesis hash; hash, nonce, priority-fee,
+                       simulate, preview, and submit fail if rpc_url belongs
+                       to a different cluster; see 'solxact help hash'
+  solana_cli_config -- true, or the path of a Solana CLI config file; the
+                       profile starts with the json_rpc_url, keypair_path, and
+                       commitment of that file, and then the profile's own
+                       settings override them.  true means the Solana CLI's
+                       config file at ~/.config/solana/cli/config.yml
+
+A profile named solana-cli that reads the Solana CLI's config file is always
+available, unless the config file defines one of that name, so that:
+
+$ solxact profile solana-cli submit < transaction.bin
+
+submits a transaction to the Solana CLI's configured RPC URL.
+
+Wherever a command takes an <RPC_URL>, the name of a profile that has an
+rpc_url may be given instead, in which case that profile's rpc_url, headers,
+and cluster are used.  For example, the following submits a transaction
+through two profiles' RPC providers:
+
+$ solxact submit provider other-provider < transaction.bin
+
+The following environment variables, if set, override the settings of the
+profile in use:
+
+  SOLXACT_RPC_URL    -- rpc_url
+  SOLXACT_COMMITMENT -- commitment
+  SOLXACT_KEYPAIR    -- keypair
+  SOLXACT_CLUSTER    -- cluster
+
+A profile's headers, bearer_token, basic_auth, and cluster are not used with
+an RPC URL given by SOLXACT_RPC_URL, since they belong to the profile's own
+rpc_url.  Values given on the command line override both.  Paths beginning with ~/ are
+relative to the home directory.
+
+The values of headers, bearer_token, and basic_auth are secrets, which may be
+read from elsewhere; see 'solxact help headers'.
+
+";
+
+#[rustfmt::skip]
+pub const HEADERS_USAGE_MESSAGE : &str = "
+
+solxact sends only a Content-Type header with its requests to RPC nodes, but
+some RPC providers require further HTTP headers, such as API keys, bearer
+tokens, or basic auth credentials.  These may be given by options which are
+given before the command, and which may be repeated:
+
+  solxact [header <NAME> <SECRET>] [bearer-token <SECRET>]
+          [basic-auth <SECRET>] <COMMAND> <ARGUMENTS>
+
+  header       -- sends the header of the given name, with the secret as its
+                  value
+  bearer-token -- sends the secret as an Authorization: Bearer header
+  basic-auth   -- sends the secret, which is <USER>:<PASSWORD>, as an
+                  Authorization: Basic header
+
+So that secrets do not appear on the command line, where other users of the
+system may see them and where shell history keeps them, each <SECRET> must be
+given as one of:
+
+  env:<NAME>  -- the value of the environment variable of that name
+  file:<PATH> -- the contents of that file, without any trailing newline
+
+Headers given by these options are sent with each request to the RPC node
+that the command uses, and a command that uses more than one RPC node, such
+as a submit to several RPC nodes, fails rather than send them to every one.
+Headers for each of several RPC nodes are instead given by the headers,
+bearer_token, and basic_auth settings of a profile, which are sent only with
+requests to that profile's rpc_url; see 'solxact help config'.  Where both
+give a header of the same name, ignoring case, only the option's is sent, so
+that for example bearer-token replaces a profile's basic_auth.  Only one of an
+Authorization header, bearer-token, and basic-auth may be given.
+
+The values of a profile's headers, bearer_token, and basic_auth may also be
+given as env:<NAME> or file:<PATH>, so that secrets are kept out of config
+files.  Any other value is used as is.
+
+Examples:
+
+$ solxact bearer-token env:PROVIDER_TOKEN submit https://rpc.example.com \\
+    < transaction.bin
+
+$ solxact header x-api-key file:~/.secrets/api-key simulate \\
+    https://rpc.example.com < transaction.bin
+
+";
+
+#[rustfmt::skip]
+pub const ERRORS_USAGE_MESSAGE : &str = "
+
+When a solxact command fails, solxact prints the error to standard error and
+exits with an exit code that identifies the category of the failure:
+
+  2  -- usage     : invalid command line arguments
+  3  -- parse     : text that could not be parsed, such as encode parameters,
+                    data values, schemas, pubkeys, or JSON transactions
+  4  -- decode    : a transaction or instruction data that could not be
+                    decoded
+  5  -- encode    : a value that could not be encoded, or a transaction that
+                    exceeds the limits of the transaction format
+  6  -- keypair   : a keypair or seed phrase that could not be read, derived,
+                    or generated
+  7  -- signature : a signature that is missing, invalid, or does not match
+                    its transaction
+  8  -- rpc       : a failure to communicate with an RPC node, or an
+                    unexpected response from one
+  9  -- rejected  : a transaction that was rejected by simulation, preflight,
+                    or the cluster
+  10 -- io        : a failure to read or write a file or standard stream
+
+The format that errors are printed in is selected by an option which is given
+before the command:
+
+  solxact [errors <text|json>] <COMMAND> <ARGUMENTS>
+
+text, the default, prints the error message for a person to read.  json
+prints a single line JSON object giving the category, exit code, and message
+of the error, for example:
+
+  {\"category\":\"rpc\",\"exit_code\":8,\"message\":\"Account ... does not exist\"}
+
+For example, the following simulates a transaction and branches on whether
+the cluster rejected it:
+
+$ solxact errors json simulate devnet < transaction.bin > /dev/null
+$ [ $? -eq 9 ] && echo \"Transaction would fail\"
+
 ";
 
 #[rustfmt::skip]
@@ -331,7 +695,7 @@ $ solxact hash https://api.devnet.solana.com
 
 The following cluster identifiers may be used to refer to specific clusters:
 
-l, localhost -- http://127.0.0.7:8899
+l, localhost -- http://127.0.0.1:8899
 d, devnet -- https://api.devnet.solana.com,
 t, testnet -- https://api.testnet.solana.com
 m, mainnet -- https://api.mainnet-beta.solana.com
@@ -341,6 +705,171 @@ testnet cluster:
 
 $ solxact hash t
 
+A recent blockhash can only be used until the cluster reaches a given block
+height, after which transactions that use it expire.  To keep that height
+alongside the transaction, give:
+
+  solxact hash [expiry <FILE>] [record] [cluster <CLUSTER>] [<RPC_URL>]
+
+where expiry writes to <FILE> a JSON object giving the blockhash, its
+last_valid_block_height, and the genesis_hash of the cluster that it came
+from.  solxact submit expiry <FILE> uses it to tell when the transaction has
+expired, and to refuse to send the transaction to a different cluster; see
+'solxact help submit'.  The last_valid_block_height is null if the RPC node
+does not provide it.
+
+If record is given, the same record is also kept in
+~/.cache/solxact/blockhashes (or $XDG_CACHE_HOME/solxact/blockhashes), under
+the name of the blockhash, and solxact submit record uses it in place of an
+expiry file.  This keeps track of the blockhash of a transaction passed
+through solxact hash, sign, and submit without keeping a file alongside it,
+but only on the system and for the user that applied it.  Records older than
+a day are removed.  Nothing is written there unless record is given.
+
+Because the mainnet cluster is used when no <RPC_URL> is given, it is easy to
+use the wrong cluster by mistake.  cluster <CLUSTER> pins the cluster that is
+expected: the RPC node's genesis hash is fetched, and if it is not that of
+<CLUSTER>, solxact hash fails.  <CLUSTER> is one of m (or mainnet, or
+mainnet-beta), t (or testnet), or d (or devnet), or the genesis hash of any
+cluster.  solxact simulate, preview, and submit accept cluster <CLUSTER> too.
+
+";
+
+#[rustfmt::skip]
+pub const NONCE_USAGE_MESSAGE : &str = "
+
+solxact nonce will read an encoded transaction from standard input and prepare
+it to use a durable nonce in place of a recent blockhash.  It inserts a System
+program AdvanceNonceAccount instruction as the first instruction of the
+transaction, and applies the nonce stored in the nonce account as the recent
+blockhash of the transaction.  It will then re-encode the transaction and write
+its encoded form to standard output.
+
+A transaction that uses a durable nonce does not expire after a short time as
+a transaction using a recent blockhash does, and so can be signed offline over
+as long a period as is needed.  The transaction remains valid until the nonce
+is advanced, which happens when the transaction, or any other transaction
+using the same nonce account, is executed.
+
+To use solxact nonce:
+
+  solxact nonce <NONCE_ACCOUNT> <NONCE_AUTHORITY> [<RPC_URL>]
+  or
+  solxact nonce value <NONCE> <NONCE_ACCOUNT> <NONCE_AUTHORITY>
+
+<NONCE_ACCOUNT> is the pubkey of the nonce account and <NONCE_AUTHORITY> is the
+pubkey of its authority, which will be required to sign the transaction.  As
+with all pubkey arguments, these are either base58-encoded pubkeys or the paths
+to key files.
+
+In the first form, the nonce is read from the nonce account as fetched from an
+RPC node.  If <RPC_URL> is not given, the standard Solana mainnet RPC node is
+used.  The following cluster identifiers may also be used:
+
+l, localhost -- http://127.0.0.1:8899
+d, devnet -- https://api.devnet.solana.com
+t, testnet -- https://api.testnet.solana.com
+m, mainnet -- https://api.mainnet-beta.solana.com
+
+In the second form, the Base58-encoded nonce is supplied directly and no RPC
+node is contacted, which allows the transaction to be prepared on a machine
+without network access.
+
+If the transaction already begins with an AdvanceNonceAccount instruction for
+the same nonce account and nonce authority, another is not added, and only the
+nonce is applied.  If that instruction is for a different nonce account or
+nonce authority, solxact nonce fails.
+
+For example, the following will create a transfer transaction that uses a
+durable nonce fetched from the devnet cluster, and then sign it:
+
+$ solxact encode encoding rust_bincode_fixedint                          \\
+                 fee_payer ./my_key.json                                 \\
+                 program 11111111111111111111111111111111                \\
+                 account ./my_key.json ws                                \\
+                 account AVheJF4ZzCZjfysZP2FHdFERY3r7dh9AdBRRcJRWKARc w  \\
+                 enum 2 [ u64 12131001000 ]                              \\
+  | solxact nonce ./nonce_account.json ./my_key.json devnet              \\
+  | solxact sign ./my_key.json
+
+";
+
+#[rustfmt::skip]
+pub const PRIORITY_FEE_USAGE_MESSAGE : &str = "
+
+solxact priority-fee will read an encoded transaction from standard input and
+set its compute unit limit, its priority fee, or both, using Compute Budget
+program SetComputeUnitLimit and SetComputeUnitPrice instructions.  It will
+then re-encode the transaction and write its encoded form to standard output.
+
+Transactions that pay a priority fee are scheduled ahead of transactions that
+do not, and so land more quickly when the cluster is busy.  The priority fee
+paid is the compute unit price multiplied by the compute unit limit.
+
+To use solxact priority-fee:
+
+  solxact priority-fee [limit <UNITS>] [price <MICRO_LAMPORTS>]
+  or
+  solxact priority-fee [limit <UNITS>] percentile <PERCENTILE> [<RPC_URL>]
+  or
+  solxact priority-fee limit auto [margin <PERCENT>]
+                       [price <MICRO_LAMPORTS> | percentile <PERCENTILE>]
+                       [<RPC_URL>]
+
+<UNITS> is the most compute units that the transaction may use, which may be
+at most 1400000.
+
+If the limit is auto, the compute unit limit is sized by simulating the
+transaction using an RPC node: the transaction is simulated with the maximum
+compute unit limit, and its limit is then set to the compute units that the
+simulation consumed plus <PERCENT> percent, which is 10 if not given.  The
+transaction is simulated using the cluster's most recent blockhash in place of
+its own, and without verifying its signatures, so it may be sized before it is
+signed and before its recent blockhash is applied.  If the simulation fails,
+the transaction is not written.
+
+<MICRO_LAMPORTS> is the compute unit price, in micro-lamports (millionths of a
+lamport) per compute unit.
+
+In the second form, the compute unit price is chosen automatically: the
+prioritization fees paid in recent slots by transactions that wrote to any of
+the accounts that this transaction writes to are fetched from an RPC node, and
+the fee at <PERCENTILE> (0 - 100) of those is used.  Writable accounts that
+are loaded from address lookup tables are included, by fetching those tables
+from the RPC node.
+
+If an RPC node is used and <RPC_URL> is not given, the standard Solana mainnet
+RPC node is used.  The following cluster identifiers
+may also be used:
+
+l, localhost -- http://127.0.0.1:8899
+d, devnet -- https://api.devnet.solana.com
+t, testnet -- https://api.testnet.solana.com
+m, mainnet -- https://api.mainnet-beta.solana.com
+
+If the transaction already has a SetComputeUnitLimit or SetComputeUnitPrice
+instruction, that instruction is replaced; otherwise the instruction is added
+to the beginning of the transaction, after any AdvanceNonceAccount instruction.
+
+Changing the transaction's instructions invalidates its signatures, which are
+removed.  Since a priority fee is best chosen just before submission, the
+recent blockhash should be applied afterwards (see solxact help hash), and
+the transaction then signed.
+
+For example, the following will give a transfer transaction a compute unit
+limit sized by simulation and a compute unit price at the 75th percentile of
+recent fees on mainnet, and then apply a recent blockhash and sign it:
+
+$ solxact encode encoding rust_bincode_fixedint                          \\
+                 fee_payer ./my_key.json                                 \\
+                 program 11111111111111111111111111111111                \\
+                 account ./my_key.json ws                                \\
+                 account AVheJF4ZzCZjfysZP2FHdFERY3r7dh9AdBRRcJRWKARc w  \\
+                 enum 2 [ u64 12131001000 ]                              \\
+  | solxact priority-fee limit auto percentile 75 mainnet                \\
+  | solxact hash mainnet                                                 \\
+  | solxact sign ./my_key.json
+
 ";
 
 #[rustfmt::skip]
@@ -357,6 +886,31 @@ For example, the following command would sign a transaction using two keys:
 
 $ solxact sign ./my_key.json ./my_admin_key.json
 
+Instead of a key file, a key may be derived from a BIP39 seed phrase, such as
+those used by wallets like Phantom and Solflare.  The seed phrase, and an
+optional passphrase, are prompted for without being echoed.  The following
+forms are accepted:
+
+  prompt://                      -- use the seed directly, without derivation
+  prompt://?key=<ACCOUNT>        -- derive along m/44'/501'/<ACCOUNT>'
+  prompt://?key=<ACCOUNT>/<CHANGE>
+                                 -- derive along m/44'/501'/<ACCOUNT>'/<CHANGE>'
+  prompt://?full-path=<PATH>     -- derive along <PATH>
+  seed:<PATH>                    -- derive along <PATH>
+
+All components of a derivation path must be hardened.  Most wallets derive
+their first account's key along m/44'/501'/0'/0', which is prompt://?key=0/0.
+
+The seed phrase must be a valid BIP39 English mnemonic: a mistyped word is
+reported as an error rather than used to derive a different key.  A keypair
+source that is given more than once, for example as both the fee payer and a
+signer, is only prompted for once.
+
+For example, the following command would sign a transaction using the key of
+a wallet's first account:
+
+$ solxact sign 'prompt://?key=0/0'
+
 ";
 
 #[rustfmt::skip]
@@ -376,6 +930,113 @@ signature.
 
 ";
 
+#[rustfmt::skip]
+pub const VERIFY_USAGE_MESSAGE : &str = "
+
+solxact verify will read an encoded transaction from standard input and check
+every signature of the transaction against the transaction's message.  For
+each required signer, a line is printed to standard output giving the signer's
+pubkey followed by one of:
+
+  valid   -- the signature is a valid ed25519 signature of the message
+  invalid -- the signature is not a valid signature of the message
+  missing -- the transaction has not yet been signed by this signer
+
+If any signature is invalid, solxact verify exits with an error.  An invalid
+signature usually means that the transaction was modified after it was signed,
+for example by applying a new recent blockhash, or that the signature was
+corrupted.  Missing signatures are not considered errors; use solxact
+show-unsigned to list only the signers that have yet to sign.
+
+For example, the following will verify the signatures of a transaction stored
+in a file before submitting it:
+
+$ cat transaction.bin | solxact verify
+
+";
+
+#[rustfmt::skip]
+pub const MERGE_USAGE_MESSAGE : &str = "
+
+solxact merge will read several encoded copies of the same transaction, each of
+which may carry a different subset of the transaction's signatures, and write a
+single encoded transaction carrying all of their signatures to standard output.
+
+This is useful when a transaction requires signatures from several signers who
+each sign their own copy of the transaction with solxact sign, possibly on
+different machines.
+
+The arguments to solxact merge are the paths of files containing the encoded
+transactions to merge.  A path of - reads a transaction from standard input.
+
+All of the transactions must have exactly the same message; that is, they must
+differ only in their signatures.  If any transaction differs from the others,
+or if two transactions carry different signatures from the same signer, then
+solxact merge fails and no transaction is written.
+
+For example, the following will merge the signatures of three copies of a
+transaction, each signed by a different signer, and submit the result:
+
+$ solxact merge ./signed_by_alice.bin ./signed_by_bob.bin ./signed_by_carol.bin \\
+  | solxact submit
+
+";
+
+#[rustfmt::skip]
+pub const MESSAGE_USAGE_MESSAGE : &str = "
+
+solxact message will read an encoded transaction from standard input and write
+the transaction's message to standard output.  The message is the portion of
+the transaction that every signer signs; it is the transaction without its
+signatures.
+
+This allows a transaction to be signed by a device that solxact cannot use
+directly, such as a hardware security module or an external wallet.  The
+resulting signatures can be added to the transaction using solxact attach.
+
+To use solxact message:
+
+  solxact message [raw | hex | base58 | base64]
+
+The optional argument selects the format the message is written in.  raw
+writes the message bytes themselves, and is the default.  hex, base58, and
+base64 write the message as a single line of text in that encoding.
+
+For example, the following will print the message of a transaction in hex:
+
+$ cat transaction.bin | solxact message hex
+
+";
+
+#[rustfmt::skip]
+pub const ATTACH_USAGE_MESSAGE : &str = "
+
+solxact attach will read an encoded transaction from standard input, add the
+signatures given as command line arguments, then re-encode and write the signed
+transaction to standard output.  solxact add-signature is another name for
+solxact attach.
+
+Each argument is of the form:
+
+  <PUBKEY>=<SIGNATURE>
+
+where <PUBKEY> is the pubkey of the signer (a base58-encoded pubkey, or the
+path to a key file), and <SIGNATURE> is the Base58-encoded signature that the
+signer produced for the transaction's message, as written by solxact message.
+
+Every signature is checked before it is added: the pubkey must be a required
+signer of the transaction, and the signature must be a valid signature of the
+transaction's message by that pubkey.  If any check fails, solxact attach fails
+and no transaction is written.
+
+For example, the following will add a signature produced by an external
+wallet:
+
+$ cat transaction.bin                                                    \\
+  | solxact attach AVheJF4ZzCZjfysZP2FHdFERY3r7dh9AdBRRcJRWKARc=<SIGNATURE>
+
+";
+
 #[rustfmt::skip]
 pub const SIMULATE_USAGE_MESSAGE : &str = "
 
@@ -383,9 +1044,16 @@ solxact simulate will read an encoded transaction from standard input and
 simulate its execution for a given cluster.  On success, it will print to standard
 output the encoded transaction.  On failure it will print to stderr the failure.
 
-If no arguments are passed to solxact simulate, then the mainnet cluster will
-be used for the simulation.  If a single argument is passed to solxact
-simulate, it is the URL of the RPC node to be used to perform the simulation.
+To use solxact simulate:
+
+  solxact simulate [report] [sig-verify] [replace-blockhash]
+                   [commitment <COMMITMENT>] [min-context-slot <SLOT>]
+                   [account <PUBKEY>]... [cluster <CLUSTER>]
+                   [error-table <PROGRAM_ID> <FILE>]... [<RPC_URL>]
+
+If no <RPC_URL> is passed to solxact simulate, then the mainnet cluster will be
+used for the simulation.  Otherwise, it is the URL of the RPC node to be used
+to perform the simulation.
 
 For example, the following will simulate the transaction execution on the
 devnet cluster:
@@ -394,7 +1062,7 @@ $ solxact simulate https://api.devnet.solana.com
 
 The following cluster identifiers may be used to refer to specific clusters:
 
-l, localhost -- http://127.0.0.7:8899
+l, localhost -- http://127.0.0.1:8899
 d, devnet -- https://api.devnet.solana.com,
 t, testnet -- https://api.testnet.solana.com
 m, mainnet -- https://api.mainnet-beta.solana.com
@@ -404,8 +1072,153 @@ cluster:
 
 $ solxact simulate t
 
-Note that transactions that are simulated do not need to be signed or have
-their most recent blockhash applied.
+If report is given, then instead of the transaction, a JSON report of the
+simulation is printed to standard output.  The report is printed even if the
+simulation fails, in which case the failure is also printed to stderr.  The
+report gives:
+
+  err            -- the error that the transaction failed with, or null
+  error          -- a description of that error, or null; see below
+  logs           -- the log messages of the programs that were invoked
+  invocations    -- the program invocations recorded in the logs; see below
+  units_consumed -- the compute units that the transaction consumed
+  return_data    -- the program_id of the program that last set return data,
+                    and that data, hex-encoded; or null if there was none
+  accounts       -- for each account given with account, its address and its
+                    state after the simulation: lamports, owner, executable,
+                    rent_epoch, and hex-encoded data; or null if the account
+                    does not exist
+
+The following options are passed to the RPC node's simulation:
+
+  sig-verify                -- verify the transaction's signatures
+  replace-blockhash         -- simulate using the cluster's most recent
+                               blockhash in place of the transaction's own;
+                               cannot be used with sig-verify
+  commitment <COMMITMENT>   -- the commitment level of the bank to simulate
+                               against: processed, confirmed, or finalized
+  min-context-slot <SLOT>   -- the minimum slot that the simulation may be
+                               evaluated at
+  account <PUBKEY>          -- an account whose state after the simulation
+                               is included in the report; may be given more
+                               than once
+
+If cluster <CLUSTER> is given, the simulation fails unless the RPC node
+belongs to <CLUSTER>; see 'solxact help hash'.
+
+The invocations of the report are a tree of the programs that the transaction
+invoked, each giving:
+
+  program_id             -- the program that was invoked
+  program_name           -- the name of the program, if it is well-known
+  compute_units_consumed -- the compute units that the invocation consumed
+  result                 -- success, failed, or incomplete if the logs end
+                            before the invocation does
+  error                  -- if the invocation failed, why; and custom_name,
+                            the name of its custom error, if known
+  logs                   -- the messages that the program logged
+  data                   -- the data that the program logged, hex-encoded
+  return_data            -- the data that the program returned, hex-encoded
+  invocations            -- the invocations that the program made
+
+The error of the report describes why the transaction failed.  If an
+instruction failed, it gives the instruction_index of the instruction, its
+program_id, program_name and instruction_name if they are well-known, the
+error_program_id of the program that the error came from, and the error; if
+the error is a custom program error, its custom_code, and its custom_name if
+it is known.  The program that the error came from is that of the deepest
+invocation that failed within the instruction, as recorded in the logs, and
+is the instruction's own program only when there are no logs.  The error also
+gives a message, which is what is printed to stderr, for example:
+
+  Instruction 1 (Token Transfer) failed: custom program error 1
+  (InsufficientFunds)
+
+  Instruction 2 (JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4) failed in
+  Token: custom program error 1 (InsufficientFunds)
+
+The names of the custom errors of the System, Stake, Vote, Token, Token-2022,
+and Associated Token Account programs are known.  The names of the custom
+errors of other programs may be given with:
+
+  error-table <PROGRAM_ID> <FILE>
+
+where <FILE> is a JSON file that either maps each error code to its name, for
+example { \"6000\" : \"Unauthorized\", \"0x1771\" : \"Expired\" }, or is an Anchor
+IDL of the program, whose errors give the code and name of each error.  It
+may be given more than once, and names that it gives take precedence over
+those that are known.
+
+Note that transactions that are simulated do not need to be signed, and with
+replace-blockhash do not need to have their most recent blockhash applied.
+
+For example, the following will simulate a transaction that has not had a
+recent blockhash applied on devnet, and print its logs:
+
+$ solxact simulate report replace-blockhash devnet < transaction.bin       \\
+  | jq .logs
+
+";
+
+#[rustfmt::skip]
+pub const PREVIEW_USAGE_MESSAGE : &str = "
+
+solxact preview will read an encoded transaction from standard input, simulate
+its execution for a given cluster, and print to standard output a JSON report
+of how it would change the balances of the accounts that it writes.  It is
+meant to be used to check what a transaction does before signing it.
+
+To use solxact preview:
+
+  solxact preview [commitment <COMMITMENT>] [cluster <CLUSTER>]
+                  [error-table <PROGRAM_ID> <FILE>]... [<RPC_URL>]
+
+If no <RPC_URL> is passed to solxact preview, then the mainnet cluster will be
+used.  Otherwise, it is the URL of the RPC node to be used, or one of the
+cluster identifiers l, d, t, or m as described in 'solxact help simulate'.
+
+The current state of each writable account of the transaction is fetched, and
+then the transaction is simulated, with its state after the simulation
+returned.  The transaction does not need to be signed, and is simulated using
+the cluster's most recent blockhash in place of its own, so it does not need
+to have a recent blockhash applied either.  commitment gives the commitment
+level of the bank to use: processed, confirmed, or finalized.  If cluster
+<CLUSTER> is given, preview fails unless the RPC node belongs to <CLUSTER>;
+see 'solxact help hash'.
+
+Writable accounts that are loaded from address lookup tables are included,
+after the transaction's own writable accounts; the lookup tables are fetched
+from the RPC node to find them.
+
+The report gives:
+
+  err            -- the error that the simulation failed with, or null
+  error          -- a description of that error, or null; error-table gives
+                    the names of custom errors, as for 'solxact simulate'
+  units_consumed -- the compute units that the transaction consumed
+  accounts       -- the balance changes of each writable account, see below
+
+Each of the accounts gives:
+
+  address         -- the address of the account
+  exists_before   -- whether the account exists before the transaction
+  exists_after    -- whether the account exists after the transaction
+  lamports_before -- the lamports of the account before the transaction
+  lamports_after  -- the lamports of the account after the transaction
+  lamport_delta   -- lamports_after less lamports_before
+  token           -- if the account is a token account of the Token or
+                     Token-2022 program before or after the transaction, its
+                     mint, its owner, and its amount_before, amount_after,
+                     and amount_delta, in the token's smallest units; or null
+
+An account that does not exist has no lamports and no tokens.  If the
+simulation fails, the report gives no balance changes, although the fee
+payer would still be charged the transaction's fee; the report is still
+printed, and the failure is printed to stderr.
+
+For example, the following will preview a transaction on devnet:
+
+$ solxact preview devnet < transaction.bin
 
 ";
 
@@ -417,9 +1230,25 @@ it to a cluster for execution. It will wait for the transaction to be executed
 and then print to standard out the json-encoded results of simulating the
 transaction.
 
-If no arguments are passed to solxact submit, then the mainnet cluster will be
-used for the submission.  If a single argument is passed to solxact submit, it
-is the URL of the RPC node to be used to submit the transaction to a cluster.
+To use solxact submit:
+
+  solxact submit [report] [no-wait] [commitment <COMMITMENT>]
+                 [timeout <SECONDS>] [skip-preflight]
+                 [preflight-commitment <COMMITMENT>] [max-retries <COUNT>]
+                 [rebroadcast <SECONDS>] [expiry <FILE> | record]
+                 [cluster <CLUSTER>]
+                 [error-table <PROGRAM_ID> <FILE>]... [<RPC_URL>]...
+
+If no <RPC_URL> is passed to solxact submit, then the mainnet cluster will be
+used for the submission.  Otherwise, it is the URL of the RPC node to be used
+to submit the transaction to a cluster.
+
+More than one <RPC_URL> may be given, in which case the transaction is sent
+to all of them in parallel, and then tracked through whichever of them first
+returns its signature.  Re-sent transactions are sent to all of them too.
+This lets a transaction land even if some of the RPC nodes are degraded.  If
+none of them returns a signature, the failure of the first that rejected the
+transaction is reported, or if none did, the first failure.
 
 For example, the following will execute the transaction on the devnet cluster:
 
@@ -427,7 +1256,7 @@ $ solxact submit https://api.devnet.solana.com
 
 The following cluster identifiers may be used to refer to specific clusters:
 
-l, localhost -- http://127.0.0.7:8899
+l, localhost -- http://127.0.0.1:8899
 d, devnet -- https://api.devnet.solana.com
 t, testnet -- https://api.testnet.solana.com
 m, mainnet -- https://api.mainnet-beta.solana.com
@@ -436,6 +1265,68 @@ For example, the following will submit the transaction to the testnet cluster:
 
 $ solxact submit testnet
 
+And the following will submit the transaction through two RPC providers:
+
+$ solxact submit https://rpc.example.com https://rpc.example.org
+
+After sending the transaction, solxact submit waits until it reaches a
+commitment level, or until a timeout passes, in which case it fails.  The
+following options control sending and waiting:
+
+  commitment <COMMITMENT>   -- the commitment level to wait for: processed,
+                               confirmed, or finalized; the default is
+                               finalized
+  timeout <SECONDS>         -- how long to wait, from when the transaction is
+                               sent; the default is 120
+  no-wait                   -- do not wait; print the transaction signature
+                               as soon as the transaction is sent
+  skip-preflight            -- the RPC node does not simulate the transaction
+                               before sending it
+  preflight-commitment <COMMITMENT>
+                            -- the commitment level of the bank that the
+                               RPC node's preflight simulation uses
+  max-retries <COUNT>       -- how many times the RPC node retries sending
+                               the transaction; by default it retries until
+                               the transaction is finalized or its blockhash
+                               expires
+  rebroadcast <SECONDS>     -- how often to re-send the transaction until
+                               it is executed, in case it was dropped before
+                               reaching the leader; the default is 2, and 0
+                               never re-sends it
+  expiry <FILE>             -- the expiry file written by solxact hash expiry
+                               when the transaction's recent blockhash was
+                               applied; once the block height passes the last
+                               valid block height that it gives, without the
+                               transaction having been executed, submit fails
+                               with an expired error
+  record                    -- use the record kept by solxact hash record
+                               for the transaction's recent blockhash in
+                               place of an expiry file; submit fails if there
+                               is no such record
+  cluster <CLUSTER>         -- the cluster that the transaction is meant for;
+                               see 'solxact help hash'
+
+Before the transaction is sent, the genesis hash of every RPC node is checked
+against that of the cluster given by cluster, and that of the cluster that
+the recent blockhash came from, as recorded in the expiry file or the record
+given by record.  If any RPC node belongs to a different cluster, the
+transaction is not sent.  Without expiry, record, or cluster, the cluster of
+the RPC nodes is only checked against that of any profile they come from.
+
+If the transaction fails, either in the RPC node's preflight simulation or
+once executed, a description of its failure is printed to stderr, in the same
+way as for solxact simulate; error-table gives the names of custom errors in
+the same way too.  See 'solxact help simulate'.
+
+If report is given, then instead of the transaction signature, a JSON report
+of the execution is printed to standard output, giving the signature, the
+slot that the transaction was executed in, and the err, error, logs,
+invocations, and units_consumed of the execution as for simulate report.  If
+the transaction failed preflight simulation, the signature and slot are null.
+The execution can only be fetched once the transaction is confirmed, so with
+commitment processed, report waits for confirmed.  report cannot be used with
+no-wait.
+
 Note that transactions that are submitted must have a valid recent blockhash
 supplied (e.g. via solxact hash) and be signed (e.g. via solxact sign).
 
@@ -527,6 +1418,8 @@ is one of:
 - The path to a file containing a JSON formatted array whose contents is a
   keypair from which the pubkey will be extracted
 - A Base58-encoded pubkey
+- A BIP39 seed phrase keypair source, as described by solxact help sign, in
+  which case the seed phrase will be prompted for
 
 
 For example, to print out the Base58-encoded pubkey for a keypair file:
@@ -542,4 +1435,66 @@ To convert a pubkey from Base58 to Base64:
 
 $ solxact pubkey base64 metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s
 
+To print out the pubkey of a wallet's first account from its seed phrase:
+
+$ solxact pubkey 'prompt://?key=0/0'
+
+";
+
+#[rustfmt::skip]
+pub const KEYGEN_USAGE_MESSAGE : &str = "
+
+solxact keygen will generate a new keypair and write it to a file in the Solana
+json key file format, which is the format read by all solxact commands that
+accept key files.  The pubkey of the new keypair is printed to standard output.
+
+To use solxact keygen:
+
+  solxact keygen [force] [mnemonic <WORD_COUNT>] [prefix <PREFIX>]
+                 [suffix <SUFFIX>] [ignore-case] [threads <COUNT>] <PATH>
+
+<PATH> is the path of the key file to write.  solxact keygen will not
+overwrite an existing file unless force is given.
+
+The remaining optional arguments are:
+
+  mnemonic <WORD_COUNT>
+
+      Generates the keypair from a new random BIP39 mnemonic of <WORD_COUNT>
+      words, which must be one of 12, 15, 18, 21, or 24.  The mnemonic is
+      printed to standard output on the line following the pubkey.  The
+      mnemonic has no passphrase and the keypair is made from its seed without
+      derivation, as the Solana command line tools do, so the keypair can be
+      recovered from the mnemonic using the prompt:// keypair source described
+      by solxact help sign.
+
+  prefix <PREFIX>
+  suffix <SUFFIX>
+
+      Searches for a \"vanity\" keypair whose Base58-encoded pubkey begins
+      with <PREFIX> and/or ends with <SUFFIX>, by repeatedly generating
+      keypairs until a matching one is found.  Each additional character
+      makes the search take about 58 times longer.  While searching, the
+      number of keypairs searched and the rate of search are periodically
+      printed to standard error.
+
+  ignore-case
+
+      Matches <PREFIX> and <SUFFIX> without regard to case, which makes the
+      search much faster.
+
+  threads <COUNT>
+
+      The number of threads to search with; the default is the number of
+      CPUs.
+
+For example, the following will generate a new keypair in ./my_key.json:
+
+$ solxact keygen ./my_key.json
+
+The following will search for a keypair whose pubkey begins with \"Sol\", and
+will also generate a 24 word mnemonic for it:
+
+$ solxact keygen mnemonic 24 prefix Sol ./my_vanity_key.json
+
 ";